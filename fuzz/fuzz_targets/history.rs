@@ -0,0 +1,17 @@
+#![no_main]
+
+//! Drives `History` with steps decoded from fuzzer input and compares it with reference model.
+//! Run with `cargo fuzz run history` from the repository root.
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = "../../tests/history_model/mod.rs"]
+mod history_model;
+
+fuzz_target!(|data: &[u8]| {
+    let (options, steps) = history_model::decode(data);
+    if let Err(e) = history_model::Harness::run(options, &steps) {
+        panic!("{e}");
+    }
+});
@@ -1,10 +1,19 @@
 use std::ops::RangeInclusive;
 
-use chrono::{Datelike, Months, NaiveDate, NaiveDateTime, SubsecRound, TimeDelta, Timelike};
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
 use egui_plot::{AxisHints, GridInput, GridMark};
 
 use crate::{importer::DateTimeInfo, types::datetime_f64_mapper::DateTimeF64Mapper};
 
+/// Calendar unit, to which x can be snapped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalendarUnit {
+    Minute,
+    Hour,
+    Day,
+    Month,
+}
+
 pub struct DateTimeCtx {
     pub info: DateTimeInfo,
     base_steps: Box<[f64]>,
@@ -46,6 +55,63 @@ impl DateTimeCtx {
         }
     }
 
+    /// Formats x as date and time in format of imported data.
+    pub fn label(&self, x: f64) -> String {
+        self.info
+            .mapper
+            .f64_to_time(x)
+            .format(&self.info.format)
+            .to_string()
+    }
+
+    /// Time, by which timestamps of later points move, when `count` points are inserted before them.
+    pub fn insert_shift(&self, count: usize) -> TimeDelta {
+        self.info.mapper.f64_to_time_delta(count as f64)
+    }
+
+    /// Start of `unit` nearest to x.
+    pub fn snap(&self, x: f64, unit: CalendarUnit) -> f64 {
+        let mapper = &self.info.mapper;
+        let time = mapper.f64_to_time(x);
+        let day = time.date().and_time(NaiveTime::MIN);
+        let (start, next) = match unit {
+            CalendarUnit::Minute => {
+                let start = day + TimeDelta::minutes((time - day).num_minutes());
+                (start, start + TimeDelta::minutes(1))
+            }
+            CalendarUnit::Hour => {
+                let start = day + TimeDelta::hours((time - day).num_hours());
+                (start, start + TimeDelta::hours(1))
+            }
+            CalendarUnit::Day => (day, day + TimeDelta::days(1)),
+            CalendarUnit::Month => {
+                let start = day.with_day(1).unwrap();
+                (start, start.checked_add_months(Months::new(1)).unwrap())
+            }
+        };
+        if time - start <= next - time {
+            mapper.time_to_f64(&start)
+        } else {
+            mapper.time_to_f64(&next)
+        }
+    }
+
+    /// X moved by `count` of `unit`, negative count moves back.
+    pub fn shift(&self, x: f64, unit: CalendarUnit, count: i32) -> f64 {
+        let mapper = &self.info.mapper;
+        let time = mapper.f64_to_time(x);
+        let time = match unit {
+            CalendarUnit::Minute => Some(time + TimeDelta::minutes(count.into())),
+            CalendarUnit::Hour => Some(time + TimeDelta::hours(count.into())),
+            CalendarUnit::Day => Some(time + TimeDelta::days(count.into())),
+            CalendarUnit::Month if count >= 0 => {
+                time.checked_add_months(Months::new(count.unsigned_abs()))
+            }
+            CalendarUnit::Month => time.checked_sub_months(Months::new(count.unsigned_abs())),
+        };
+        time.map_or(x, |time| mapper.time_to_f64(&time))
+    }
+
     pub fn x_axes(&self, base_step_size: f64) -> Vec<AxisHints> {
         let mapper = &self.info.mapper;
         let base_steps = self.base_steps();
@@ -0,0 +1,93 @@
+use std::{fmt, fs::File, io, path::Path};
+
+use csv::Writer;
+
+use crate::{functions::function::Func, importer::DateTimeInfo, types::point::X};
+
+#[derive(Debug)]
+pub enum ExporterError {
+    IOError(io::Error),
+    CSVError(csv::Error),
+}
+
+impl fmt::Display for ExporterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IOError(e) => write!(f, "can not write file: {}", e),
+            Self::CSVError(e) => write!(f, "can not write csv: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for ExporterError {
+    fn from(value: io::Error) -> Self {
+        Self::IOError(value)
+    }
+}
+
+impl From<csv::Error> for ExporterError {
+    fn from(value: csv::Error) -> Self {
+        Self::CSVError(value)
+    }
+}
+
+pub struct Exporter;
+
+impl Exporter {
+    pub fn export<'a>(
+        path: impl AsRef<Path>,
+        columns: impl IntoIterator<Item = (&'a str, &'a Func)>,
+        date_time: Option<&DateTimeInfo>,
+    ) -> Result<(), ExporterError> {
+        Self::write(File::create(path)?, columns, date_time)
+    }
+
+    /// Writes functions as columns named by the first item of `columns`, after column of x.
+    /// X is written as date, if `date_time` is given. Missing values and x, at which
+    /// function has no point, are written as empty cells.
+    pub fn write<'a>(
+        writer: impl io::Write,
+        columns: impl IntoIterator<Item = (&'a str, &'a Func)>,
+        date_time: Option<&DateTimeInfo>,
+    ) -> Result<(), ExporterError> {
+        let (names, funcs): (Vec<_>, Vec<_>) = columns.into_iter().unzip();
+        let mut csv = Writer::from_writer(writer);
+        let x_name = if date_time.is_some() { "datum" } else { "x" };
+        csv.write_record([x_name].into_iter().chain(names))?;
+
+        let points = funcs.iter().map(|f| f.points());
+        let start = points.clone().filter_map(|p| p.first()).map(|p| p.x);
+        let end = points.filter_map(|p| p.last()).map(|p| p.x);
+        let (Some(start), Some(end)) = (start.reduce(X::min), end.reduce(X::max)) else {
+            return Ok(csv.flush()?);
+        };
+        let mut record = Vec::with_capacity(funcs.len() + 1);
+        let mut x = start;
+        while x <= end {
+            record.clear();
+            record.push(match date_time {
+                Some(info) => info.mapper.f64_to_time(x).format(&info.format).to_string(),
+                None => x.to_string(),
+            });
+            for func in &funcs {
+                let y = Self::value_at(func, x).filter(|y| !y.is_nan());
+                record.push(y.map_or(String::new(), |y| y.to_string()));
+            }
+            csv.write_record(&record)?;
+            x += 1.0;
+        }
+        Ok(csv.flush()?)
+    }
+
+    fn value_at(func: &Func, x: X) -> Option<f64> {
+        let points = func.points();
+        let index = (x - points.first()?.x).round();
+        if index < 0.0 {
+            return None;
+        }
+        points
+            .get(index as usize)
+            .filter(|p| (p.x - x).abs() < 0.5)
+            .map(|p| p.y)
+    }
+}
@@ -0,0 +1,440 @@
+use std::{fmt, ops::RangeInclusive};
+
+use crate::types::point::{Point, X, Y};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionError {
+    /// Position in text and the character
+    UnexpectedChar(usize, char),
+    UnexpectedEnd,
+    UnknownName(String),
+    UnknownFunction(String),
+    /// Name of function and number of arguments it was called with
+    WrongArgCount(String, usize),
+    /// Result at x is infinite or NaN
+    NotFinite(X),
+    /// Source function has no point at x
+    MissingPoint(X),
+    /// Source functions do not share any x
+    NoPoints,
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedChar(pos, c) => write!(f, "unexpected '{}' at {}", c, pos),
+            Self::UnexpectedEnd => write!(f, "unexpected end of expression"),
+            Self::UnknownName(name) => write!(f, "unknown function or constant '{}'", name),
+            Self::UnknownFunction(name) => write!(f, "unknown math function '{}'", name),
+            Self::WrongArgCount(name, count) => {
+                write!(f, "'{}' can not be called with {} arguments", name, count)
+            }
+            Self::NotFinite(x) => write!(f, "result is not finite at x {}", x),
+            Self::MissingPoint(x) => write!(f, "source function has no point at x {}", x),
+            Self::NoPoints => write!(f, "source functions have no common x"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MathFn {
+    Abs,
+    Sqrt,
+    Exp,
+    Ln,
+    Log10,
+    Sin,
+    Cos,
+    Tan,
+    Floor,
+    Ceil,
+    Round,
+    Min,
+    Max,
+    Pow,
+}
+
+impl MathFn {
+    fn from_name(name: &str) -> Option<Self> {
+        let f = match name {
+            "abs" => Self::Abs,
+            "sqrt" => Self::Sqrt,
+            "exp" => Self::Exp,
+            "ln" => Self::Ln,
+            "log10" => Self::Log10,
+            "sin" => Self::Sin,
+            "cos" => Self::Cos,
+            "tan" => Self::Tan,
+            "floor" => Self::Floor,
+            "ceil" => Self::Ceil,
+            "round" => Self::Round,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "pow" => Self::Pow,
+            _ => return None,
+        };
+        Some(f)
+    }
+
+    fn arg_count(&self) -> usize {
+        match self {
+            Self::Min | Self::Max | Self::Pow => 2,
+            _ => 1,
+        }
+    }
+
+    fn apply(&self, args: &[Y]) -> Y {
+        match self {
+            Self::Abs => args[0].abs(),
+            Self::Sqrt => args[0].sqrt(),
+            Self::Exp => args[0].exp(),
+            Self::Ln => args[0].ln(),
+            Self::Log10 => args[0].log10(),
+            Self::Sin => args[0].sin(),
+            Self::Cos => args[0].cos(),
+            Self::Tan => args[0].tan(),
+            Self::Floor => args[0].floor(),
+            Self::Ceil => args[0].ceil(),
+            Self::Round => args[0].round(),
+            Self::Min => args[0].min(args[1]),
+            Self::Max => args[0].max(args[1]),
+            Self::Pow => args[0].powf(args[1]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Const(Y),
+    /// X of evaluated point
+    X,
+    /// Index into sources of expression
+    Source(usize),
+    Neg(Box<Node>),
+    Binary(BinOp, Box<Node>, Box<Node>),
+    Call(MathFn, Vec<Node>),
+}
+
+impl Node {
+    fn eval(&self, x: X, values: &[Y]) -> Y {
+        match self {
+            Node::Const(c) => *c,
+            Node::X => x,
+            Node::Source(i) => values[*i],
+            Node::Neg(n) => -n.eval(x, values),
+            Node::Binary(op, a, b) => {
+                let (a, b) = (a.eval(x, values), b.eval(x, values));
+                match op {
+                    BinOp::Add => a + b,
+                    BinOp::Sub => a - b,
+                    BinOp::Mul => a * b,
+                    BinOp::Div => a / b,
+                    BinOp::Pow => a.powf(b),
+                }
+            }
+            Node::Call(f, args) => {
+                let args = args.iter().map(|a| a.eval(x, values)).collect::<Vec<_>>();
+                f.apply(&args)
+            }
+        }
+    }
+}
+
+/// Arithmetic expression over functions, constants and math functions, e.g. `A - 0.5 * "B 2"`.
+///
+/// Functions are referred by name, which is either an identifier or any text in double quotes.
+/// `x` is x of evaluated point, `pi` and `e` are constants.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    text: String,
+    /// Ids of functions, which expression uses
+    sources: Vec<usize>,
+    root: Node,
+}
+
+impl Expression {
+    /// `resolve` maps name of function to its id.
+    pub fn parse(
+        text: &str,
+        resolve: impl Fn(&str) -> Option<usize>,
+    ) -> Result<Self, ExpressionError> {
+        let mut parser = Parser {
+            chars: text.char_indices().collect(),
+            pos: 0,
+            sources: Vec::new(),
+            resolve: &resolve,
+        };
+        let root = parser.expr()?;
+        parser.skip_whitespace();
+        if let Some((pos, c)) = parser.peek() {
+            return Err(ExpressionError::UnexpectedChar(pos, c));
+        }
+        Ok(Self {
+            text: text.to_string(),
+            sources: parser.sources,
+            root,
+        })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn sources(&self) -> &[usize] {
+        &self.sources
+    }
+
+    /// Evaluates expression at each x shared by all `sources`, which are points of functions
+    /// in order of `Expression::sources`. If expression has no sources, it is evaluated on
+    /// `x_range`. Returns x of first value and values.
+    pub fn evaluate(
+        &self,
+        sources: &[&[Point]],
+        x_range: Option<RangeInclusive<X>>,
+    ) -> Result<(X, Vec<Y>), ExpressionError> {
+        let mut range = if sources.is_empty() { x_range } else { None };
+        for points in sources {
+            let (first, last) = match (points.first(), points.last()) {
+                (Some(first), Some(last)) => (first.x, last.x),
+                _ => return Err(ExpressionError::NoPoints),
+            };
+            range = Some(match range {
+                Some(range) => range.start().max(first)..=range.end().min(last),
+                None => first..=last,
+            });
+        }
+        let range = match range {
+            Some(range) if range.start() <= range.end() => range,
+            _ => return Err(ExpressionError::NoPoints),
+        };
+        let (start, len) = (*range.start(), (range.end() - range.start()) as usize + 1);
+        let mut values = vec![0.0; sources.len()];
+        let mut result = Vec::with_capacity(len);
+        for i in 0..len {
+            let x = start + i as X;
+            for (value, points) in values.iter_mut().zip(sources) {
+                *value = Self::value_at(points, x).ok_or(ExpressionError::MissingPoint(x))?;
+            }
+            let y = self.root.eval(x, &values);
+            // Missing source value makes the result missing.
+            if !y.is_finite() && !values.iter().any(|v| v.is_nan()) {
+                return Err(ExpressionError::NotFinite(x));
+            }
+            result.push(y);
+        }
+        Ok((start, result))
+    }
+
+    fn value_at(points: &[Point], x: X) -> Option<Y> {
+        // Points are usually at consecutive x, so direct index is tried first.
+        let index = (x - points.first()?.x) as usize;
+        match points.get(index) {
+            Some(p) if p.x == x => Some(p.y),
+            _ => points
+                .binary_search_by(|p| p.x.total_cmp(&x))
+                .ok()
+                .map(|i| points[i].y),
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: Vec<(usize, char)>,
+    pos: usize,
+    sources: Vec<usize>,
+    resolve: &'a dyn Fn(&str) -> Option<usize>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<(usize, char)> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some((_, c)) if c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips whitespace and consumes `c`, if it is next.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if matches!(self.peek(), Some((_, n)) if n == c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn unexpected(&self) -> ExpressionError {
+        match self.peek() {
+            Some((pos, c)) => ExpressionError::UnexpectedChar(pos, c),
+            None => ExpressionError::UnexpectedEnd,
+        }
+    }
+
+    fn expr(&mut self) -> Result<Node, ExpressionError> {
+        let mut node = self.term()?;
+        loop {
+            let op = if self.eat('+') {
+                BinOp::Add
+            } else if self.eat('-') {
+                BinOp::Sub
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(op, Box::new(node), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Node, ExpressionError> {
+        let mut node = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                BinOp::Mul
+            } else if self.eat('/') {
+                BinOp::Div
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(op, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, ExpressionError> {
+        if self.eat('-') {
+            return Ok(Node::Neg(Box::new(self.unary()?)));
+        }
+        let base = self.atom()?;
+        if self.eat('^') {
+            // Power is right associative and binds tighter than unary minus on its left.
+            return Ok(Node::Binary(
+                BinOp::Pow,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Node, ExpressionError> {
+        if self.eat('(') {
+            let node = self.expr()?;
+            if !self.eat(')') {
+                return Err(self.unexpected());
+            }
+            return Ok(node);
+        }
+        if self.eat('"') {
+            let start = self.pos;
+            while matches!(self.peek(), Some((_, c)) if c != '"') {
+                self.pos += 1;
+            }
+            let name = self.text(start);
+            if !self.eat('"') {
+                return Err(ExpressionError::UnexpectedEnd);
+            }
+            return self.source(name);
+        }
+        match self.peek() {
+            Some((_, c)) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some((_, c)) if c.is_alphabetic() || c == '_' => {
+                let start = self.pos;
+                while matches!(self.peek(), Some((_, c)) if c.is_alphanumeric() || c == '_') {
+                    self.pos += 1;
+                }
+                let name = self.text(start);
+                if self.eat('(') {
+                    self.call(name)
+                } else {
+                    self.name(name)
+                }
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn text(&self, start: usize) -> String {
+        self.chars[start..self.pos].iter().map(|(_, c)| c).collect()
+    }
+
+    fn number(&mut self) -> Result<Node, ExpressionError> {
+        let start = self.pos;
+        while matches!(self.peek(), Some((_, c)) if c.is_ascii_digit() || c == '.') {
+            self.pos += 1;
+        }
+        // Exponent, e.g. 1e-3
+        if matches!(self.peek(), Some((_, 'e' | 'E'))) {
+            let mark = self.pos;
+            self.pos += 1;
+            if matches!(self.peek(), Some((_, '+' | '-'))) {
+                self.pos += 1;
+            }
+            if matches!(self.peek(), Some((_, c)) if c.is_ascii_digit()) {
+                while matches!(self.peek(), Some((_, c)) if c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+            } else {
+                self.pos = mark;
+            }
+        }
+        let text = self.text(start);
+        text.parse()
+            .map(Node::Const)
+            .map_err(|_| ExpressionError::UnexpectedChar(self.chars[start].0, self.chars[start].1))
+    }
+
+    fn name(&mut self, name: String) -> Result<Node, ExpressionError> {
+        if (self.resolve)(&name).is_some() {
+            return self.source(name);
+        }
+        match name.as_str() {
+            "x" => Ok(Node::X),
+            "pi" => Ok(Node::Const(std::f64::consts::PI)),
+            "e" => Ok(Node::Const(std::f64::consts::E)),
+            _ => Err(ExpressionError::UnknownName(name)),
+        }
+    }
+
+    fn source(&mut self, name: String) -> Result<Node, ExpressionError> {
+        let id = (self.resolve)(&name).ok_or(ExpressionError::UnknownName(name))?;
+        let index = match self.sources.iter().position(|s| *s == id) {
+            Some(index) => index,
+            None => {
+                self.sources.push(id);
+                self.sources.len() - 1
+            }
+        };
+        Ok(Node::Source(index))
+    }
+
+    fn call(&mut self, name: String) -> Result<Node, ExpressionError> {
+        let f = MathFn::from_name(&name).ok_or(ExpressionError::UnknownFunction(name.clone()))?;
+        let mut args = Vec::new();
+        if !self.eat(')') {
+            loop {
+                args.push(self.expr()?);
+                if self.eat(')') {
+                    break;
+                }
+                if !self.eat(',') {
+                    return Err(self.unexpected());
+                }
+            }
+        }
+        if args.len() != f.arg_count() {
+            return Err(ExpressionError::WrongArgCount(name, args.len()));
+        }
+        Ok(Node::Call(f, args))
+    }
+}
@@ -1,32 +1,48 @@
+pub mod fit;
 pub mod func_builder;
 pub mod func_check_iter;
+pub mod func_diff;
 pub mod func_range;
 pub mod func_range_delete;
 pub mod func_safe_copy_iter;
 pub mod func_safe_iter;
 pub mod func_values_check_iter;
+pub mod gap_fill;
+pub mod outliers;
+pub mod resample;
 pub mod selection;
+pub mod spectrum;
 pub mod stats;
+pub mod transform;
 
 use egui_plot::{Line, PlotPoints};
 use enumflags2::{bitflags, BitFlags};
+use fit::{Fit, FitError, FitModel};
 use func_range::FuncRange;
 use func_safe_copy_iter::FuncSafeCopyIter;
+use func_values_check_iter::FuncValuesCheckIter;
+use outliers::OutlierDetector;
+use resample::Resample;
 use selection::Selection;
+use spectrum::{Filter, Spectrum, Window};
 use stats::Stats;
 use std::{
+    fmt,
     ops::{Range, RangeInclusive},
     usize,
 };
+use transform::Transform;
 
 use func_range_delete::FuncRangeDelete;
 
 use crate::{
-    stretchers::y_stretcher::YStretcherFlags,
+    stretchers::{
+        stretch_profile::StretchProfile,
+        y_stretcher::{StretchBaseline, YStretcherFlags},
+    },
     types::{
         func_line::FuncLine,
         point::{vector, Point, X, Y},
-        skip_end_iterator::SkipEndIterator,
     },
 };
 
@@ -43,6 +59,19 @@ pub enum FuncInsertMode {
     Values,
 }
 
+/// How inserted values make room for themselves among existing points.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum InsertPolicy {
+    /// Points after insertion are moved by number of inserted values.
+    #[default]
+    Shift,
+    /// Inserted values overwrite points after insertion, x of other points does not change.
+    Overwrite,
+    /// Inserted values and points from insertion to end of selection are resampled
+    /// into the span of these points, so x of points after selection does not change.
+    Stretch,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum StretchType {
     Expand,
@@ -54,6 +83,11 @@ pub enum StretchType {
 pub struct StretchY {
     pub factor: Y,
     pub flags: BitFlags<YStretcherFlags>,
+    /// Factor at each point is `factor` to the power of weight of the profile,
+    /// so stretch with inverse factor reverts it exactly.
+    pub profile: StretchProfile,
+    /// Line, from which distances of points are stretched.
+    pub baseline: StretchBaseline,
 }
 
 impl StretchY {
@@ -78,14 +112,18 @@ impl StretchY {
         }
     }
 
+    /// Stretch with profile other than constant needs positive factor.
     pub fn stretches(&self) -> bool {
         !Self::is_not_stretch(self.factor, self.flags)
+            && (self.profile.is_constant() || self.factor > 0.0)
     }
 
     pub fn no_stretch() -> Self {
         Self {
             factor: 1.0,
             flags: BitFlags::empty(),
+            profile: StretchProfile::Constant,
+            baseline: StretchBaseline::Chord,
         }
     }
 
@@ -93,18 +131,72 @@ impl StretchY {
         if Self::is_not_stretch(factor, flags) {
             return None;
         }
-        Some(Self { factor, flags })
+        Some(Self {
+            factor,
+            flags,
+            profile: StretchProfile::Constant,
+            baseline: StretchBaseline::Chord,
+        })
+    }
+
+    /// Returns `None` if the stretch with `profile` does not stretch.
+    pub fn with_profile(self, profile: StretchProfile) -> Option<Self> {
+        Some(Self { profile, ..self }).filter(|s| s.stretches())
+    }
+
+    pub fn with_baseline(self, baseline: StretchBaseline) -> Self {
+        Self { baseline, ..self }
+    }
+
+    /// Same stretch with baseline mode instead of resolved lines, so it can be applied
+    /// to other selection.
+    pub fn unresolved(&self) -> Self {
+        Self {
+            baseline: self.baseline.mode().clone(),
+            ..self.clone()
+        }
+    }
+
+    /// Stretch, which reverts this one.
+    pub fn inverted(&self) -> Self {
+        Self {
+            factor: 1.0 / self.factor,
+            flags: self.flags,
+            profile: self.profile.clone(),
+            baseline: self.baseline.clone(),
+        }
+    }
+
+    /// Factor for point at relative position `t`, 0 is the start and 1 is the end of
+    /// stretched range.
+    pub fn factor_at(&self, t: f64) -> Y {
+        if self.profile.is_constant() {
+            self.factor
+        } else {
+            self.factor.powf(self.profile.weight(t))
+        }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum StretchYBoundsError {
     BoundsOutOfRange,
     Unstretchable,
 }
 
+impl fmt::Display for StretchYBoundsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BoundsOutOfRange => write!(f, "target can not be reached by stretching"),
+            Self::Unstretchable => write!(f, "selection can not be stretched"),
+        }
+    }
+}
+
 pub struct StretchYBounds {
     min: Y,
     max: Y,
+    baseline: StretchBaseline,
 }
 
 impl StretchYBounds {
@@ -112,6 +204,7 @@ impl StretchYBounds {
         Self {
             min: Y::NAN,
             max: Y::NAN,
+            baseline: StretchBaseline::Chord,
         }
     }
 
@@ -120,21 +213,34 @@ impl StretchYBounds {
     }
 
     pub fn new(min: Y, max: Y) -> Self {
-        Self { min, max }
+        Self {
+            min,
+            max,
+            baseline: StretchBaseline::Chord,
+        }
     }
 
     pub fn new_top(max: Y) -> Self {
-        Self { min: Y::NAN, max }
+        Self {
+            min: Y::NAN,
+            max,
+            baseline: StretchBaseline::Chord,
+        }
     }
 
     pub fn new_bottom(min: Y) -> Self {
-        Self { min, max: Y::NAN }
+        Self {
+            min,
+            max: Y::NAN,
+            baseline: StretchBaseline::Chord,
+        }
     }
 
     pub fn new_both_by_max(max: Y) -> Self {
         Self {
             min: Y::NEG_INFINITY,
             max,
+            baseline: StretchBaseline::Chord,
         }
     }
 
@@ -142,6 +248,7 @@ impl StretchYBounds {
         Self {
             min,
             max: Y::INFINITY,
+            baseline: StretchBaseline::Chord,
         }
     }
 
@@ -156,6 +263,14 @@ impl StretchYBounds {
         flags
     }
 
+    pub fn with_baseline(self, baseline: StretchBaseline) -> Self {
+        Self { baseline, ..self }
+    }
+
+    pub fn baseline(&self) -> &StretchBaseline {
+        &self.baseline
+    }
+
     pub fn min(&self) -> Y {
         self.min
     }
@@ -227,6 +342,8 @@ pub struct Func {
     selection: Selection,
     stats: Stats,
     flags: BitFlags<FuncFlags>,
+    /// Sorted x ranges between the last sample before outage and the first sample after it
+    gaps: Vec<Range<X>>,
 }
 
 pub enum FuncError {
@@ -243,9 +360,92 @@ impl Func {
             stats,
             points,
             flags: FuncFlags::InvalidSelectionStats.into(),
+            gaps: Vec::new(),
         }
     }
 
+    /// Ranges of x, where samples were not measured. Samples strictly inside a gap were
+    /// added by interpolation. Gaps move with points, when values are inserted or deleted.
+    pub fn gaps(&self) -> &[Range<X>] {
+        &self.gaps
+    }
+
+    pub fn set_gaps(&mut self, mut gaps: Vec<Range<X>>) {
+        gaps.sort_by(|a, b| a.start.total_cmp(&b.start));
+        self.gaps = gaps;
+    }
+
+    /// Moves gaps by edit, which replaced points from `start` up to `end` by points up to
+    /// `new_end` and moved points from `end` to `new_end`. Gap ends inside replaced points
+    /// are trimmed to the new points, gaps, whose points were all removed, are dropped.
+    fn move_gaps(&mut self, start: X, end: X, new_end: X) {
+        if self.gaps.is_empty() {
+            return;
+        }
+        let map = |x: X| {
+            if x < start {
+                x
+            } else if x >= end {
+                x + new_end - end
+            } else {
+                x.min(new_end)
+            }
+        };
+        self.gaps = self
+            .gaps
+            .iter()
+            .map(|g| map(g.start)..map(g.end))
+            .filter(|g| g.start < g.end)
+            .collect();
+    }
+
+    pub fn is_in_gap(&self, x: X) -> bool {
+        let i = self.gaps.partition_point(|g| g.end <= x);
+        self.gaps.get(i).is_some_and(|g| g.start < x && x < g.end)
+    }
+
+    /// X of the first point and values of function resampled by `resample`.
+    pub fn resampled(&self, resample: &Resample) -> Option<(X, Vec<Y>)> {
+        resample.values(&self.points)
+    }
+
+    /// All values transformed by `transform`, samples are `x_unit` units of rate long.
+    pub fn transformed(&self, transform: &Transform, x_unit: X) -> Vec<Y> {
+        transform.values(&self.points, x_unit)
+    }
+
+    /// Spectrum of selected points, windowed by `window`.
+    pub fn selection_spectrum(&self, window: Window) -> Option<Spectrum> {
+        Spectrum::new(self.points_selection(), window)
+    }
+
+    /// Selected values filtered by `filter`.
+    pub fn selection_filtered(&self, filter: &Filter) -> Vec<Y> {
+        filter.values(self.points_selection())
+    }
+
+    /// Fits `model` to selected points.
+    pub fn fit_selection(&self, model: &FitModel) -> Result<Fit, FitError> {
+        model.fit(self.points_selection())
+    }
+
+    /// Indexes of selected points, which `detector` finds to be outliers.
+    pub fn selection_outliers(&self, detector: &OutlierDetector) -> Vec<usize> {
+        let range = self.selection_index_range();
+        detector
+            .detect(&self.points[range.clone()])
+            .into_iter()
+            .map(|i| range.start + i)
+            .collect()
+    }
+
+    /// Indexes of points strictly inside `gap`.
+    pub fn gap_index_range(&self, gap: &Range<X>) -> Range<usize> {
+        let start = self.points.partition_point(|p| p.x <= gap.start);
+        let end = self.points.partition_point(|p| p.x < gap.end);
+        start..end.max(start)
+    }
+
     pub fn values(&self) -> FuncYValuesIter {
         FuncYValuesIter::new(self.points().iter())
     }
@@ -294,17 +494,42 @@ impl Func {
         self.selection.change_selection(new_selection, &self.points);
     }
 
+    /// Recomputes selection after points were inserted or removed.
+    fn update_selection(&mut self) {
+        let selection = self.selection.x_range().clone();
+        self.selection.change_selection(&selection, &self.points);
+    }
+
     fn update_stats_with_selection(&mut self) {
-        self.stats
-            .update_max(self.selection.stats().max_index(), &self.points);
-        self.stats
-            .update_min(self.selection.stats().min_index(), &self.points);
+        let range = self.selection_index_range();
+        let selection = self.selection.stats();
+        // Bound inside of selection could have been moved, so it has to be searched again.
+        let mut recompute = BitFlags::empty();
+        if range.contains(&self.stats.min_index()) {
+            self.stats.set_min(selection.min_index());
+            recompute |= RecomputeStats::Min;
+        }
+        if range.contains(&self.stats.max_index()) {
+            self.stats.set_max(selection.max_index());
+            recompute |= RecomputeStats::Max;
+        }
+        if !recompute.is_empty() {
+            for outside in [0..range.start, range.end..self.points.len()] {
+                self.stats
+                    .update_with_range(&self.points, &outside, recompute);
+            }
+        }
+        self.stats.update_max(selection.max_index(), &self.points);
+        self.stats.update_min(selection.min_index(), &self.points);
     }
 
-    pub fn line(&self) -> Line {
-        let points = PlotPoints::Owned(self.points.clone());
-        let line = Line::new(points);
-        line
+    /// Line for each run of points without missing values.
+    pub fn lines(&self) -> Vec<Line> {
+        self.points
+            .split(|p| p.y.is_nan())
+            .filter(|points| !points.is_empty())
+            .map(|points| Line::new(PlotPoints::Owned(points.to_vec())))
+            .collect()
     }
 
     pub fn delete<'a>(&'a mut self) -> FuncRangeDelete<'a> {
@@ -321,45 +546,14 @@ impl Func {
             let x = first.x;
             let index = self.x_to_index(x);
             let count = self.points.len();
-            if count < 1 {
-                self.points.extend(points);
-            } else {
-                self.points
-                    .splice(index..index, [first].into_iter().chain(points));
-                let inserted = self.points.len() - count;
-                if index == 0 || index == count {
-                    let add = if index == 0 {
-                        let last_inserted_x = self.points[index + inserted - 1].x;
-                        let after = self.points[index + inserted].x;
-                        after - last_inserted_x - 1.0
-                    } else {
-                        let first_inserted_x = self.points[index].x;
-                        let before = self.points[index - 1].x;
-                        before - first_inserted_x + 1.0
-                    };
-                    if add.abs() >= X::EPSILON {
-                        self.points[index..index + inserted]
-                            .iter_mut()
-                            .for_each(|p| p.x += add);
-                    }
-                }
-                if inserted > 0 {
-                    let last_inserted_x = self.points[index + inserted - 1].x;
-                    self.points[index + inserted..].iter_mut().for_each(|p| {
-                        p.x += last_inserted_x - x;
-                    });
-                }
-            }
+            self.points
+                .splice(index..index, [first].into_iter().chain(points));
 
             let inserted = self.points.len() - count;
             let inserted_range = index..(index + inserted);
-            self.stats
-                .update_with_range(&self.points, &inserted_range, BitFlags::all());
-            self.selection.stats_mut().update_with_range(
-                &self.points,
-                &inserted_range,
-                BitFlags::all(),
-            );
+            self.place_inserted(&inserted_range, x);
+            self.stats.inserted(&inserted_range, &self.points);
+            self.update_selection();
             FuncRange::new(&self.points[inserted_range])
         } else {
             FuncRange::new(&[])
@@ -382,71 +576,192 @@ impl Func {
             let x = first.x;
             let index = self.x_to_index(x);
             let count = self.points.len();
-            if count < 1 {
-                self.points.extend(points);
-            } else {
-                let y_add = if index == 0 {
-                    let mut last = first;
-                    let y_after = self.points[0].y;
-                    let points = SkipEndIterator::new(points, &mut last);
-                    self.points.splice(index..index, points);
-                    y_after - last.y
-                } else {
-                    let y_before = self.points[index - 1].y;
-                    self.points.splice(index..index, points);
-                    y_before - first.y
-                };
-
-                let inserted = self.points.len() - count;
-                if index == 0 || index == count {
-                    let add = if index == 0 {
-                        let last_inserted_x = self.points[index + inserted - 1].x;
-                        let after = self.points[index + inserted].x;
-                        after - last_inserted_x - 1.0
-                    } else {
-                        let first_inserted_x = self.points[index].x;
-                        let before = self.points[index - 1].x;
-                        before - first_inserted_x + 1.0
-                    };
-                    if add.abs() >= X::EPSILON {
-                        self.points[index..index + inserted]
-                            .iter_mut()
-                            .for_each(|p| p.x += add);
-                    }
-                }
-
-                if inserted > 0 {
-                    if y_add.abs() >= Y::EPSILON {
-                        self.points[index..(index + inserted)]
-                            .iter_mut()
-                            .for_each(|p| {
-                                p.y += y_add;
-                            });
-                    }
-
-                    let last_inserted_x = self.points[index + inserted - 1].x;
-
-                    self.points[index + inserted..].iter_mut().for_each(|p| {
-                        p.x += last_inserted_x - x;
-                    });
-                }
-            }
+            let points = [first].into_iter().chain(points).collect::<Vec<_>>();
+            let y_add = self.pattern_y_add(index, points[0].y, points[points.len() - 1].y);
+            self.points.splice(index..index, points);
 
             let inserted = self.points.len() - count;
             let inserted_range = index..(index + inserted);
-            self.stats
-                .update_with_range(&self.points, &inserted_range, BitFlags::all());
-            self.selection.stats_mut().update_with_range(
-                &self.points,
-                &inserted_range,
-                BitFlags::all(),
-            );
+            if y_add.abs() >= Y::EPSILON {
+                self.points[inserted_range.clone()]
+                    .iter_mut()
+                    .for_each(|p| p.y += y_add);
+            }
+            self.place_inserted(&inserted_range, x);
+            self.stats.inserted(&inserted_range, &self.points);
+            self.update_selection();
             FuncRange::new(&self.points[inserted_range])
         } else {
             FuncRange::new(&[])
         }
     }
 
+    /// Pattern continues from point before it, or ends in point after it,
+    /// when inserted at start. Pattern is not moved, if any of those values is missing.
+    fn pattern_y_add(&self, index: usize, first: Y, last: Y) -> Y {
+        let add = match (index.checked_sub(1), self.points.first()) {
+            (Some(before), _) => self.points[before].y - first,
+            (None, Some(after)) => after.y - last,
+            (None, None) => 0.0,
+        };
+        if add.is_nan() {
+            0.0
+        } else {
+            add
+        }
+    }
+
+    fn insert_mode_values(&self, index: usize, values: &[Y], mode: FuncInsertMode) -> Vec<Y> {
+        let y_add = match (mode, values.first(), values.last()) {
+            (FuncInsertMode::Pattern, Some(first), Some(last)) => {
+                self.pattern_y_add(index, *first, *last)
+            }
+            _ => 0.0,
+        };
+        values.iter().map(|y| y + y_add).collect()
+    }
+
+    /// Places inserted points on consecutive x, starting at x of point,
+    /// which was at place of insertion, and moves points after them.
+    fn place_inserted(&mut self, range: &Range<usize>, x: X) {
+        let base = match (self.points.get(range.end), range.start.checked_sub(1)) {
+            (Some(after), _) => after.x,
+            (None, Some(before)) => self.points[before].x + 1.0,
+            (None, None) => x,
+        };
+        for (i, p) in self.points[range.clone()].iter_mut().enumerate() {
+            p.x = base + i as X;
+        }
+        let add = range.len() as X;
+        self.points[range.end..].iter_mut().for_each(|p| p.x += add);
+        self.move_gaps(base, base, base + add);
+    }
+
+    /// Replaces `len` points starting at `at` with points created from `values`.
+    /// Returns replaced values and number of inserted values.
+    pub fn replace_values(
+        &mut self,
+        at: X,
+        len: usize,
+        values: impl IntoIterator<Item = Y>,
+    ) -> (Box<[Y]>, usize) {
+        let index = self.x_to_index(at);
+        self.replace_range(index..index.saturating_add(len), at, values)
+    }
+
+    /// Removes `len` points inserted at `at`.
+    /// Insertion after end of function appends points, so then they are removed from the end.
+    pub fn remove_inserted(&mut self, at: X, len: usize) -> Box<[Y]> {
+        let index = self
+            .x_to_index(at)
+            .min(self.points.len().saturating_sub(len));
+        self.replace_range(index..(index + len), at, std::iter::empty())
+            .0
+    }
+
+    /// Writes `values` over points starting at `at`, so x of points after them does not change.
+    /// Values, which do not fit into function, are appended. Returns overwritten values.
+    pub fn overwrite(&mut self, at: X, values: &[Y], mode: FuncInsertMode) -> Box<[Y]> {
+        let index = self.x_to_index(at);
+        let values = self.insert_mode_values(index, values, mode);
+        let end = index.saturating_add(values.len());
+        self.replace_range(index..end, at, values).0
+    }
+
+    /// Resamples `values` followed by points from `at` to the end of selection
+    /// into the same number of points, which these points had.
+    /// Returns replaced values, which are empty if `at` is after selection.
+    pub fn stretch_into_selection(
+        &mut self,
+        at: X,
+        values: &[Y],
+        mode: FuncInsertMode,
+    ) -> Box<[Y]> {
+        let index = self.x_to_index(at);
+        let end = self.selection_index_range().end.max(index);
+        if index == end || values.is_empty() {
+            return Box::new([]);
+        }
+        let values = self.insert_mode_values(index, values, mode);
+        let joined = values
+            .into_iter()
+            .chain(self.points[index..end].iter().map(|p| p.y))
+            .collect::<Vec<_>>();
+        let resampled = Self::resample(&joined, end - index);
+        self.replace_range(index..end, at, resampled).0
+    }
+
+    /// Reverts `overwrite` or `stretch_into_selection`, which wrote `len` values at `at`
+    /// in place of `old` values.
+    pub fn restore_overwritten(
+        &mut self,
+        at: X,
+        len: usize,
+        old: impl ExactSizeIterator<Item = Y>,
+    ) {
+        // Values written after end of function were appended,
+        // so writing started at most at the end of function before the write.
+        let index = self
+            .x_to_index(at)
+            .min((self.points.len() + old.len()).saturating_sub(len));
+        self.replace_range(index..(index + len), at, old);
+    }
+
+    /// Linearly interpolates `values` into `len` evenly spaced values.
+    fn resample(values: &[Y], len: usize) -> Vec<Y> {
+        if values.len() < 2 || len < 2 {
+            return values.first().map(|y| vec![*y; len]).unwrap_or_default();
+        }
+        let step = (values.len() - 1) as Y / (len - 1) as Y;
+        (0..len)
+            .map(|i| {
+                let t = i as Y * step;
+                let j = (t.floor() as usize).min(values.len() - 2);
+                values[j] + (values[j + 1] - values[j]) * (t - j as Y)
+            })
+            .collect()
+    }
+
+    fn replace_range(
+        &mut self,
+        range: Range<usize>,
+        at: X,
+        values: impl IntoIterator<Item = Y>,
+    ) -> (Box<[Y]>, usize) {
+        let index = range.start;
+        let end = range.end.min(self.points.len());
+        let x = match (self.points.get(index), self.points.last()) {
+            (Some(p), _) => p.x,
+            (None, Some(last)) => last.x + 1.0,
+            (None, None) => at,
+        };
+        let old_end = self
+            .points
+            .get(end)
+            .map_or(x + end.saturating_sub(index) as X, |p| p.x);
+        let new_points = FuncValuesCheckIter::new(values.into_iter(), x).collect::<Vec<_>>();
+        let inserted = new_points.len();
+        let old = self
+            .points
+            .splice(index..end, new_points)
+            .map(|p| p.y)
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        let add = inserted as X - old.len() as X;
+        if add.abs() >= X::EPSILON {
+            self.points[index + inserted..]
+                .iter_mut()
+                .for_each(|p| p.x += add);
+        }
+        self.move_gaps(x, old_end, old_end + add);
+
+        self.stats = Stats::new(0, 0);
+        self.stats
+            .update_with_range(&self.points, &(0..self.points.len()), BitFlags::all());
+        self.update_selection();
+        (old, inserted)
+    }
+
     pub fn min_y_stretch_factor_for_bounds(
         &self,
         bounds: &StretchYBounds,
@@ -483,13 +798,17 @@ impl Func {
             let mut factor: Y = Y::INFINITY;
             if min != Y::NEG_INFINITY {
                 let min_index;
-                (factor, min_index) =
-                    self.find_min_y_stretch_factor_and_index_for_bound(&StretchYBound::Min(min))?;
+                (factor, min_index) = self.find_min_y_stretch_factor_and_index_for_bound(
+                    &StretchYBound::Min(min),
+                    bounds.baseline(),
+                )?;
                 bound_index = BoundIndex::Min(min_index);
             }
             if max != Y::INFINITY {
-                let (factor_max, max_index) =
-                    self.find_min_y_stretch_factor_and_index_for_bound(&StretchYBound::Max(max))?;
+                let (factor_max, max_index) = self.find_min_y_stretch_factor_and_index_for_bound(
+                    &StretchYBound::Max(max),
+                    bounds.baseline(),
+                )?;
                 if (factor_max - 1.0).abs() < (factor - 1.0).abs() {
                     factor = factor_max;
                     bound_index = BoundIndex::Max(max_index);
@@ -500,13 +819,17 @@ impl Func {
             }
             factor
         } else if flags.contains(YStretcherFlags::Top) {
-            let (factor, index) =
-                self.find_min_y_stretch_factor_and_index_for_bound(&StretchYBound::Max(max))?;
+            let (factor, index) = self.find_min_y_stretch_factor_and_index_for_bound(
+                &StretchYBound::Max(max),
+                bounds.baseline(),
+            )?;
             bound_index = BoundIndex::Max(index);
             factor
         } else if flags.contains(YStretcherFlags::Bottom) {
-            let (factor, index) =
-                self.find_min_y_stretch_factor_and_index_for_bound(&StretchYBound::Min(min))?;
+            let (factor, index) = self.find_min_y_stretch_factor_and_index_for_bound(
+                &StretchYBound::Min(min),
+                bounds.baseline(),
+            )?;
             bound_index = BoundIndex::Min(index);
             factor
         } else {
@@ -555,11 +878,20 @@ impl Func {
     fn find_min_y_stretch_factor_and_index_for_bound(
         &self,
         bound: &StretchYBound,
+        baseline: &StretchBaseline,
     ) -> Result<(Y, usize), StretchYBoundsError> {
         let range = self.selection_index_range();
         if !self.is_selection_stretchable() {
             return Err(StretchYBoundsError::Unstretchable);
         }
+        if *baseline != StretchBaseline::Chord {
+            return match self.stretch_line(baseline) {
+                Some(line) => {
+                    self.find_min_y_stretch_factor_and_index_for_bound_around(bound, &line)
+                }
+                None => Err(StretchYBoundsError::BoundsOutOfRange),
+            };
+        }
         let first = &self.points[range.start];
         let last = &self.points[range.end - 1];
         let line = match self.y_stretch_line() {
@@ -690,41 +1022,50 @@ impl Func {
         Ok(factor)
     }
 
+    /// Factor, which moves the farthest selected point on the side of `bound` from `line`
+    /// onto the bound. Unlike chord, other baselines do not have to go through any point,
+    /// so all selected points on that side are searched.
+    fn find_min_y_stretch_factor_and_index_for_bound_around(
+        &self,
+        bound: &StretchYBound,
+        line: &FuncLine,
+    ) -> Result<(Y, usize), StretchYBoundsError> {
+        let (m, above) = match bound {
+            StretchYBound::Max(max) => (*max, true),
+            StretchYBound::Min(min) => (*min, false),
+        };
+        if !m.is_finite() {
+            return Err(StretchYBoundsError::BoundsOutOfRange);
+        }
+        let range = self.selection_index_range();
+        let (factor, index) = self.points[range.clone()]
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| {
+                let ly = line.at(p.x);
+                let d = p.y - ly;
+                let on_side = if above {
+                    d >= Y::EPSILON
+                } else {
+                    d <= -Y::EPSILON
+                };
+                on_side.then(|| ((m - ly) / d, range.start + i))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .ok_or(StretchYBoundsError::Unstretchable)?;
+        if !factor.is_finite() || factor <= 0.0 {
+            return Err(StretchYBoundsError::BoundsOutOfRange);
+        }
+        Ok((factor, index))
+    }
+
     /// Returns whether points were modified
     pub fn stretch_y(&mut self, bounds: &StretchYBounds) -> Result<bool, StretchYBoundsError> {
-        let (factor, index) = self.min_y_stretch_factor_and_index_for_bounds(bounds)?;
-        let flags = bounds.flags();
-        if let Some(stretch) = StretchY::new(factor, flags) {
-            let is_stretch = stretch.stretch_type() == StretchType::Expand;
-            if let Some(line) = self.y_stretch_line() {
-                if self.stretch_y_with_factor_and_line_no_stats_update(&stretch, &line) {
-                    self.selection.stats_mut().set_bound(&index);
-                    if flags.is_all() {
-                        let (search_index, recompute) = match index {
-                            BoundIndex::Max(_) => {
-                                (self.selection.stats().min_index(), RecomputeStats::Min)
-                            }
-                            BoundIndex::Min(_) => {
-                                (self.selection.stats().max_index(), RecomputeStats::Max)
-                            }
-                        };
-                        let stretches_up = is_stretch == (recompute == RecomputeStats::Max);
-                        let search_range = self.new_bound_search_range(
-                            search_index,
-                            line.k(),
-                            stretches_up,
-                            NewBoundSearchRangeFlag::IncludeBoundPoints,
-                        );
-                        self.selection.stats_mut().update_with_range(
-                            &self.points,
-                            &search_range,
-                            recompute.into(),
-                        );
-                    }
-                    self.update_stats_with_selection();
-                    return Ok(true);
-                }
-            }
+        let factor = self.min_y_stretch_factor_for_bounds(bounds)?;
+        if let Some(stretch) = StretchY::new(factor, bounds.flags())
+            .map(|s| s.with_baseline(bounds.baseline().clone()))
+        {
+            return Ok(self.stretch_y_with_factor(&stretch));
         }
         Ok(false)
     }
@@ -748,11 +1089,11 @@ impl Func {
     fn stretch_y_points_with_factor<'a, Iter: IntoIterator<Item = &'a mut Point>>(
         points: Iter,
         line: &FuncLine,
-        factor: Y,
+        factor_at: impl Fn(X) -> Y,
     ) {
         points.into_iter().enumerate().for_each(|(_i, p)| {
             let ly = line.at(p.x);
-            p.y = factor.mul_add(p.y - ly, ly);
+            p.y = factor_at(p.x).mul_add(p.y - ly, ly);
         });
     }
 
@@ -761,111 +1102,78 @@ impl Func {
         if self.points.len() < 2 || range.len() < 2 {
             return None;
         }
-        let start = &self.points[range.start];
-        let end = &self.points[range.end - 1];
+        // Chord goes through the first and the last present value.
+        let mut present = self.points[range].iter().filter(|p| !p.y.is_nan());
+        let start = present.next()?;
+        let end = present.next_back()?;
         // This should be safe, because if start and end are not valid function points,
         // then somewhere is bug, which needs to be fixed.
         // Otherwise this as whole doesn't matter.
         Some(FuncLine::new(start, end).expect("Function is compromised!\nIt contains invalid points!\nThis means that whole application could be compromised!"))
     }
 
-    /// Returns whether points were modified
-    pub fn stretch_y_with_factor(&mut self, stretch: &StretchY) -> bool {
-        let is_stretch = match stretch.stretch_type() {
-            StretchType::Expand => true,
-            StretchType::Shrink => false,
-            StretchType::None => return false,
-        };
-        if let Some(line) = self.y_stretch_line() {
-            if self.stretch_y_with_factor_and_line_no_stats_update(stretch, &line) {
-                let flags = stretch.flags;
-                let is_factor_negative = stretch.factor.is_sign_negative();
-                let mut negate_recompute = false;
-                if is_factor_negative {
-                    if flags.is_all() {
-                        self.selection.stats_mut().swap_min_max(&self.points);
-                    } else {
-                        negate_recompute = true;
-                    }
+    /// Line of selection given by `baseline`, `None` for resolved baseline.
+    pub fn stretch_line(&self, baseline: &StretchBaseline) -> Option<FuncLine> {
+        match baseline {
+            StretchBaseline::Chord => self.y_stretch_line(),
+            _ => {
+                let range = self.selection_index_range();
+                if range.len() < 2 {
+                    return None;
                 }
+                baseline.line(&self.points[range])
+            }
+        }
+    }
 
-                let mut len = 0;
-                let mut recompute = [(RecomputeStats::Min, 0); 2];
-
-                if flags.contains(YStretcherFlags::Top) {
-                    recompute[len] = (RecomputeStats::Max, self.selection.stats().max_index());
-                    len += 1;
-                }
-                if flags.contains(YStretcherFlags::Bottom) {
-                    recompute[len] = (RecomputeStats::Min, self.selection.stats().min_index());
-                    len += 1;
-                }
-                for (stats_type, bound_index) in recompute[0..len].into_iter() {
-                    let stretches_up = is_stretch == (*stats_type == RecomputeStats::Max);
-                    let search_range = self.new_bound_search_range(
-                        *bound_index,
-                        line.k(),
-                        stretches_up,
-                        BitFlags::all(),
-                    );
-                    let mut recompute: BitFlags<RecomputeStats> = stats_type.clone().into();
-                    if negate_recompute {
-                        recompute = !recompute;
-                    }
+    /// Returns whether points were modified
+    pub fn stretch_y_with_factor(&mut self, stretch: &StretchY) -> bool {
+        match self.stretch_line(&stretch.baseline) {
+            Some(line) => self.stretch_y_with_factor_around(stretch, &line),
+            None => false,
+        }
+    }
 
-                    self.selection.stats_mut().update_with_range(
-                        &self.points,
-                        &search_range,
-                        recompute,
-                    );
-                }
-                if negate_recompute {
-                    let range = self.selection_index_range();
-                    let stats = self.selection.stats_mut();
-                    if flags.contains(YStretcherFlags::Top) {
-                        stats.set_max(if line.k() >= Y::EPSILON {
-                            range.end.saturating_sub(1)
-                        } else {
-                            range.start
-                        });
-                    } else if flags.contains(YStretcherFlags::Bottom) {
-                        stats.set_min(if line.k() >= Y::EPSILON {
-                            range.start
-                        } else {
-                            range.end.saturating_sub(1)
-                        });
-                    }
-                }
-                self.update_stats_with_selection();
-                return true;
-            }
+    /// Stretches distances from `line` instead of baseline of `stretch`.
+    /// Returns whether points were modified
+    pub fn stretch_y_with_factor_around(&mut self, stretch: &StretchY, line: &FuncLine) -> bool {
+        if self.selection_index_range().len() < 2 {
+            return false;
+        }
+        if self.stretch_y_with_factor_and_line_no_stats_update(stretch, line) {
+            // Stretching goes through whole selection anyway, so selection stats are recomputed.
+            self.update_selection();
+            self.update_stats_with_selection();
+            return true;
         }
         false
     }
-
     /// Returns whether points were modified
     fn stretch_y_with_factor_and_line_no_stats_update(
         &mut self,
         stretch: &StretchY,
         line: &FuncLine,
     ) -> bool {
-        let StretchY { factor, flags } = stretch;
+        let flags = stretch.flags;
         if stretch.stretches() {
             let range = self.selection_index_range();
+            let start = self.points[range.start].x;
+            let width = self.points[range.end - 1].x - start;
+            let factor_at = |x: X| stretch.factor_at((x - start) / width);
             let iter = self.points[range].iter_mut();
             if flags.is_all() {
-                Self::stretch_y_points_with_factor(iter, &line, *factor)
+                Self::stretch_y_points_with_factor(iter, line, factor_at)
             } else if flags.contains(YStretcherFlags::Top) {
                 Self::stretch_y_points_with_factor(
                     iter.filter(|p| p.y > line.at(p.x)),
-                    &line,
-                    *factor,
+                    line,
+                    factor_at,
                 )
             } else {
                 Self::stretch_y_points_with_factor(
                     iter.filter(|p| p.y < line.at(p.x)),
-                    &line,
-                    *factor,
+                    line,
+                    factor_at,
                 )
             }
             return true;
@@ -885,7 +1193,7 @@ impl Func {
     /// Returns whether points were modified
     fn stretch_y_with_factor_no_stats_update(&mut self, stretch: &StretchY) -> bool {
         if self.is_selection_stretchable() {
-            if let Some(line) = self.y_stretch_line() {
+            if let Some(line) = self.stretch_line(&stretch.baseline) {
                 return self.stretch_y_with_factor_and_line_no_stats_update(stretch, &line);
             }
         }
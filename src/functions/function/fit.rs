@@ -0,0 +1,379 @@
+use std::{f64::consts::PI, fmt};
+
+use crate::types::{
+    func_line::FuncLine,
+    point::{Point, X, Y},
+};
+
+/// Maximal number of frequencies tried by sinusoid fit before refining the best one.
+const SINUSOID_FREQUENCIES: usize = 2000;
+const SINUSOID_REFINE_STEPS: usize = 40;
+
+/// Model fitted to points by least squares.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FitModel {
+    /// Polynomial of given degree
+    Polynomial(usize),
+    /// `a * e^(b * x)`, fitted to logarithm of values, which have to have the same sign
+    Exponential,
+    /// `a + b * ln(x - origin)`, where the first point is one after origin
+    Logarithmic,
+    /// `offset + amplitude * sin(omega * x + phase)`
+    Sinusoid,
+    /// Continuous lines between given number of segments of the same width
+    PiecewiseLinear(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FitError {
+    NotEnoughPoints,
+    /// Values of exponential have to have the same sign and be nonzero
+    MixedSigns,
+    /// Points do not determine the model
+    Singular,
+}
+
+impl fmt::Display for FitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEnoughPoints => write!(f, "not enough points for the model"),
+            Self::MixedSigns => write!(f, "values have to be all positive or all negative"),
+            Self::Singular => write!(f, "points do not determine the model"),
+        }
+    }
+}
+
+/// Fitted curve. X of polynomials and sinusoid is relative to `center`,
+/// polynomial is in powers of `(x - center) / scale` for precision.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FitCurve {
+    Polynomial {
+        coefficients: Box<[Y]>,
+        center: X,
+        scale: X,
+    },
+    Exponential {
+        a: Y,
+        b: Y,
+        center: X,
+    },
+    Logarithmic {
+        a: Y,
+        b: Y,
+        origin: X,
+    },
+    Sinusoid {
+        offset: Y,
+        amplitude: Y,
+        omega: Y,
+        phase: Y,
+        center: X,
+    },
+    PiecewiseLinear(Box<[Point]>),
+}
+
+/// Residuals of fitted curve at present values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FitStats {
+    pub rmse: Y,
+    pub r_squared: Y,
+    pub max_residual: Y,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+    pub curve: FitCurve,
+    pub stats: FitStats,
+}
+
+impl FitModel {
+    pub const ALL: [Self; 5] = [
+        Self::Polynomial(2),
+        Self::Exponential,
+        Self::Logarithmic,
+        Self::Sinusoid,
+        Self::PiecewiseLinear(4),
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Polynomial(_) => "Polynomial",
+            Self::Exponential => "Exponential",
+            Self::Logarithmic => "Logarithmic",
+            Self::Sinusoid => "Sinusoid",
+            Self::PiecewiseLinear(_) => "Piecewise linear",
+        }
+    }
+
+    /// Fits model to `points`, missing values are skipped.
+    pub fn fit(&self, points: &[Point]) -> Result<Fit, FitError> {
+        let points = points
+            .iter()
+            .filter(|p| !p.y.is_nan())
+            .copied()
+            .collect::<Vec<_>>();
+        let curve = self.curve(&points)?;
+        let stats = curve.stats(&points);
+        Ok(Fit { curve, stats })
+    }
+
+    fn curve(&self, points: &[Point]) -> Result<FitCurve, FitError> {
+        let (Some(first), Some(last)) = (points.first(), points.last()) else {
+            return Err(FitError::NotEnoughPoints);
+        };
+        let center = (first.x + last.x) / 2.0;
+        match *self {
+            Self::Polynomial(degree) => {
+                let scale = ((last.x - first.x) / 2.0).max(1.0);
+                let coefficients = least_squares(points, degree + 1, |x, row| {
+                    let t = (x - center) / scale;
+                    row.iter_mut().fold(1.0, |power, c| {
+                        *c = power;
+                        power * t
+                    });
+                })?;
+                Ok(FitCurve::Polynomial {
+                    coefficients: coefficients.into_boxed_slice(),
+                    center,
+                    scale,
+                })
+            }
+            Self::Exponential => {
+                let sign = first.y.signum();
+                if points.iter().any(|p| p.y == 0.0 || p.y.signum() != sign) {
+                    return Err(FitError::MixedSigns);
+                }
+                let logs = points
+                    .iter()
+                    .map(|p| Point::new(p.x - center, (p.y * sign).ln()))
+                    .collect::<Vec<_>>();
+                let c = least_squares(&logs, 2, |x, row| row.copy_from_slice(&[1.0, x]))?;
+                Ok(FitCurve::Exponential {
+                    a: sign * c[0].exp(),
+                    b: c[1],
+                    center,
+                })
+            }
+            Self::Logarithmic => {
+                let origin = first.x - 1.0;
+                let c = least_squares(points, 2, |x, row| {
+                    row.copy_from_slice(&[1.0, (x - origin).ln()])
+                })?;
+                Ok(FitCurve::Logarithmic {
+                    a: c[0],
+                    b: c[1],
+                    origin,
+                })
+            }
+            Self::Sinusoid => Self::sinusoid(points, center),
+            Self::PiecewiseLinear(segments) => {
+                let segments = segments.max(1);
+                let width = (last.x - first.x) / segments as X;
+                if width.is_nan() || width <= 0.0 {
+                    return Err(FitError::NotEnoughPoints);
+                }
+                let knots = (0..=segments)
+                    .map(|i| first.x + i as X * width)
+                    .collect::<Vec<_>>();
+                let values = least_squares(points, knots.len(), |x, row| {
+                    row.fill(0.0);
+                    let t = ((x - first.x) / width).clamp(0.0, segments as X);
+                    let i = (t.floor() as usize).min(segments - 1);
+                    row[i] = 1.0 - (t - i as X);
+                    row[i + 1] = t - i as X;
+                })?;
+                Ok(FitCurve::PiecewiseLinear(
+                    knots
+                        .into_iter()
+                        .zip(values)
+                        .map(|(x, y)| Point::new(x, y))
+                        .collect(),
+                ))
+            }
+        }
+    }
+
+    /// Tries frequencies from half a period to half of points per selection and
+    /// refines the best one by golden section search.
+    fn sinusoid(points: &[Point], center: X) -> Result<FitCurve, FitError> {
+        let length = points[points.len() - 1].x - points[0].x;
+        let max_cycles = points.len() as X / 2.0;
+        if points.len() < 4 || length.is_nan() || length <= 0.0 {
+            return Err(FitError::NotEnoughPoints);
+        }
+        let fit = |cycles: X| {
+            let omega = 2.0 * PI * cycles / length;
+            let c = least_squares(points, 3, |x, row| {
+                let (sin, cos) = (omega * (x - center)).sin_cos();
+                row.copy_from_slice(&[1.0, sin, cos]);
+            })
+            .ok()?;
+            let curve = FitCurve::Sinusoid {
+                offset: c[0],
+                amplitude: c[1].hypot(c[2]),
+                omega,
+                phase: c[2].atan2(c[1]),
+                center,
+            };
+            let error = curve.squared_error(points);
+            Some((curve, error))
+        };
+        let step = (max_cycles / SINUSOID_FREQUENCIES as X).max(0.5);
+        let count = ((max_cycles - 0.5) / step).floor().max(0.0) as usize + 1;
+        let best = (0..count)
+            .map(|i| 0.5 + i as X * step)
+            .filter_map(|cycles| Some((cycles, fit(cycles)?.1)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .ok_or(FitError::Singular)?
+            .0;
+        let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+        let (mut low, mut high) = ((best - step).max(step / 2.0), best + step);
+        let error = |cycles| fit(cycles).map_or(Y::INFINITY, |(_, error)| error);
+        for _ in 0..SINUSOID_REFINE_STEPS {
+            let (a, b) = (high - ratio * (high - low), low + ratio * (high - low));
+            if error(a) < error(b) {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+        fit((low + high) / 2.0)
+            .or_else(|| fit(best))
+            .map(|(curve, _)| curve)
+            .ok_or(FitError::Singular)
+    }
+}
+
+impl FitCurve {
+    pub fn at(&self, x: X) -> Y {
+        match self {
+            Self::Polynomial {
+                coefficients,
+                center,
+                scale,
+            } => {
+                let t = (x - center) / scale;
+                coefficients.iter().rev().fold(0.0, |y, c| y.mul_add(t, *c))
+            }
+            Self::Exponential { a, b, center } => a * (b * (x - center)).exp(),
+            Self::Logarithmic { a, b, origin } if x > *origin => b.mul_add((x - origin).ln(), *a),
+            Self::Logarithmic { .. } => Y::NAN,
+            Self::Sinusoid {
+                offset,
+                amplitude,
+                omega,
+                phase,
+                center,
+            } => amplitude.mul_add((omega * (x - center) + phase).sin(), *offset),
+            Self::PiecewiseLinear(knots) if knots.len() < 2 => {
+                knots.first().map_or(Y::NAN, |k| k.y)
+            }
+            Self::PiecewiseLinear(knots) => {
+                // Segments at ends continue outside of knots.
+                let i = knots
+                    .partition_point(|k| k.x <= x)
+                    .clamp(1, knots.len() - 1);
+                let (a, b) = (knots[i - 1], knots[i]);
+                (b.y - a.y) / (b.x - a.x) * (x - a.x) + a.y
+            }
+        }
+    }
+
+    /// Line of polynomial of degree at most one.
+    pub fn line(&self) -> Option<FuncLine> {
+        match self {
+            Self::Polynomial {
+                coefficients,
+                center,
+                scale,
+            } if coefficients.len() <= 2 => {
+                let q = coefficients.first().copied().unwrap_or(0.0);
+                let k = coefficients.get(1).copied().unwrap_or(0.0) / scale;
+                Some(FuncLine::from_k_q(k, (-k).mul_add(*center, q)))
+            }
+            _ => None,
+        }
+    }
+
+    fn squared_error(&self, points: &[Point]) -> Y {
+        points.iter().map(|p| (p.y - self.at(p.x)).powi(2)).sum()
+    }
+
+    fn stats(&self, points: &[Point]) -> FitStats {
+        let n = points.len() as Y;
+        let mean = points.iter().map(|p| p.y).sum::<Y>() / n;
+        let total = points.iter().map(|p| (p.y - mean).powi(2)).sum::<Y>();
+        let error = self.squared_error(points);
+        FitStats {
+            rmse: (error / n).sqrt(),
+            r_squared: if total > 0.0 {
+                1.0 - error / total
+            } else {
+                1.0
+            },
+            max_residual: points
+                .iter()
+                .map(|p| (p.y - self.at(p.x)).abs())
+                .fold(0.0, Y::max),
+        }
+    }
+}
+
+/// Coefficients of `len` basis functions, whose values at x are written into row
+/// by `basis`, minimizing squared error at `points`.
+fn least_squares(
+    points: &[Point],
+    len: usize,
+    mut basis: impl FnMut(X, &mut [Y]),
+) -> Result<Vec<Y>, FitError> {
+    if points.len() < len || len == 0 {
+        return Err(FitError::NotEnoughPoints);
+    }
+    // Normal equations with right side as the last column
+    let mut matrix = vec![vec![0.0; len + 1]; len];
+    let mut row = vec![0.0; len];
+    for p in points {
+        basis(p.x, &mut row);
+        for (i, equation) in matrix.iter_mut().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                equation[j] = row[i].mul_add(*value, equation[j]);
+            }
+            equation[len] = row[i].mul_add(p.y, equation[len]);
+        }
+    }
+    let scale = matrix
+        .iter()
+        .map(|equation| equation[..len].iter().fold(0.0, |m: Y, v| m.max(v.abs())))
+        .fold(0.0, Y::max);
+    // Gaussian elimination with partial pivoting
+    for col in 0..len {
+        let pivot = (col..len)
+            .max_by(|a, b| matrix[*a][col].abs().total_cmp(&matrix[*b][col].abs()))
+            .unwrap_or(col);
+        let size = matrix[pivot][col].abs();
+        if size.is_nan() || size <= scale * 1e-12 {
+            return Err(FitError::Singular);
+        }
+        matrix.swap(col, pivot);
+        let (upper, lower) = matrix.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for row in lower {
+            let factor = row[col] / pivot_row[col];
+            for (v, p) in row.iter_mut().zip(pivot_row.iter()).skip(col) {
+                *v = (-factor).mul_add(*p, *v);
+            }
+        }
+    }
+    let mut coefficients = vec![0.0; len];
+    for r in (0..len).rev() {
+        let sum = (r + 1..len).fold(matrix[r][len], |sum, c| {
+            (-matrix[r][c]).mul_add(coefficients[c], sum)
+        });
+        coefficients[r] = sum / matrix[r][r];
+    }
+    if coefficients.iter().all(|c| c.is_finite()) {
+        Ok(coefficients)
+    } else {
+        Err(FitError::Singular)
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use crate::types::{
     func_line::{FuncLine, FuncLineErr},
     point::{Point, X, Y},
@@ -15,6 +17,9 @@ pub enum FuncBuilderError {
 pub struct FuncBuilder {
     points: Vec<Point>,
     stats: Stats,
+    /// Longer distance of consecutive points is recorded as gap
+    gap_threshold: X,
+    gaps: Vec<Range<X>>,
 }
 
 impl FuncBuilder {
@@ -22,27 +27,53 @@ impl FuncBuilder {
         Self {
             points: Vec::new(),
             stats: Stats::new(0, 0),
+            gap_threshold: X::INFINITY,
+            gaps: Vec::new(),
         }
     }
 
+    /// Points further apart than `threshold` are still joined by line, but the range
+    /// between them is recorded as gap of function.
+    pub fn with_gap_threshold(self, threshold: X) -> Self {
+        Self {
+            gap_threshold: threshold,
+            ..self
+        }
+    }
+
+    /// Adds point and points at whole x between the last point and it. NaN `point.y` is
+    /// missing value, points between missing value and other point are missing too.
     pub fn add_point(&mut self, point: &Point) -> Result<(), FuncBuilderError> {
-        if !point.x.is_finite() || !point.y.is_finite() {
+        if !point.x.is_finite() || point.y.is_infinite() {
             return Err(FuncBuilderError::PointIsNotFinite);
         }
         if let Some(last) = self.points.last() {
             if !(point.x > last.x) {
                 return Err(FuncBuilderError::XGoesBackwards);
             }
+            if point.x - last.x > self.gap_threshold {
+                self.gaps.push(last.x..point.x);
+            }
 
-            let line = match FuncLine::new(last, point) {
-                Ok(line) => line,
-                Err(e) => {
-                    return Err(match e {
-                        FuncLineErr::IsNotLine => FuncBuilderError::PointIsNotFinite,
-                        FuncLineErr::LineIsNotFunction => FuncBuilderError::XGoesBackwards,
-                    })
+            let line = if last.y.is_nan() || point.y.is_nan() {
+                None
+            } else {
+                match FuncLine::new(last, point) {
+                    Ok(line) => Some(line),
+                    Err(e) => {
+                        return Err(match e {
+                            FuncLineErr::IsNotLine => FuncBuilderError::PointIsNotFinite,
+                            FuncLineErr::LineIsNotFunction => FuncBuilderError::XGoesBackwards,
+                        })
+                    }
                 }
             };
+            let point = *point;
+            let at = |x: X| match &line {
+                Some(line) => line.at(x),
+                None if (x - point.x).abs() < X::EPSILON => point.y,
+                None => Y::NAN,
+            };
 
             let first = last.x.trunc() + 1.0;
             let mut i = 0;
@@ -50,7 +81,7 @@ impl FuncBuilder {
             let add_count = (point.x - first).trunc().max(0.0) as usize + 1;
             if add_count >= 1 {
                 if last.x.fract().abs() >= X::EPSILON {
-                    let y = line.at(first);
+                    let y = at(first);
                     let len = self.points.len();
                     self.points[len - 1] = Point::new(first, y);
                     i += 1;
@@ -58,8 +89,8 @@ impl FuncBuilder {
 
                 self.points.reserve(add_count);
                 for i in i..add_count {
-                    let y = line.at(first);
-                    self.add_valid_point(&Point::new(first + i as f64, y));
+                    let x = first + i as f64;
+                    self.add_valid_point(&Point::new(x, at(x)));
                 }
             }
         } else {
@@ -82,6 +113,8 @@ impl Into<Func> for FuncBuilder {
                 self.points.truncate(self.points.len() - 1);
             }
         }
-        Func::new_from(self.points)
+        let mut func = Func::new_from(self.points);
+        func.set_gaps(self.gaps);
+        func
     }
 }
@@ -26,14 +26,20 @@ impl<Iter: Iterator<Item = Point>> Iterator for FuncCheckIter<Iter> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(point) = self.iter.next() {
-            if !(point.x > self.last.x) || point.y.is_nan() {
+            // X has to grow, missing x is invalid too.
+            if point.x.is_nan() || point.x <= self.last.x || point.y.is_infinite() {
                 self.invalidate();
                 return None;
             }
-            if self.last.y.is_nan() {
+            if self.last.x == X::NEG_INFINITY {
                 self.last = point;
                 return Some(point);
             }
+            let x = self.last.x.trunc() + 1.0;
+            // Missing value can not be interpolated.
+            if self.last.y.is_nan() || point.y.is_nan() {
+                return Some(Point::new(x, Y::NAN));
+            }
             let line = match FuncLine::new(&self.last, &point) {
                 Ok(line) => line,
                 Err(e) => {
@@ -41,7 +47,6 @@ impl<Iter: Iterator<Item = Point>> Iterator for FuncCheckIter<Iter> {
                     return None;
                 }
             };
-            let x = self.last.x.trunc() + 1.0;
             let y = line.at(x);
             Some(Point::new(x, y))
         } else {
@@ -0,0 +1,202 @@
+use std::ops::Range;
+
+use crate::types::point::{Point, Y};
+
+/// Maximal number of inserted and deleted values, for which exact alignment is searched.
+/// If there are more differences, whole differing middle part is reported as one region.
+const MAX_EDIT_DISTANCE: usize = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffKind {
+    Changed,
+    Inserted,
+    Deleted,
+}
+
+/// Region, where values of original and current function differ.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffRegion {
+    /// Range of indexes into original points
+    pub original: Range<usize>,
+    /// Range of indexes into current points
+    pub current: Range<usize>,
+}
+
+impl DiffRegion {
+    pub fn kind(&self) -> DiffKind {
+        if self.original.is_empty() {
+            DiffKind::Inserted
+        } else if self.current.is_empty() {
+            DiffKind::Deleted
+        } else {
+            DiffKind::Changed
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiffSummary {
+    /// Number of values, which are present in both functions, but differ
+    pub changed: usize,
+    pub inserted: usize,
+    pub deleted: usize,
+    pub max_abs_deviation: Y,
+}
+
+impl DiffSummary {
+    pub fn is_empty(&self) -> bool {
+        self.changed == 0 && self.inserted == 0 && self.deleted == 0
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FuncDiff {
+    regions: Vec<DiffRegion>,
+    summary: DiffSummary,
+}
+
+impl FuncDiff {
+    pub fn new(original: &[Point], current: &[Point]) -> Self {
+        let original = original.iter().map(|p| p.y).collect::<Vec<_>>();
+        let current = current.iter().map(|p| p.y).collect::<Vec<_>>();
+        Self::from_values(&original, &current)
+    }
+
+    pub fn from_values(original: &[Y], current: &[Y]) -> Self {
+        let prefix = original
+            .iter()
+            .zip(current)
+            .take_while(|(a, b)| Self::same(**a, **b))
+            .count();
+        let suffix = original[prefix..]
+            .iter()
+            .rev()
+            .zip(current[prefix..].iter().rev())
+            .take_while(|(a, b)| Self::same(**a, **b))
+            .count();
+        let a = &original[prefix..original.len() - suffix];
+        let b = &current[prefix..current.len() - suffix];
+
+        let mut regions = Vec::new();
+        if !a.is_empty() || !b.is_empty() {
+            match Self::matches(a, b) {
+                Some(matches) => {
+                    let (mut ai, mut bi) = (0, 0);
+                    for (am, bm) in matches.into_iter().chain([(a.len(), b.len())]) {
+                        if am > ai || bm > bi {
+                            regions.push(DiffRegion {
+                                original: (prefix + ai)..(prefix + am),
+                                current: (prefix + bi)..(prefix + bm),
+                            });
+                        }
+                        (ai, bi) = (am + 1, bm + 1);
+                    }
+                }
+                None => regions.push(DiffRegion {
+                    original: prefix..(prefix + a.len()),
+                    current: prefix..(prefix + b.len()),
+                }),
+            }
+        }
+
+        let mut summary = DiffSummary::default();
+        for region in regions.iter() {
+            let paired = region.original.len().min(region.current.len());
+            let pairs = original[region.original.clone()]
+                .iter()
+                .zip(&current[region.current.clone()]);
+            for (a, b) in pairs {
+                if !Self::same(*a, *b) {
+                    summary.changed += 1;
+                    summary.max_abs_deviation = summary.max_abs_deviation.max((a - b).abs());
+                }
+            }
+            summary.inserted += region.current.len() - paired;
+            summary.deleted += region.original.len() - paired;
+        }
+        Self { regions, summary }
+    }
+
+    pub fn regions(&self) -> &[DiffRegion] {
+        &self.regions
+    }
+
+    pub fn summary(&self) -> &DiffSummary {
+        &self.summary
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    fn same(a: Y, b: Y) -> bool {
+        (a - b).abs() < Y::EPSILON || a.is_nan() && b.is_nan()
+    }
+
+    /// Myers' algorithm, returns pairs of indexes of matching values in ascending order
+    /// or None, if edit distance is bigger than `MAX_EDIT_DISTANCE`.
+    fn matches(a: &[Y], b: &[Y]) -> Option<Vec<(usize, usize)>> {
+        let n = a.len() as isize;
+        let m = b.len() as isize;
+        let max_d = (a.len() + b.len()).min(MAX_EDIT_DISTANCE) as isize;
+        let offset = max_d + 1;
+        let mut v = vec![0_isize; 2 * offset as usize + 1];
+        // Trace of `v` before each step, only diagonals reachable in that step are kept.
+        let mut trace: Vec<Box<[isize]>> = Vec::new();
+        let mut found = None;
+        'search: for d in 0..=max_d {
+            trace.push(v[(offset - d) as usize..=(offset + d) as usize].into());
+            for k in (-d..=d).step_by(2) {
+                let i = (offset + k) as usize;
+                let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                    v[i + 1]
+                } else {
+                    v[i - 1] + 1
+                };
+                let mut y = x - k;
+                while x < n && y < m && Self::same(a[x as usize], b[y as usize]) {
+                    x += 1;
+                    y += 1;
+                }
+                v[i] = x;
+                if x >= n && y >= m {
+                    found = Some(d);
+                    break 'search;
+                }
+            }
+        }
+
+        let mut matches = Vec::new();
+        let (mut x, mut y) = (n, m);
+        for d in (1..=found?).rev() {
+            let v = &trace[d as usize];
+            let get = |k: isize| v[(k + d) as usize];
+            let k = x - y;
+            let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+                k + 1
+            } else {
+                k - 1
+            };
+            let prev_x = get(prev_k);
+            let prev_y = prev_x - prev_k;
+            let (mid_x, mid_y) = if prev_k == k + 1 {
+                (prev_x, prev_y + 1)
+            } else {
+                (prev_x + 1, prev_y)
+            };
+            while x > mid_x && y > mid_y {
+                x -= 1;
+                y -= 1;
+                matches.push((x as usize, y as usize));
+            }
+            (x, y) = (prev_x, prev_y);
+        }
+        while x > 0 && y > 0 {
+            x -= 1;
+            y -= 1;
+            matches.push((x as usize, y as usize));
+        }
+        matches.reverse();
+        Some(matches)
+    }
+}
@@ -1,8 +1,6 @@
-use enumflags2::BitFlags;
+use crate::types::point::{Point, X};
 
-use crate::types::point::Point;
-
-use super::{Func, RecomputeStats};
+use super::Func;
 
 pub struct FuncRangeDelete<'a> {
     func: &'a mut Func,
@@ -24,28 +22,20 @@ impl<'a> Drop for FuncRangeDelete<'a> {
             return;
         }
 
-        let mut recompute = BitFlags::empty();
-        if indexes.contains(&self.func.stats.max_index()) {
-            recompute |= RecomputeStats::Max;
-        }
-        if indexes.contains(&self.func.stats.min_index()) {
-            recompute |= RecomputeStats::Min;
-        }
         let first_removed_x = points[indexes.start].x;
         points.drain(indexes.clone());
-        if indexes.end < points.len() {
-            let after = points[indexes.start].x;
+        // Gaps after the last point are trimmed to the removed points.
+        let mut after = X::INFINITY;
+        if indexes.start < points.len() {
+            after = points[indexes.start].x;
             let diff = after - first_removed_x;
             for point in points[indexes.start..].iter_mut() {
                 point.x -= diff;
             }
         }
+        self.func.move_gaps(first_removed_x, after, first_removed_x);
 
-        self.func.stats.update_with_range(
-            &self.func.points,
-            &(0..self.func.points.len()),
-            recompute,
-        );
+        self.func.stats.removed(indexes, &self.func.points);
         self.func.selection.delete_selection();
     }
 }
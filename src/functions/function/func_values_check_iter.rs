@@ -23,7 +23,8 @@ impl<Iter: Iterator<Item = Y>> Iterator for FuncValuesCheckIter<Iter> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(y) = self.iter.next() {
-            if y.is_nan() || !self.x.is_finite() {
+            // NaN is missing value.
+            if y.is_infinite() || !self.x.is_finite() {
                 self.invalidate();
                 return None;
             }
@@ -0,0 +1,85 @@
+use std::ops::Range;
+
+use crate::types::point::{Point, X, Y};
+
+use super::Func;
+
+/// Strategy for values of samples inside gap of measured data.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum GapFill {
+    /// Values inside gap are missing
+    #[default]
+    Empty,
+    /// Line between samples bounding the gap
+    Linear,
+    /// Value of the last sample before the gap
+    Previous,
+    /// Values one period earlier, e.g. from the previous day or week
+    PreviousPeriod(X),
+    /// Mean of values at the same position in all other periods, which are not in gaps
+    Seasonal(X),
+}
+
+impl GapFill {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Empty => "Leave empty",
+            Self::Linear => "Linear",
+            Self::Previous => "Previous value",
+            Self::PreviousPeriod(_) => "Previous period",
+            Self::Seasonal(_) => "Seasonal average",
+        }
+    }
+
+    /// X of the first sample inside `gap` and new values of samples inside it.
+    /// Returns `None` if the gap has no samples or values can not be computed from
+    /// samples of `func`. Missing values are skipped by seasonal average.
+    pub fn values(&self, func: &Func, gap: &Range<X>) -> Option<(X, Vec<Y>)> {
+        let points = func.points();
+        let inside = func.gap_index_range(gap);
+        if inside.is_empty() {
+            return None;
+        }
+        let before = inside.start.checked_sub(1).map(|i| &points[i]);
+        let after = points.get(inside.end);
+        let xs = points[inside.clone()].iter().map(|p| p.x);
+        let values = match self {
+            Self::Empty => vec![Y::NAN; inside.len()],
+            Self::Linear => {
+                let (before, after) = (before?, after?);
+                let k = (after.y - before.y) / (after.x - before.x);
+                xs.map(|x| k.mul_add(x - before.x, before.y)).collect()
+            }
+            Self::Previous => vec![before?.y; inside.len()],
+            Self::PreviousPeriod(period) => xs
+                .map(|x| Self::value_at(points, x - period))
+                .collect::<Option<_>>()?,
+            Self::Seasonal(period) => xs
+                .map(|x| {
+                    let first = points.first()?.x;
+                    let last = points.last()?.x;
+                    let mut shifted = x - ((x - first) / period).floor() * period;
+                    let (mut sum, mut count) = (0.0, 0);
+                    while shifted <= last {
+                        if (shifted - x).abs() >= X::EPSILON && !func.is_in_gap(shifted) {
+                            if let Some(y) = Self::value_at(points, shifted).filter(|y| !y.is_nan())
+                            {
+                                sum += y;
+                                count += 1;
+                            }
+                        }
+                        shifted += period;
+                    }
+                    Some(sum / count as Y).filter(|_| count > 0)
+                })
+                .collect::<Option<_>>()?,
+        };
+        Some((points[inside.start].x, values))
+    }
+
+    /// Value of sample at `x`, samples are one apart.
+    fn value_at(points: &[Point], x: X) -> Option<Y> {
+        let i = points.partition_point(|p| p.x < x - 0.5);
+        points.get(i).filter(|p| (p.x - x).abs() < 0.5).map(|p| p.y)
+    }
+}
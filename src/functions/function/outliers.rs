@@ -0,0 +1,130 @@
+use crate::types::point::{Point, Y};
+
+/// Scale of median absolute deviation, which makes it estimate of standard deviation
+/// of normally distributed values.
+const MAD_SCALE: Y = 1.4826;
+
+/// Method of finding samples, which do not fit into their neighbourhood.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlierDetector {
+    /// Values further than `threshold` standard deviations from mean
+    ZScore { threshold: Y },
+    /// Values further than `threshold` scaled median absolute deviations from median
+    /// of `window` samples on each side
+    Hampel { window: usize, threshold: Y },
+    /// Spikes, whose value differs from both neighbours by more than `limit`
+    RateOfChange { limit: Y },
+}
+
+impl Default for OutlierDetector {
+    fn default() -> Self {
+        Self::Hampel {
+            window: 5,
+            threshold: 3.0,
+        }
+    }
+}
+
+impl OutlierDetector {
+    pub const ALL: [Self; 3] = [
+        Self::ZScore { threshold: 3.0 },
+        Self::Hampel {
+            window: 5,
+            threshold: 3.0,
+        },
+        Self::RateOfChange { limit: 1.0 },
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::ZScore { .. } => "Z-score",
+            Self::Hampel { .. } => "Hampel",
+            Self::RateOfChange { .. } => "Rate of change",
+        }
+    }
+
+    /// Indexes of outliers in `points`. Missing values are never outliers and are skipped.
+    pub fn detect(&self, points: &[Point]) -> Vec<usize> {
+        let present = || points.iter().enumerate().filter(|(_, p)| !p.y.is_nan());
+        match *self {
+            Self::ZScore { threshold } => {
+                let count = present().count() as Y;
+                let mean = present().map(|(_, p)| p.y).sum::<Y>() / count;
+                let variance = present().map(|(_, p)| (p.y - mean).powi(2)).sum::<Y>() / count;
+                let deviation = variance.sqrt();
+                if deviation.is_nan() || deviation <= 0.0 {
+                    return Vec::new();
+                }
+                present()
+                    .filter(|(_, p)| (p.y - mean).abs() > threshold * deviation)
+                    .map(|(i, _)| i)
+                    .collect()
+            }
+            Self::Hampel { window, threshold } => present()
+                .filter(|(i, p)| {
+                    let start = i.saturating_sub(window);
+                    let end = (i + window + 1).min(points.len());
+                    let mut values = points[start..end]
+                        .iter()
+                        .map(|p| p.y)
+                        .filter(|y| !y.is_nan())
+                        .collect::<Vec<_>>();
+                    let center = median(&mut values);
+                    values.iter_mut().for_each(|y| *y = (*y - center).abs());
+                    let mad = median(&mut values) * MAD_SCALE;
+                    (p.y - center).abs() > threshold * mad.max(Y::EPSILON)
+                })
+                .map(|(i, _)| i)
+                .collect(),
+            Self::RateOfChange { limit } => present()
+                .filter(|(i, p)| {
+                    let jump = |j: Option<usize>| {
+                        j.and_then(|j| points.get(j))
+                            .filter(|n| !n.y.is_nan())
+                            .map(|n| p.y - n.y)
+                    };
+                    match (jump(i.checked_sub(1)), jump(Some(i + 1))) {
+                        (Some(before), Some(after)) => {
+                            before.abs() > limit
+                                && after.abs() > limit
+                                && before.is_sign_positive() == after.is_sign_positive()
+                        }
+                        (Some(jump), None) | (None, Some(jump)) => jump.abs() > limit,
+                        (None, None) => false,
+                    }
+                })
+                .map(|(i, _)| i)
+                .collect(),
+        }
+    }
+}
+
+/// Values, which replace `outliers` by line between the closest present samples,
+/// which are not outliers. `outliers` are sorted indexes into `points`.
+pub fn interpolated(points: &[Point], outliers: &[usize]) -> Vec<Y> {
+    let is_good = |j: &usize| !points[*j].y.is_nan() && outliers.binary_search(j).is_err();
+    outliers
+        .iter()
+        .map(|&i| {
+            let before = (0..i).rev().find(is_good).map(|j| points[j]);
+            let after = (i + 1..points.len()).find(is_good).map(|j| points[j]);
+            match (before, after) {
+                (Some(a), Some(b)) => (b.y - a.y) / (b.x - a.x) * (points[i].x - a.x) + a.y,
+                (Some(p), None) | (None, Some(p)) => p.y,
+                (None, None) => points[i].y,
+            }
+        })
+        .collect()
+}
+
+fn median(values: &mut [Y]) -> Y {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.is_empty() {
+        Y::NAN
+    } else if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
@@ -0,0 +1,146 @@
+use crate::types::{
+    datetime_f64_mapper::DateTimeF64Mapper,
+    point::{Point, X, Y},
+};
+
+/// Tolerance of x, at which sample still belongs to interval starting at that x.
+const X_TOLERANCE: X = 1e-9;
+
+/// Value of resampled sample computed from samples in its interval.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Aggregation {
+    #[default]
+    Mean,
+    Min,
+    Max,
+    Sum,
+    /// The last value in interval
+    Last,
+    /// Line between samples around the new sample, used for upsampling
+    Interpolate,
+}
+
+impl Aggregation {
+    pub const ALL: [Self; 6] = [
+        Self::Mean,
+        Self::Min,
+        Self::Max,
+        Self::Sum,
+        Self::Last,
+        Self::Interpolate,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Mean => "Mean",
+            Self::Min => "Min",
+            Self::Max => "Max",
+            Self::Sum => "Sum",
+            Self::Last => "Last",
+            Self::Interpolate => "Interpolate",
+        }
+    }
+}
+
+/// Change of sampling interval. Sample at new x `k` aggregates samples with old x
+/// from `offset + k * step` up to, but not including, `offset + (k + 1) * step`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Resample {
+    /// New interval in units of old x
+    pub step: X,
+    /// Old x of new x zero
+    pub offset: X,
+    pub aggregation: Aggregation,
+}
+
+impl Resample {
+    pub fn new(step: X, aggregation: Aggregation) -> Self {
+        Self {
+            step,
+            offset: 0.0,
+            aggregation,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: X) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Resampling of x mapped by `old` into x mapped by `new`.
+    pub fn between(
+        old: &DateTimeF64Mapper,
+        new: &DateTimeF64Mapper,
+        aggregation: Aggregation,
+    ) -> Self {
+        Self::new(old.time_delta_to_f64(&new.step()), aggregation)
+            .with_offset(old.time_to_f64(&new.f64_to_time(0.0)))
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.step.is_finite() && self.step > 0.0 && self.offset.is_finite()
+    }
+
+    /// New x of old `x`.
+    pub fn x(&self, x: X) -> X {
+        (x - self.offset) / self.step
+    }
+
+    /// Old x of new `x`.
+    pub fn old_x(&self, x: X) -> X {
+        x * self.step + self.offset
+    }
+
+    /// X of the first resampled point and resampled values. Intervals without
+    /// present values are missing. Returns `None` if there are no points to resample.
+    pub fn values(&self, points: &[Point]) -> Option<(X, Vec<Y>)> {
+        let (first, last) = (points.first()?, points.last()?);
+        if !self.is_valid() {
+            return None;
+        }
+        if self.aggregation == Aggregation::Interpolate {
+            let start = (self.x(first.x) - X_TOLERANCE).ceil();
+            let end = (self.x(last.x) + X_TOLERANCE).floor();
+            let len = (end - start + 1.0).max(0.0) as usize;
+            let values = (0..len)
+                .map(|i| Self::interpolate(points, self.old_x(start + i as X)))
+                .collect();
+            return Some((start, values));
+        }
+        let start = (self.x(first.x) + X_TOLERANCE).floor();
+        let len = (self.x(last.x) + X_TOLERANCE).floor() - start + 1.0;
+        let mut intervals = vec![Vec::new(); len as usize];
+        for p in points.iter().filter(|p| !p.y.is_nan()) {
+            let i = (self.x(p.x) + X_TOLERANCE).floor() - start;
+            intervals[i as usize].push(p.y);
+        }
+        let values = intervals
+            .into_iter()
+            .map(|values| self.aggregate(&values))
+            .collect();
+        Some((start, values))
+    }
+
+    fn aggregate(&self, values: &[Y]) -> Y {
+        if values.is_empty() {
+            return Y::NAN;
+        }
+        match self.aggregation {
+            Aggregation::Mean => values.iter().sum::<Y>() / values.len() as Y,
+            Aggregation::Min => values.iter().copied().fold(Y::INFINITY, Y::min),
+            Aggregation::Max => values.iter().copied().fold(Y::NEG_INFINITY, Y::max),
+            Aggregation::Sum => values.iter().sum(),
+            Aggregation::Last | Aggregation::Interpolate => values[values.len() - 1],
+        }
+    }
+
+    /// Value at `x` on line between the closest points, missing if one of them is missing.
+    fn interpolate(points: &[Point], x: X) -> Y {
+        let i = points.partition_point(|p| p.x < x - X_TOLERANCE);
+        match (i.checked_sub(1).map(|i| points[i]), points.get(i)) {
+            (_, Some(b)) if (b.x - x).abs() <= X_TOLERANCE => b.y,
+            (Some(a), Some(b)) => (b.y - a.y) / (b.x - a.x) * (x - a.x) + a.y,
+            _ => Y::NAN,
+        }
+    }
+}
@@ -25,7 +25,12 @@ impl Selection {
     }
 
     pub fn end_index(&self, points: &[Point]) -> usize {
-        (Func::x_to_points_index(points, *self.value_range.end()) + 1).min(points.len())
+        match points.first() {
+            Some(first) if *self.value_range.end() >= first.x => {
+                (Func::x_to_points_index(points, *self.value_range.end()) + 1).min(points.len())
+            }
+            _ => 0,
+        }
     }
 
     pub fn min(&self, points: &[Point]) -> Option<Y> {
@@ -41,7 +46,12 @@ impl Selection {
     }
 
     pub fn index_range(&self, points: &[Point]) -> Range<usize> {
-        self.start_index(points)..self.end_index(points)
+        let start = self.start_index(points);
+        start..self.end_index(points).max(start)
+    }
+
+    pub fn x_range(&self) -> &RangeInclusive<X> {
+        &self.value_range
     }
 
     pub fn stats(&self) -> &Stats {
@@ -0,0 +1,300 @@
+use std::f64::consts::PI;
+
+use crate::types::point::{Point, X, Y};
+
+use super::outliers;
+
+/// Window applied to values before spectrum is computed, which lowers leakage
+/// of strong frequencies into their neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Window {
+    Rectangular,
+    #[default]
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl Window {
+    pub const ALL: [Self; 4] = [Self::Rectangular, Self::Hann, Self::Hamming, Self::Blackman];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Rectangular => "Rectangular",
+            Self::Hann => "Hann",
+            Self::Hamming => "Hamming",
+            Self::Blackman => "Blackman",
+        }
+    }
+
+    pub fn weights(&self, len: usize) -> Vec<Y> {
+        let last = len.saturating_sub(1).max(1) as Y;
+        (0..len)
+            .map(|i| {
+                let phase = 2.0 * PI * i as Y / last;
+                match self {
+                    Self::Rectangular => 1.0,
+                    Self::Hann => 0.5 - 0.5 * phase.cos(),
+                    Self::Hamming => 0.54 - 0.46 * phase.cos(),
+                    Self::Blackman => 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// One-sided amplitude spectrum. Frequencies are in cycles per one x, amplitude of
+/// sinusoid, whose frequency falls onto a bin, is its amplitude in values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum {
+    pub frequencies: Vec<X>,
+    pub amplitudes: Vec<Y>,
+}
+
+impl Spectrum {
+    /// Spectrum of `points`, whose x are one apart. Missing values are interpolated.
+    /// Mean is removed before windowing, so it does not leak, and it is the constant bin.
+    /// Returns None for less than two present values.
+    pub fn new(points: &[Point], window: Window) -> Option<Self> {
+        let filled = filled(points)?;
+        let mean = filled.iter().sum::<Y>() / filled.len() as Y;
+        let weights = window.weights(points.len());
+        let values = filled
+            .iter()
+            .zip(&weights)
+            .map(|(y, w)| Complex::new((y - mean) * w, 0.0))
+            .collect();
+        let bins = dft(values, false);
+        let len = bins.len();
+        let weight = weights.iter().sum::<Y>();
+        let (frequencies, mut amplitudes): (Vec<_>, Vec<_>) = bins[..=len / 2]
+            .iter()
+            .enumerate()
+            .map(|(k, c)| {
+                let one_sided = if k == 0 || 2 * k == len { 1.0 } else { 2.0 };
+                (k as X / len as X, one_sided * c.abs() / weight)
+            })
+            .unzip();
+        amplitudes[0] = mean.abs();
+        Some(Self {
+            frequencies,
+            amplitudes,
+        })
+    }
+
+    /// Frequency and amplitude of the highest bin, except for the constant one.
+    pub fn peak(&self) -> Option<(X, Y)> {
+        self.frequencies
+            .iter()
+            .zip(&self.amplitudes)
+            .skip(1)
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(f, a)| (*f, *a))
+    }
+}
+
+/// Filter in frequency domain, frequencies are in cycles per one x.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    /// Keeps frequencies up to `cutoff`
+    LowPass { cutoff: X },
+    /// Removes frequencies from `low` to `high`
+    BandStop { low: X, high: X },
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::LowPass { cutoff: 0.1 }
+    }
+}
+
+impl Filter {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::LowPass { .. } => "Low-pass",
+            Self::BandStop { .. } => "Band-stop",
+        }
+    }
+
+    /// Filter with frequencies multiplied by `factor`, e.g. to convert them to other unit.
+    pub fn scaled(&self, factor: X) -> Self {
+        match *self {
+            Self::LowPass { cutoff } => Self::LowPass {
+                cutoff: cutoff * factor,
+            },
+            Self::BandStop { low, high } => Self::BandStop {
+                low: low * factor,
+                high: high * factor,
+            },
+        }
+    }
+
+    fn passes(&self, frequency: X) -> bool {
+        match *self {
+            Self::LowPass { cutoff } => frequency <= cutoff,
+            Self::BandStop { low, high } => frequency < low || frequency > high,
+        }
+    }
+
+    /// Filtered values of `points`, whose x are one apart. Line between the first and
+    /// the last value is kept aside, so ends do not leak into each other.
+    /// Missing values are interpolated for filtering and stay missing.
+    pub fn values(&self, points: &[Point]) -> Vec<Y> {
+        let Some(filled) = filled(points) else {
+            return points.iter().map(|p| p.y).collect();
+        };
+        let (first, last) = (filled[0], filled[filled.len() - 1]);
+        let slope = (last - first) / (filled.len() - 1) as Y;
+        let trend = |i: usize| first + slope * i as Y;
+        let residuals = filled
+            .iter()
+            .enumerate()
+            .map(|(i, y)| Complex::new(y - trend(i), 0.0))
+            .collect();
+        let mut bins = dft(residuals, false);
+        let len = bins.len();
+        for (k, bin) in bins.iter_mut().enumerate() {
+            if !self.passes(k.min(len - k) as X / len as X) {
+                *bin = Complex::new(0.0, 0.0);
+            }
+        }
+        dft(bins, true)
+            .iter()
+            .zip(points)
+            .enumerate()
+            .map(|(i, (c, p))| {
+                if p.y.is_nan() {
+                    Y::NAN
+                } else {
+                    c.re / len as Y + trend(i)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Values of `points` with missing ones interpolated, None for less than two present values.
+fn filled(points: &[Point]) -> Option<Vec<Y>> {
+    let missing = (0..points.len())
+        .filter(|i| points[*i].y.is_nan())
+        .collect::<Vec<_>>();
+    if points.len() - missing.len() < 2 {
+        return None;
+    }
+    let mut values = points.iter().map(|p| p.y).collect::<Vec<_>>();
+    for (i, y) in missing.iter().zip(outliers::interpolated(points, &missing)) {
+        values[*i] = y;
+    }
+    Some(values)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Complex {
+    re: Y,
+    im: Y,
+}
+
+impl Complex {
+    fn new(re: Y, im: Y) -> Self {
+        Self { re, im }
+    }
+
+    /// Unit complex number at `angle`.
+    fn unit(angle: Y) -> Self {
+        Self::new(angle.cos(), angle.sin())
+    }
+
+    fn abs(&self) -> Y {
+        self.re.hypot(self.im)
+    }
+
+    fn conj(&self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+
+    fn add(&self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+
+    fn sub(&self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+
+    fn mul(&self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+
+    fn scale(&self, factor: Y) -> Self {
+        Self::new(self.re * factor, self.im * factor)
+    }
+}
+
+/// Discrete Fourier transform of any length, the inverse one is not divided by length.
+/// Other lengths than powers of two are computed by Bluestein's algorithm.
+fn dft(mut values: Vec<Complex>, inverse: bool) -> Vec<Complex> {
+    let len = values.len();
+    if len.is_power_of_two() || len == 0 {
+        fft(&mut values, inverse);
+        return values;
+    }
+    let sign = if inverse { 1.0 } else { -1.0 };
+    // k² is taken modulo 2 * len, so angle stays precise for long inputs.
+    let chirp = (0..len)
+        .map(|k| Complex::unit(sign * PI * ((k * k) % (2 * len)) as Y / len as Y))
+        .collect::<Vec<_>>();
+    let size = (2 * len - 1).next_power_of_two();
+    let mut a = vec![Complex::new(0.0, 0.0); size];
+    for ((a, v), c) in a.iter_mut().zip(&values).zip(&chirp) {
+        *a = v.mul(*c);
+    }
+    let mut b = vec![Complex::new(0.0, 0.0); size];
+    b[0] = chirp[0].conj();
+    for k in 1..len {
+        b[k] = chirp[k].conj();
+        b[size - k] = chirp[k].conj();
+    }
+    fft(&mut a, false);
+    fft(&mut b, false);
+    for (a, b) in a.iter_mut().zip(&b) {
+        *a = a.mul(*b);
+    }
+    fft(&mut a, true);
+    a.iter()
+        .zip(&chirp)
+        .map(|(a, c)| a.mul(*c).scale(1.0 / size as Y))
+        .collect()
+}
+
+/// In-place radix-2 fast Fourier transform, length has to be a power of two.
+fn fft(values: &mut [Complex], inverse: bool) {
+    let len = values.len();
+    if len <= 1 {
+        return;
+    }
+    let bits = len.trailing_zeros();
+    for i in 0..len {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut size = 2;
+    while size <= len {
+        let step = Complex::unit(sign * 2.0 * PI / size as Y);
+        for chunk in values.chunks_mut(size) {
+            let (low, high) = chunk.split_at_mut(size / 2);
+            let mut twiddle = Complex::new(1.0, 0.0);
+            for (l, h) in low.iter_mut().zip(high) {
+                let t = h.mul(twiddle);
+                *h = l.sub(t);
+                *l = l.add(t);
+                twiddle = twiddle.mul(step);
+            }
+        }
+        size *= 2;
+    }
+}
@@ -64,6 +64,48 @@ impl Stats {
         }
     }
 
+    /// Moves indexes behind inserted points and updates stats with them.
+    pub fn inserted(&mut self, range: &Range<usize>, points: &[Point]) {
+        if range.len() >= points.len() {
+            *self = Self::new(range.start, range.start);
+        } else {
+            for index in [&mut self.min_index, &mut self.max_index] {
+                if *index >= range.start {
+                    *index += range.len();
+                }
+            }
+        }
+        self.update_with_range(points, range, BitFlags::all());
+    }
+
+    /// Moves indexes behind removed points and recomputes stats, whose points were removed.
+    pub fn removed(&mut self, range: &Range<usize>, points: &[Point]) {
+        let mut recompute = BitFlags::empty();
+        for (index, stats) in [
+            (&mut self.min_index, RecomputeStats::Min),
+            (&mut self.max_index, RecomputeStats::Max),
+        ] {
+            if range.contains(index) {
+                *index = 0;
+                recompute |= stats;
+            } else if *index >= range.end {
+                *index -= range.len();
+            }
+        }
+        if !recompute.is_empty() {
+            self.update_with_range(points, &(0..points.len()), recompute);
+        }
+    }
+
+    /// Whether value at `i` replaces bound at `index`. Missing values are never bounds,
+    /// unless all values are missing.
+    #[inline(always)]
+    fn is_new_bound(i: usize, index: usize, points: &[Point], bound: Ordering) -> bool {
+        let y = points[i].y;
+        let bound_y = points[index].y;
+        !y.is_nan() && (bound_y.is_nan() || y.total_cmp(&bound_y) == bound)
+    }
+
     #[inline(always)]
     pub fn min_max_point_y(
         min_index: &mut usize,
@@ -71,31 +113,23 @@ impl Stats {
         i: usize,
         points: &[Point],
     ) {
-        let y = points[i].y;
-        match y.total_cmp(&points[*min_index].y) {
-            Ordering::Less => *min_index = i,
-            Ordering::Equal => (),
-            Ordering::Greater => {
-                if y.total_cmp(&points[*max_index].y) == Ordering::Greater {
-                    *max_index = i;
-                }
-            }
-        };
+        if Self::is_new_bound(i, *min_index, points, Ordering::Less) {
+            *min_index = i;
+        }
+        if Self::is_new_bound(i, *max_index, points, Ordering::Greater) {
+            *max_index = i;
+        }
     }
 
     pub fn update_max(&mut self, i: usize, points: &[Point]) {
-        let max_index = &mut self.max_index;
-        let y = points[i].y;
-        if y.total_cmp(&points[*max_index].y) == Ordering::Greater {
-            *max_index = i;
+        if Self::is_new_bound(i, self.max_index, points, Ordering::Greater) {
+            self.max_index = i;
         }
     }
 
     pub fn update_min(&mut self, i: usize, points: &[Point]) {
-        let min_index = &mut self.min_index;
-        let y = points[i].y;
-        if y.total_cmp(&points[*min_index].y) == Ordering::Less {
-            *min_index = i;
+        if Self::is_new_bound(i, self.min_index, points, Ordering::Less) {
+            self.min_index = i;
         }
     }
 
@@ -125,30 +159,23 @@ impl Stats {
         self.max_index
     }
 
+    /// `None` if there are no points or all values are missing.
     pub fn min<'a>(&self, points: &'a [Point]) -> Option<Y> {
-        if let Some(point) = points.get(self.min_index) {
-            Some(point.y)
-        } else {
-            None
-        }
+        points
+            .get(self.min_index)
+            .map(|p| p.y)
+            .filter(|y| !y.is_nan())
     }
 
     pub fn max<'a>(&self, points: &'a [Point]) -> Option<Y> {
-        if let Some(point) = points.get(self.max_index) {
-            Some(point.y)
-        } else {
-            None
-        }
+        points
+            .get(self.max_index)
+            .map(|p| p.y)
+            .filter(|y| !y.is_nan())
     }
 
     pub fn value_range<'a>(&self, points: &'a [Point]) -> Option<RangeInclusive<Y>> {
-        if let (Some(Point { y: min, .. }), Some(Point { y: max, .. })) =
-            (points.get(self.min_index), points.get(self.max_index))
-        {
-            Some(RangeInclusive::new(*min, *max))
-        } else {
-            None
-        }
+        Some(RangeInclusive::new(self.min(points)?, self.max(points)?))
     }
 }
 
@@ -0,0 +1,66 @@
+use chrono::TimeDelta;
+
+use crate::types::{
+    datetime_f64_mapper::DateTimeF64Mapper,
+    point::{Point, X, Y},
+};
+
+/// Transform of all values of function between rate and cumulative quantity,
+/// e.g. between power and energy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    /// Change from the previous value per unit, the first value is missing
+    Derivative,
+    /// Running sum of values multiplied by length of sample in units, starting at `initial`.
+    /// The first value only sets start of sum, so integral inverts derivative.
+    Integral { initial: Y },
+}
+
+impl Transform {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Derivative => "Derivative",
+            Self::Integral { .. } => "Integral",
+        }
+    }
+
+    /// Length of sample, whose x are one apart, in `unit` of time.
+    pub fn x_unit(mapper: &DateTimeF64Mapper, unit: TimeDelta) -> X {
+        1.0 / mapper.time_delta_to_f64(&unit)
+    }
+
+    /// Transform, which turns values transformed by this one from `points` back.
+    pub fn inverse(&self, points: &[Point]) -> Self {
+        match self {
+            Self::Derivative => Self::Integral {
+                initial: points.first().map_or(0.0, |p| p.y),
+            },
+            Self::Integral { .. } => Self::Derivative,
+        }
+    }
+
+    /// Transformed values of `points`, whose x are one apart. Length of sample is
+    /// `x_unit` units, in which rate is given. Missing values stay missing, integral
+    /// skips them and derivative is missing next to them.
+    pub fn values(&self, points: &[Point], x_unit: X) -> Vec<Y> {
+        match *self {
+            Self::Derivative => {
+                let first = points.first().map(|_| Y::NAN);
+                let rest = points.windows(2).map(|w| (w[1].y - w[0].y) / x_unit);
+                first.into_iter().chain(rest).collect()
+            }
+            Self::Integral { initial } => {
+                let mut sum = initial;
+                let first = points.first().map(|_| initial);
+                let rest = points.iter().skip(1).map(|p| {
+                    if p.y.is_nan() {
+                        return Y::NAN;
+                    }
+                    sum += p.y * x_unit;
+                    sum
+                });
+                first.into_iter().chain(rest).collect()
+            }
+        }
+    }
+}
@@ -3,13 +3,19 @@ use std::*;
 use ops::RangeInclusive;
 
 use crate::{
-    functions::function::{
-        func_values_check_iter::FuncValuesCheckIter, Func, FuncYValuesIter, StretchY,
-        StretchYBounds, StretchYBoundsError,
+    functions::{
+        expression::{Expression, ExpressionError},
+        function::{
+            func_values_check_iter::FuncValuesCheckIter, resample::Resample, spectrum::Filter,
+            transform::Transform, Func, FuncInsertMode, FuncYValuesIter, InsertPolicy, StretchY,
+            StretchYBounds, StretchYBoundsError,
+        },
     },
-    shared_op::SharedOp,
+    shared_op::{FuncChange, SharedOp},
+    stretchers::y_stretcher::StretchBaseline,
     types::{
         bit_set::{self, BitSet},
+        func_line::FuncLine,
         point::{X, Y},
     },
 };
@@ -21,13 +27,13 @@ pub enum GraphFuncState {
 }
 
 pub struct SelectionValuesIterator<'a> {
-    active_funcs: ActiveFuncsIter<'a>,
+    funcs: vec::IntoIter<&'a Func>,
 }
 
 impl<'a> SelectionValuesIterator<'a> {
-    pub fn new(funcs: ActiveFuncsIter<'a>) -> Self {
+    fn new(funcs: impl Iterator<Item = &'a Func>) -> Self {
         Self {
-            active_funcs: funcs,
+            funcs: funcs.collect::<Vec<_>>().into_iter(),
         }
     }
 }
@@ -36,14 +42,10 @@ impl<'a> Iterator for SelectionValuesIterator<'a> {
     type Item = FuncYValuesIter<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(func) = self.active_funcs.next() {
-            Some(func.values_selections())
-        } else {
-            None
-        }
+        self.funcs.next().map(|func| func.values_selections())
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.active_funcs.size_hint()
+        self.funcs.size_hint()
     }
 }
 
@@ -89,6 +91,13 @@ impl<'a> ExactSizeIterator for ActiveFuncsIter<'a> {}
 struct Functions {
     functions: Vec<Func>,
     active_funcs: BitSet,
+    /// Functions, which can not be modified, regardless of whether they are active.
+    locked_funcs: BitSet,
+    /// Selection of each function, which is used instead of selection of graph.
+    selections: Vec<Option<RangeInclusive<X>>>,
+    /// Stable id of each function, which does not change, when other functions are removed.
+    ids: Vec<usize>,
+    next_id: usize,
 }
 
 impl<'a> Functions {
@@ -118,10 +127,6 @@ impl<'a> Functions {
         })
     }
 
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Func> {
-        self.functions.iter_mut()
-    }
-
     pub fn len(&self) -> usize {
         self.functions.len()
     }
@@ -147,24 +152,48 @@ impl<'a> Functions {
         });
     }
 
-    pub fn for_each_active_mut(&'a mut self, mut apply: impl FnMut(&mut Func)) {
+    /// Indexes of active functions, which are not locked.
+    pub fn editable_indexes(&self) -> Vec<usize> {
+        self.active_funcs
+            .iter()
+            .filter(|fi| !self.locked_funcs.contains(*fi))
+            .collect()
+    }
+
+    pub fn for_each_editable_mut(&'a mut self, mut apply: impl FnMut(&mut Func)) {
         let functions = self.functions.as_mut_slice();
         for fi in self.active_funcs.iter() {
-            apply(&mut functions[fi]);
+            if !self.locked_funcs.contains(fi) {
+                apply(&mut functions[fi]);
+            }
         }
     }
 
-    pub fn for_each_active_ok_mut<T, E>(
+    pub fn for_each_editable_ok_mut<T, E>(
         &'a mut self,
         mut apply: impl FnMut(&mut Func) -> Result<T, E>,
     ) -> Result<(), E> {
         let functions = self.functions.as_mut_slice();
         for fi in self.active_funcs.iter() {
-            apply(&mut functions[fi])?;
+            if !self.locked_funcs.contains(fi) {
+                apply(&mut functions[fi])?;
+            }
         }
         Ok(())
     }
 
+    /// Own selection of function or `graph_selection`.
+    pub fn selection(
+        &'a self,
+        index: usize,
+        graph_selection: &'a RangeInclusive<X>,
+    ) -> &'a RangeInclusive<X> {
+        self.selections
+            .get(index)
+            .and_then(|s| s.as_ref())
+            .unwrap_or(graph_selection)
+    }
+
     pub fn iter_inactive_mut(&'a mut self) -> impl Iterator<Item = &'a mut Func> {
         self.functions.iter_mut().enumerate().filter_map(|(fi, f)| {
             if self.active_funcs.contains(fi) {
@@ -207,11 +236,50 @@ impl<'a> Functions {
         };
         Some(state)
     }
+
+    /// Moves indexes in `set` starting at `from` by one up or down.
+    fn shift_indexes(set: &BitSet, from: usize, up: bool) -> BitSet {
+        let indexes = set
+            .iter()
+            .filter(|fi| up || *fi != from)
+            .map(|fi| match (fi >= from, up) {
+                (true, true) => fi + 1,
+                (true, false) => fi - 1,
+                _ => fi,
+            })
+            .collect::<Vec<_>>();
+        indexes.into_iter().into()
+    }
+
+    pub fn insert(&mut self, index: usize, id: usize, func: Func, state: GraphFuncState) {
+        self.functions.insert(index, func);
+        self.ids.insert(index, id);
+        self.selections.insert(index, None);
+        self.next_id = self.next_id.max(id + 1);
+        self.active_funcs = Self::shift_indexes(&self.active_funcs, index, true);
+        self.locked_funcs = Self::shift_indexes(&self.locked_funcs, index, true);
+        if state == GraphFuncState::Active {
+            self.active_funcs.insert(index);
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<(usize, Func, GraphFuncState)> {
+        let state = self.get_func_state(index)?;
+        self.active_funcs = Self::shift_indexes(&self.active_funcs, index, false);
+        self.locked_funcs = Self::shift_indexes(&self.locked_funcs, index, false);
+        self.selections.remove(index);
+        Some((self.ids.remove(index), self.functions.remove(index), state))
+    }
 }
 
 pub struct Graph {
     functions: Functions,
     selection: RangeInclusive<X>,
+    revision: u64,
+    /// Ids of derived functions, which are recomputed on each modification, and their expressions
+    live: Vec<(usize, Expression)>,
+    /// Selection ranges in addition to selection of graph. They are not recorded into history.
+    extra_selections: Vec<RangeInclusive<X>>,
 }
 
 impl<'a> Graph {
@@ -236,23 +304,226 @@ impl<'a> Graph {
             selection,
             functions: Functions {
                 active_funcs: (0..functions.len()).into_iter().into(),
+                locked_funcs: BitSet::new(),
+                selections: vec![None; functions.len()],
+                ids: (0..functions.len()).collect(),
+                next_id: functions.len(),
                 functions,
             },
+            revision: 0,
+            live: Vec::new(),
+            extra_selections: Vec::new(),
         }
     }
+
+    /// Number, which changes whenever values of any function change.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn modified(&mut self) {
+        self.revision = self.revision.wrapping_add(1);
+    }
+
+    /// Evaluates expression over functions of graph. Expression without functions spans
+    /// x range of graph.
+    pub fn derive(&self, expression: &Expression) -> Result<(X, Vec<Y>), ExpressionError> {
+        let sources = expression
+            .sources()
+            .iter()
+            .map(|id| {
+                let func = self.get_func(self.func_index(*id)?)?;
+                Some(func.points())
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ExpressionError::NoPoints)?;
+        expression.evaluate(&sources, self.x_range())
+    }
+
+    /// Keeps function with `id` recomputed from `expression`, see `live_updates`.
+    /// `None` makes function ordinary again. It is not recorded into history.
+    pub fn set_live_expression(&mut self, id: usize, expression: Option<Expression>) {
+        self.live.retain(|(live_id, _)| *live_id != id);
+        if let Some(expression) = expression {
+            self.live.push((id, expression));
+        }
+    }
+
+    pub fn live_expression(&self, id: usize) -> Option<&Expression> {
+        self.live
+            .iter()
+            .find(|(live_id, _)| *live_id == id)
+            .map(|(_, e)| e)
+    }
+
+    /// Index, x of the first point and values of live functions, which differ from their
+    /// recomputed values. Function, whose expression can not be evaluated, or which is
+    /// locked, is skipped.
+    pub fn live_updates(&self) -> Vec<(usize, X, Vec<Y>)> {
+        self.live
+            .iter()
+            .filter_map(|(id, expression)| {
+                let index = self.func_index(*id)?;
+                if expression.sources().contains(id) || self.functions.locked_funcs.contains(index)
+                {
+                    return None;
+                }
+                let (x, values) = self.derive(expression).ok()?;
+                let points: Vec<_> = FuncValuesCheckIter::new(values.iter().copied(), x).collect();
+                let old = self.get_func(index)?.points();
+                // Missing values are NaN, so points are compared by bits.
+                let same = old.len() == points.len()
+                    && old.iter().zip(&points).all(|(a, b)| {
+                        a.x.to_bits() == b.x.to_bits() && a.y.to_bits() == b.y.to_bits()
+                    });
+                (!same).then_some((index, x, values))
+            })
+            .collect()
+    }
+
     pub fn selection(&self) -> &RangeInclusive<X> {
         &self.selection
     }
 
+    /// Selection ranges in addition to selection of graph.
+    pub fn extra_selections(&self) -> &[RangeInclusive<X>] {
+        &self.extra_selections
+    }
+
+    pub fn add_selection_range(&mut self, range: RangeInclusive<X>) {
+        if range.start() <= range.end() {
+            self.extra_selections.push(range);
+        }
+    }
+
+    /// Removes additional selection ranges containing `x`. Returns whether any range was removed.
+    pub fn remove_selection_range_at(&mut self, x: X) -> bool {
+        let len = self.extra_selections.len();
+        self.extra_selections.retain(|r| !r.contains(&x));
+        len != self.extra_selections.len()
+    }
+
+    pub fn clear_selection_ranges(&mut self) {
+        self.extra_selections.clear();
+    }
+
+    /// Selection of graph together with additional ranges, sorted by start and with
+    /// overlapping ranges merged.
+    pub fn selection_ranges(&self) -> Vec<RangeInclusive<X>> {
+        let mut ranges = iter::once(self.selection.clone())
+            .chain(self.extra_selections.iter().cloned())
+            .collect::<Vec<_>>();
+        ranges.sort_by(|a, b| a.start().total_cmp(b.start()));
+        let mut merged: Vec<RangeInclusive<X>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start() <= last.end() => {
+                    *last = *last.start()..=last.end().max(*range.end());
+                }
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+
+    /// Value range of active functions over all selection ranges. Function with own
+    /// selection uses only its own selection.
+    fn ranges_value_range(&self) -> Option<RangeInclusive<Y>> {
+        let ranges = self.selection_ranges();
+        self.functions
+            .active_funcs
+            .iter()
+            .flat_map(|fi| {
+                let own = self.func_selection(fi).map(slice::from_ref);
+                let ranges = own.unwrap_or(&ranges);
+                self.functions.functions[fi]
+                    .points()
+                    .iter()
+                    .filter(|p| ranges.iter().any(|r| r.contains(&p.x)))
+                    .map(|p| p.y)
+            })
+            .fold(None, |a, y| match a {
+                Some(a) => Some(y.min(*a.start())..=y.max(*a.end())),
+                None => Some(y..=y),
+            })
+    }
+
+    /// Selected values of active functions, which are not locked.
     pub fn selection_points(&self) -> SelectionValuesIterator {
-        SelectionValuesIterator::new(self.functions.iter_active())
+        SelectionValuesIterator::new(
+            self.functions
+                .editable_indexes()
+                .into_iter()
+                .map(|fi| &self.functions.functions[fi]),
+        )
+    }
+
+    /// The largest number of selected values of active functions, which are not locked.
+    pub fn selection_len(&self) -> usize {
+        self.functions
+            .editable_indexes()
+            .into_iter()
+            .map(|fi| self.functions.functions[fi].points_selection().len())
+            .max()
+            .unwrap_or(0)
     }
 
+    /// Changes selection of graph, which is used by functions without own selection.
     pub fn change_selection(&mut self, selection: RangeInclusive<X>) {
         self.selection = selection;
-        self.functions
-            .iter_mut()
-            .for_each(|f| f.change_selection(&self.selection));
+        self.apply_selections();
+    }
+
+    fn apply_selections(&mut self) {
+        let functions = &mut self.functions;
+        for (f, selection) in functions.functions.iter_mut().zip(&functions.selections) {
+            f.change_selection(selection.as_ref().unwrap_or(&self.selection));
+        }
+    }
+
+    /// Own selection of function, which is used instead of selection of graph.
+    pub fn func_selection(&self, index: usize) -> Option<&RangeInclusive<X>> {
+        self.functions.selections.get(index)?.as_ref()
+    }
+
+    /// Sets own selection of function, `None` makes it follow selection of graph.
+    /// Returns previous own selection, or `None`, if `index` is out of range.
+    pub fn set_func_selection(
+        &mut self,
+        index: usize,
+        selection: Option<RangeInclusive<X>>,
+    ) -> Option<Option<RangeInclusive<X>>> {
+        let old = std::mem::replace(self.functions.selections.get_mut(index)?, selection);
+        self.apply_selection(index);
+        Some(old)
+    }
+
+    fn apply_selection(&mut self, index: usize) {
+        let functions = &mut self.functions;
+        let selection = functions.selections[index]
+            .as_ref()
+            .unwrap_or(&self.selection);
+        functions.functions[index].change_selection(selection);
+    }
+
+    pub fn is_locked(&self, index: usize) -> Option<bool> {
+        (index < self.len()).then(|| self.functions.locked_funcs.contains(index))
+    }
+
+    /// Locks or unlocks function against modification. Returns previous lock state,
+    /// or `None`, if `index` is out of range.
+    pub fn set_locked(&mut self, index: usize, locked: bool) -> Option<bool> {
+        let was_locked = self.is_locked(index)?;
+        if locked {
+            self.functions.locked_funcs.insert(index);
+        } else {
+            self.functions.locked_funcs.remove(index);
+        }
+        Some(was_locked)
+    }
+
+    pub fn locked_func_indexes(&'a self) -> impl ExactSizeIterator<Item = usize> + Clone + 'a {
+        self.functions.locked_funcs.iter()
     }
 
     pub fn move_selection_by(&mut self, add: RangeInclusive<X>) {
@@ -289,10 +560,111 @@ impl<'a> Graph {
         self.functions.iter()
     }
 
+    pub fn get_func(&self, index: usize) -> Option<&Func> {
+        self.functions.functions.get(index)
+    }
+
     pub fn len(&self) -> usize {
         self.functions.len()
     }
 
+    /// Stable id of function, which does not change, when other functions are added or removed.
+    /// Functions passed to `Graph::new` have ids equal to their indexes.
+    pub fn func_id(&self, index: usize) -> Option<usize> {
+        self.functions.ids.get(index).copied()
+    }
+
+    pub fn func_index(&self, id: usize) -> Option<usize> {
+        self.functions.ids.iter().position(|i| *i == id)
+    }
+
+    /// Id, which is given to next new function.
+    pub fn next_func_id(&self) -> usize {
+        self.functions.next_id
+    }
+
+    /// X range of all functions, regardless of whether they are active.
+    pub fn x_range(&self) -> Option<RangeInclusive<X>> {
+        self.functions()
+            .filter_map(|f| Some(f.points().first()?.x..=f.points().last()?.x))
+            .reduce(|a, r| a.start().min(*r.start())..=a.end().max(*r.end()))
+    }
+
+    /// X of the first point and values of each function resampled by `resample`,
+    /// regardless of whether it is active. Empty function gives `None`.
+    pub fn resampled(&self, resample: &Resample) -> Vec<Option<(X, Vec<Y>)>> {
+        self.functions().map(|f| f.resampled(resample)).collect()
+    }
+
+    /// Index, x of the first point and all transformed values of each active function,
+    /// which is not locked.
+    pub fn transformed(&self, transform: &Transform, x_unit: X) -> Vec<(usize, X, Vec<Y>)> {
+        self.active_func_indexes()
+            .filter_map(|fi| {
+                let (x, values) = self.func_transformed(fi, transform, x_unit)?;
+                Some((fi, x, values))
+            })
+            .collect()
+    }
+
+    /// X of the first point and all transformed values of function at `index`, `None` if
+    /// it is locked or empty.
+    pub fn func_transformed(
+        &self,
+        index: usize,
+        transform: &Transform,
+        x_unit: X,
+    ) -> Option<(X, Vec<Y>)> {
+        if self.is_locked(index) != Some(false) {
+            return None;
+        }
+        let func = self.get_func(index)?;
+        let x = func.points().first()?.x;
+        Some((x, func.transformed(transform, x_unit)))
+    }
+
+    /// Index, x of the first selected point and filtered selected values of each active
+    /// function, which is not locked.
+    pub fn filtered(&self, filter: &Filter) -> Vec<(usize, X, Vec<Y>)> {
+        self.active_func_indexes()
+            .filter(|fi| self.is_locked(*fi) == Some(false))
+            .filter_map(|fi| {
+                let func = self.get_func(fi)?;
+                let x = func.points_selection().first()?.x;
+                Some((fi, x, func.selection_filtered(filter)))
+            })
+            .collect()
+    }
+
+    /// Inserts function at `index` with `id`, so undo can give removed function its old id back.
+    /// Returns false, if `index` is out of range.
+    pub fn insert_func(
+        &mut self,
+        index: usize,
+        id: usize,
+        mut func: Func,
+        state: GraphFuncState,
+    ) -> bool {
+        if index > self.len() {
+            return false;
+        }
+        func.change_selection(&self.selection);
+        self.functions.insert(index, id, func, state);
+        self.modified();
+        true
+    }
+
+    /// Removes function and returns its id, function and its state.
+    /// Locked function is not removed.
+    pub fn remove_func(&mut self, index: usize) -> Option<(usize, Func, GraphFuncState)> {
+        if self.is_locked(index)? {
+            return None;
+        }
+        let removed = self.functions.remove(index)?;
+        self.modified();
+        Some(removed)
+    }
+
     pub fn max_x(&self) -> Option<X> {
         self.functions
             .iter_active()
@@ -325,37 +697,98 @@ impl<'a> Graph {
         self.functions.get_func_state(index)
     }
 
+    /// Inserts values into active functions, which are not locked, one iterator of values
+    /// per function. Returns values replaced in each of them, which are empty for
+    /// `InsertPolicy::Shift`.
     pub fn insert_values<YExactIter: ExactSizeIterator<Item = Y> + Clone>(
         &mut self,
         at: X,
         values: impl IntoIterator<Item = YExactIter>,
-    ) {
-        for func_values in values {
-            self.functions.for_each_active_mut(|f| {
-                f.insert_values(FuncValuesCheckIter::new(func_values.clone(), at).into());
-            });
+        policy: InsertPolicy,
+    ) -> Vec<Box<[Y]>> {
+        let editable = self.functions.editable_indexes();
+        let functions = self.functions.functions.as_mut_slice();
+        let mut replaced = Vec::new();
+        for (fi, func_values) in editable.into_iter().zip(values) {
+            let f = &mut functions[fi];
+            match policy {
+                InsertPolicy::Shift => {
+                    f.insert_values(FuncValuesCheckIter::new(func_values, at).into());
+                }
+                _ => {
+                    let values = func_values.collect::<Vec<_>>();
+                    replaced.push(Self::insert_in_place(
+                        f,
+                        at,
+                        &values,
+                        FuncInsertMode::Values,
+                        policy,
+                    ));
+                }
+            }
         }
+        self.modified();
+        replaced
     }
 
-    pub fn insert_pattern<Iter: IntoIterator<Item = Y>>(&mut self, at: X, values: Iter) {
-        let functions = self.functions.functions.as_mut_slice();
+    /// Inserts the same pattern into all active functions, which are not locked.
+    /// Returns values replaced in each of them, which are empty for `InsertPolicy::Shift`.
+    pub fn insert_pattern<Iter: IntoIterator<Item = Y>>(
+        &mut self,
+        at: X,
+        values: Iter,
+        policy: InsertPolicy,
+    ) -> Vec<Box<[Y]>> {
+        let values = values.into_iter().collect::<Vec<_>>();
+        let mut replaced = Vec::new();
+        self.functions.for_each_editable_mut(|f| match policy {
+            InsertPolicy::Shift => {
+                f.insert_pattern(FuncValuesCheckIter::new(values.iter().copied(), at).into());
+            }
+            _ => replaced.push(Self::insert_in_place(
+                f,
+                at,
+                &values,
+                FuncInsertMode::Pattern,
+                policy,
+            )),
+        });
+        self.modified();
+        replaced
+    }
 
-        let mut active_funcs = self.functions.active_funcs.iter();
+    fn insert_in_place(
+        f: &mut Func,
+        at: X,
+        values: &[Y],
+        mode: FuncInsertMode,
+        policy: InsertPolicy,
+    ) -> Box<[Y]> {
+        match policy {
+            InsertPolicy::Stretch => f.stretch_into_selection(at, values, mode),
+            _ => f.overwrite(at, values, mode),
+        }
+    }
 
-        if let Some(fi) = active_funcs.next() {
-            let mut index = fi + 1;
-            let (f_slice, mut funcs) = functions.split_at_mut(index);
-            let mut range = f_slice
-                .last_mut()
-                .unwrap()
-                .insert_pattern(FuncValuesCheckIter::new(values.into_iter(), at).into());
-            for fi in active_funcs {
-                let (f_slice, functions) = funcs.split_at_mut(fi + 1 - index);
-                funcs = functions;
-                index += fi + 1;
-                range = f_slice.last_mut().unwrap().insert_pattern(range.into());
-            }
+    /// Reverts insertion, which did not shift points.
+    fn restore_in_place<YExactIter: ExactSizeIterator<Item = Y>>(
+        &mut self,
+        at: X,
+        inserted: impl IntoIterator<Item = usize>,
+        policy: InsertPolicy,
+        replaced: impl IntoIterator<Item = YExactIter>,
+    ) {
+        let editable = self.functions.editable_indexes();
+        let functions = self.functions.functions.as_mut_slice();
+        for ((fi, inserted), old) in editable.into_iter().zip(inserted).zip(replaced) {
+            // Stretch writes exactly as many values, as it replaces.
+            let written = match policy {
+                InsertPolicy::Stretch => old.len(),
+                _ => inserted,
+            };
+            functions[fi].restore_overwritten(at, written, old);
         }
+        self.modified();
     }
 
     pub fn undo_op<YExactIter: ExactSizeIterator<Item = Y> + Clone>(
@@ -368,31 +801,53 @@ impl<'a> Graph {
     ) {
         match op {
             SharedOp::Delete(points) => {
-                self.insert_values(*self.selection.start(), points.0.clone());
+                // Deleted values are returned to start of selection of each function.
+                for (fi, values) in self
+                    .functions
+                    .editable_indexes()
+                    .into_iter()
+                    .zip(points.0.clone())
+                {
+                    let at = *self.functions.selection(fi, &self.selection).start();
+                    self.functions.functions[fi]
+                        .insert_values(FuncValuesCheckIter::new(values, at).into());
+                }
+                self.apply_selections();
+                self.modified();
             }
             SharedOp::StretchY(stretch) => {
-                self.stretch_y_with_factor(&StretchY {
-                    factor: 1.0 / stretch.factor,
-                    flags: stretch.flags,
-                });
+                self.stretch_y_with_factor(&stretch.inverted());
+            }
+            SharedOp::InsertValues(points) if points.policy != InsertPolicy::Shift => {
+                self.restore_in_place(
+                    points.x,
+                    points.values.clone().map(|values| values.len()),
+                    points.policy,
+                    points.replaced.clone(),
+                );
             }
             SharedOp::InsertValues(points) => {
-                let len = points.values.clone().map(|f_vals| f_vals.len()).max();
-                if let Some(len) = len {
-                    let new_selection = points.x..=(points.x + len as X);
-                    let selection = self.selection.clone();
-                    self.change_selection(new_selection);
-                    self.delete();
-                    self.change_selection(selection);
+                let editable = self.functions.editable_indexes();
+                let functions = self.functions.functions.as_mut_slice();
+                for (fi, values) in editable.into_iter().zip(points.values.clone()) {
+                    functions[fi].remove_inserted(points.x, values.len());
                 }
+                self.modified();
+            }
+            SharedOp::InsertPattern(points) if points.policy != InsertPolicy::Shift => {
+                self.restore_in_place(
+                    points.x,
+                    std::iter::repeat(points.values.len()),
+                    points.policy,
+                    points.replaced.clone(),
+                );
             }
             SharedOp::InsertPattern(points) => {
                 let len = points.values.len();
-                let new_selection = points.x..=(points.x + len as X);
-                let selection = self.selection.clone();
-                self.change_selection(new_selection);
-                self.delete();
-                self.change_selection(selection);
+                self.functions.for_each_editable_mut(|f| {
+                    f.remove_inserted(points.x, len);
+                });
+                self.modified();
             }
             SharedOp::MoveSelectBy(move_by) => {
                 self.change_selection(move_by.negated().move_selection(&self.selection));
@@ -403,6 +858,29 @@ impl<'a> Graph {
                     funcs.toggle(fi);
                 }
             }
+            SharedOp::ChangeLockedFuncs(change) => {
+                let funcs = &mut self.functions.locked_funcs;
+                for fi in change.clone() {
+                    funcs.toggle(fi);
+                }
+            }
+            SharedOp::ChangeFuncSelection(change) => {
+                self.set_func_selection(change.func, change.old.clone());
+            }
+            SharedOp::ReplaceValues(replace) => {
+                self.replace_values(
+                    replace.func,
+                    replace.x,
+                    replace.new.len(),
+                    replace.old.clone(),
+                );
+            }
+            SharedOp::AddFunc(change) => {
+                self.remove_func(change.index);
+            }
+            SharedOp::RemoveFunc(change) => {
+                self.insert_func_change(change);
+            }
         }
     }
 
@@ -422,10 +900,10 @@ impl<'a> Graph {
                 self.stretch_y_with_factor(stretch);
             }
             SharedOp::InsertValues(points) => {
-                self.insert_values(points.x, points.values.clone());
+                self.insert_values(points.x, points.values.clone(), points.policy);
             }
             SharedOp::InsertPattern(points) => {
-                self.insert_pattern(points.x, points.values.clone());
+                self.insert_pattern(points.x, points.values.clone(), points.policy);
             }
             SharedOp::MoveSelectBy(move_by) => {
                 self.change_selection(move_by.move_selection(&self.selection));
@@ -436,53 +914,194 @@ impl<'a> Graph {
                     funcs.toggle(fi);
                 }
             }
+            SharedOp::ChangeLockedFuncs(change) => {
+                let funcs = &mut self.functions.locked_funcs;
+                for fi in change.clone() {
+                    funcs.toggle(fi);
+                }
+            }
+            SharedOp::ChangeFuncSelection(change) => {
+                self.set_func_selection(change.func, change.new.clone());
+            }
+            SharedOp::ReplaceValues(replace) => {
+                self.replace_values(
+                    replace.func,
+                    replace.x,
+                    replace.old.len(),
+                    replace.new.clone(),
+                );
+            }
+            SharedOp::AddFunc(change) => {
+                self.insert_func_change(change);
+            }
+            SharedOp::RemoveFunc(change) => {
+                self.remove_func(change.index);
+            }
         }
     }
 
+    fn insert_func_change<YExactIter: ExactSizeIterator<Item = Y> + Clone>(
+        &mut self,
+        change: &FuncChange<YExactIter>,
+    ) {
+        let points = FuncValuesCheckIter::new(change.values.clone(), change.x).collect();
+        let mut func = Func::new_from(points);
+        func.set_gaps(change.gaps.clone());
+        self.insert_func(change.index, change.id, func, change.state);
+    }
+
+    /// Deletes selected points of active functions, which are not locked.
+    /// Points after selection are moved into it and become selected.
     pub fn delete(&mut self) {
-        self.functions.for_each_active_mut(|f| {
+        self.functions.for_each_editable_mut(|f| {
             f.delete();
         });
+        // Selection of functions has to depend only on selections of graph and functions,
+        // so undo and redo can reproduce it.
+        self.apply_selections();
+        self.modified();
+    }
+
+    /// Replaces `len` values of function at `index` starting at `at`,
+    /// regardless of whether the function is active. Locked function is not modified.
+    /// Returns replaced values and number of inserted values.
+    pub fn replace_values(
+        &mut self,
+        index: usize,
+        at: X,
+        len: usize,
+        values: impl IntoIterator<Item = Y>,
+    ) -> Option<(Box<[Y]>, usize)> {
+        if self.is_locked(index)? {
+            return None;
+        }
+        let func = self.functions.functions.get_mut(index)?;
+        let res = func.replace_values(at, len, values);
+        self.modified();
+        Some(res)
+    }
+
+    /// Stretch with baseline, which moves with stretched points, replaced by lines of
+    /// editable functions before stretching, so the stretch can be reverted.
+    pub fn resolve_stretch(&self, stretch: &StretchY) -> StretchY {
+        if stretch.baseline.is_fixed() {
+            return stretch.clone();
+        }
+        let mut lines = vec![None; self.functions.functions.len()];
+        for fi in self.functions.editable_indexes() {
+            lines[fi] = self.functions.functions[fi]
+                .stretch_line(&stretch.baseline)
+                .map(|line| (line.k(), line.q()));
+        }
+        stretch.clone().with_baseline(StretchBaseline::Resolved {
+            mode: Box::new(stretch.baseline.clone()),
+            lines: lines.into_boxed_slice(),
+        })
     }
 
     ///Return whether any function was modified
     pub fn stretch_y_with_factor(&mut self, stretch: &StretchY) -> bool {
         let mut stretched = false;
         if stretch.stretches() {
-            self.functions.for_each_active_mut(|f| {
-                let modified = f.stretch_y_with_factor(stretch);
-                if !stretched && modified {
-                    stretched = true;
-                }
-            });
+            for fi in self.functions.editable_indexes() {
+                stretched |= self.stretch_func(fi, stretch);
+            }
+        }
+        if stretched {
+            self.modified();
         }
         stretched
     }
 
-    pub fn stretch_y(&mut self, bounds: &StretchYBounds) -> Result<StretchY, StretchYBoundsError> {
-        let mut factor = Y::INFINITY;
-        let flags = bounds.flags();
-        if flags.is_empty() || bounds.is_empty() {
-            return Ok(StretchY::no_stretch());
+    /// Stretches selected values of function at `index` like `stretch_y_with_factor`,
+    /// regardless of whether the function is active. Locked function is not modified.
+    pub fn stretch_func_y_with_factor(&mut self, index: usize, stretch: &StretchY) -> bool {
+        if self.is_locked(index) != Some(false) || !stretch.stretches() {
+            return false;
         }
-        self.functions.for_each_active_ok_mut(|f| {
+        let stretched = self.stretch_func(index, stretch);
+        if stretched {
+            self.modified();
+        }
+        stretched
+    }
+
+    fn stretch_func(&mut self, index: usize, stretch: &StretchY) -> bool {
+        let func = &mut self.functions.functions[index];
+        match &stretch.baseline {
+            StretchBaseline::Resolved { lines, .. } => match lines.get(index) {
+                Some(Some((k, q))) => {
+                    func.stretch_y_with_factor_around(stretch, &FuncLine::from_k_q(*k, *q))
+                }
+                _ => false,
+            },
+            _ => func.stretch_y_with_factor(stretch),
+        }
+    }
+
+    /// Factor closest to 1, which keeps selected values of editable functions within `bounds`.
+    fn stretch_y_factor(&mut self, bounds: &StretchYBounds) -> Result<Y, StretchYBoundsError> {
+        let mut factor = Y::INFINITY;
+        self.functions.for_each_editable_ok_mut(|f| {
             let new_factor = f.min_y_stretch_factor_for_bounds(bounds)?;
             if (new_factor - 1.0).abs() < (factor - 1.0).abs() {
                 factor = new_factor;
             }
             Ok(())
         })?;
+        Ok(factor)
+    }
+
+    pub fn stretch_y(&mut self, bounds: &StretchYBounds) -> Result<StretchY, StretchYBoundsError> {
+        let flags = bounds.flags();
+        if flags.is_empty() || bounds.is_empty() {
+            return Ok(StretchY::no_stretch());
+        }
+        let factor = self.stretch_y_factor(bounds)?;
 
         if let Some(stretch) = StretchY::new(factor, flags) {
-            self.functions.for_each_active_mut(|f| {
-                f.stretch_y_with_factor(&stretch);
-            });
+            let stretch = self.resolve_stretch(&stretch.with_baseline(bounds.baseline().clone()));
+            self.stretch_y_with_factor(&stretch);
+            self.modified();
             Ok(stretch)
         } else {
             Ok(StretchY::no_stretch())
         }
     }
 
+    /// Like [`Graph::stretch_y`], but factor keeps values within `bounds` in all
+    /// selection ranges. Graph is not modified.
+    pub fn stretch_y_for_ranges(
+        &mut self,
+        bounds: &StretchYBounds,
+    ) -> Result<StretchY, StretchYBoundsError> {
+        let flags = bounds.flags();
+        if flags.is_empty() || bounds.is_empty() {
+            return Ok(StretchY::no_stretch());
+        }
+        let selection = self.selection.clone();
+        let mut factor = Y::INFINITY;
+        let mut res = Ok(());
+        for range in self.selection_ranges() {
+            self.change_selection(range);
+            match self.stretch_y_factor(bounds) {
+                Ok(new_factor) if (new_factor - 1.0).abs() < (factor - 1.0).abs() => {
+                    factor = new_factor
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    res = Err(e);
+                    break;
+                }
+            }
+        }
+        self.change_selection(selection);
+        res?;
+        Ok(StretchY::new(factor, flags)
+            .map(|s| s.with_baseline(bounds.baseline().clone()))
+            .unwrap_or_else(StretchY::no_stretch))
+    }
+
     pub fn change_each_active_func_state(
         &mut self,
         mut apply: impl FnMut(usize, &Func) -> GraphFuncState,
@@ -513,23 +1132,32 @@ impl<'a> Graph {
     }
 
     pub fn min(&self) -> Option<Y> {
+        if !self.extra_selections.is_empty() {
+            return self.ranges_value_range().map(|r| *r.start());
+        }
         self.active_functions()
             .filter_map(|f| f.selection_min())
             .min_by(|a, b| a.total_cmp(b))
     }
 
     pub fn max(&self) -> Option<Y> {
+        if !self.extra_selections.is_empty() {
+            return self.ranges_value_range().map(|r| *r.end());
+        }
         self.active_functions()
             .filter_map(|f| f.selection_max())
             .max_by(|a, b| a.total_cmp(b))
     }
 
     pub fn value_range(&self) -> Option<RangeInclusive<Y>> {
+        if !self.extra_selections.is_empty() {
+            return self.ranges_value_range();
+        }
         self.active_functions()
             .filter_map(|f| f.selection_value_range())
             .fold(None, |a, r| {
                 if let Some(a) = a {
-                    Some((a.start().min(*r.start())..=a.end().max(*r.end())).into())
+                    Some(a.start().min(*r.start())..=a.end().max(*r.end()))
                 } else {
                     Some(r)
                 }
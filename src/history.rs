@@ -4,7 +4,7 @@ use enumflags2::BitFlags;
 use history_stack::{
     entry_builder::EntryBuilder,
     pop_entry::PopEntry,
-    shared_entry::{OtherOp, SharedRedoEntry, SharedUndoEntry},
+    shared_entry::{OtherOp, RedoEntry, SharedRedoEntry, SharedUndoEntry, UndoEntry},
     HistoryError, HistoryOption, HistoryStack, IsGraphOpNonAltering,
 };
 
@@ -19,15 +19,34 @@ pub struct History<
 impl<
         OpGroupId: Clone + Default + PartialEq,
         OpOwned: OtherOp,
-        NonAlteringGraphOpHelper: IsGraphOpNonAltering<OpGroupId>,
+        NonAlteringGraphOpHelper: IsGraphOpNonAltering<OpGroupId> + Default,
     > History<OpGroupId, OpOwned, NonAlteringGraphOpHelper>
 {
     pub fn new() -> Self {
+        Self::with_helper(NonAlteringGraphOpHelper::default())
+    }
+}
+
+impl<
+        OpGroupId: Clone + Default + PartialEq,
+        OpOwned: OtherOp,
+        NonAlteringGraphOpHelper: IsGraphOpNonAltering<OpGroupId>,
+    > History<OpGroupId, OpOwned, NonAlteringGraphOpHelper>
+{
+    pub fn with_helper(helper: NonAlteringGraphOpHelper) -> Self {
         Self {
-            stack: HistoryStack::new(),
+            stack: HistoryStack::with_helper(helper),
         }
     }
 
+    pub fn helper(&self) -> &NonAlteringGraphOpHelper {
+        self.stack.helper()
+    }
+
+    pub fn helper_mut(&mut self) -> &mut NonAlteringGraphOpHelper {
+        self.stack.helper_mut()
+    }
+
     pub fn with_max_size(&mut self, max_size: usize) -> &mut Self {
         self.stack.with_max_size(max_size);
         self
@@ -62,13 +81,13 @@ impl<
 
     pub fn undo_entry<'a>(
         &'a mut self,
-    ) -> Option<SharedUndoEntry<'a, OpGroupId, OpOwned, NonAlteringGraphOpHelper>> {
+    ) -> Option<UndoEntry<'a, OpGroupId, OpOwned, NonAlteringGraphOpHelper>> {
         self.stack.undo()
     }
 
     pub fn redo_entry<'a>(
         &'a mut self,
-    ) -> Option<SharedRedoEntry<'a, OpGroupId, OpOwned, NonAlteringGraphOpHelper>> {
+    ) -> Option<RedoEntry<'a, OpGroupId, OpOwned, NonAlteringGraphOpHelper>> {
         self.stack.redo()
     }
 
@@ -5,7 +5,7 @@ mod op;
 pub mod pop_entry;
 pub mod shared_entry;
 
-use std::{collections::VecDeque, marker::PhantomData, ops::Range, usize};
+use std::{collections::VecDeque, ops::Range, usize};
 
 use entry::Entry;
 use entry_builder::EntryBuilder;
@@ -79,7 +79,9 @@ pub struct HistoryStack<
     max_size: usize,
     open_options: OpenOptions,
     flags: BitFlags<HistoryFlag>,
-    _marker: PhantomData<NonAlteringGraphOpHelper>,
+    helper: NonAlteringGraphOpHelper,
+    /// Whether all ops pushed since the last closed entry were coalesced
+    coalesce_new_entry: bool,
 }
 
 pub trait IsGraphOpNonAltering<OpGroupId> {
@@ -91,6 +93,45 @@ pub trait IsGraphOpNonAltering<OpGroupId> {
         IterChangeActiveFuncs: Iterator<Item = usize> + Clone,
         FuncIter: Iterator<Item = YExactIter>,
         YExactIter: ExactSizeIterator<Item = Y> + Clone;
+
+    /// Whether op, which does not alter history, is coalesced into the next altering entry
+    /// instead of forming its own entry. Coalesced ops are not shown in `undo_iter`,
+    /// they are undone together with the next altering entry, or with the last entry,
+    /// if undo comes first.
+    fn coalesces_graph_op<IterChangeActiveFuncs, FuncIter, YExactIter>(
+        &self,
+        _g_op: &SharedOp<IterChangeActiveFuncs, FuncIter, YExactIter>,
+        _group_id: &OpGroupId,
+    ) -> bool
+    where
+        IterChangeActiveFuncs: Iterator<Item = usize> + Clone,
+        FuncIter: Iterator<Item = YExactIter>,
+        YExactIter: ExactSizeIterator<Item = Y> + Clone,
+    {
+        false
+    }
+}
+
+impl<
+        OpGroupId: Clone + Default + PartialEq,
+        OpOwned: OtherOp,
+        NonAlteringGraphOpHelper: IsGraphOpNonAltering<OpGroupId> + Default,
+    > HistoryStack<OpGroupId, OpOwned, NonAlteringGraphOpHelper>
+{
+    pub fn new() -> Self {
+        Self::with_helper(NonAlteringGraphOpHelper::default())
+    }
+}
+
+impl<
+        OpGroupId: Clone + Default + PartialEq,
+        OpOwned: OtherOp,
+        NonAlteringGraphOpHelper: IsGraphOpNonAltering<OpGroupId> + Default,
+    > Default for HistoryStack<OpGroupId, OpOwned, NonAlteringGraphOpHelper>
+{
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<
@@ -100,7 +141,7 @@ impl<
         NonAlteringGraphOpHelper: IsGraphOpNonAltering<OpGroupId>,
     > HistoryStack<OpGroupId, OpOwned, NonAlteringGraphOpHelper>
 {
-    pub fn new() -> Self {
+    pub fn with_helper(helper: NonAlteringGraphOpHelper) -> Self {
         Self {
             entries: VecDeque::new(),
             ops: VecDeque::new(),
@@ -109,10 +150,20 @@ impl<
             open_options: OpenOptions::None,
             max_size: usize::MAX,
             flags: BitFlags::empty(),
-            _marker: PhantomData,
+            helper,
+            coalesce_new_entry: true,
         }
     }
 
+    pub fn helper(&self) -> &NonAlteringGraphOpHelper {
+        &self.helper
+    }
+
+    /// Changed helper applies to ops pushed from now on.
+    pub fn helper_mut(&mut self) -> &mut NonAlteringGraphOpHelper {
+        &mut self.helper
+    }
+
     pub fn with_max_size(&mut self, max_size: usize) -> &mut Self {
         self.max_size = max_size;
         self
@@ -130,6 +181,9 @@ impl<
     pub fn clear(&mut self) {
         self.ops.clear();
         self.entries.clear();
+        self.len = 0;
+        self.undo_len = 0;
+        self.open_options = OpenOptions::None;
     }
 
     fn treat_non_altering_entries_as_regular(&self) -> bool {
@@ -142,7 +196,7 @@ impl<
     }
 
     fn get_new_entry_start(&self) -> usize {
-        Self::get_opt_entry_op_end(self.entries.get(self.undo_len.saturating_sub(1)))
+        self.get_entry_start(self.undo_len)
     }
 
     fn get_entry_start(&self, index: usize) -> usize {
@@ -170,7 +224,7 @@ impl<
     }
 
     pub fn redo_len(&self) -> usize {
-        self.len().saturating_sub(self.undo_len())
+        self.len - self.undo_len
     }
 
     pub fn len(&self) -> usize {
@@ -183,40 +237,27 @@ impl<
         group_id: &OpGroupId,
         mut op: impl InOp<OpOwned, OpGroupId>,
     ) -> Option<usize> {
-        if op.alters_history(group_id) {
+        let alters_history = op.alters_history(group_id);
+        if alters_history {
             self.no_redo();
-        } else {
-            println!("");
         }
-        let mut start = Self::get_opt_entry_op_end(self.entries.back());
+        let coalesced = !alters_history && op.coalesces(group_id);
+        let start = self.combine_start(group_id);
         let end = self.ops.len();
-        if start >= end && self.open_options == OpenOptions::EntryIsOpen {
-            if let Some(last_entry) = self.entries.back() {
-                if last_entry.id == *group_id {
-                    // There needs to be entries.len(), because that is the only reliable way to get last entry
-                    // in case of non altering entries.
-                    start = self.get_entry_start(self.entries.len() - 1);
-                }
-            }
-        }
-
         if start < end {
             for owned_op in self.ops.range_mut(start..end).rev() {
-                match owned_op {
-                    Op::Other(other) => match op.try_combine(other) {
+                if let Op::Other(other) = owned_op {
+                    match op.try_combine(other) {
                         Ok(_) => return None,
                         Err(e) => match e {
                             OpCombineErr::OpDoesNotHaveEffect => return None,
-                            OpCombineErr::CannotCombine(v) => {
-                                op = v;
-                                if op.order_matters(owned_op.get_order_matters_op()) {
-                                    break;
-                                }
-                            }
+                            OpCombineErr::CannotCombine(v) => op = v,
                         },
-                    },
-                    _ => (),
-                };
+                    }
+                }
+                if op.order_matters(owned_op.get_order_matters_op()) {
+                    break;
+                }
             }
         }
 
@@ -224,6 +265,7 @@ impl<
             Ok(op) => {
                 let index = self.ops.len();
                 self.ops.push_back(Op::Other(op));
+                self.coalesce_new_entry &= coalesced;
                 Some(index)
             }
             Err(e) => match e {
@@ -245,22 +287,13 @@ impl<
     where
         OpOwned: OtherOp,
     {
-        if NonAlteringGraphOpHelper::graph_op_alters_history(&op, group_id) {
+        let alters_history = NonAlteringGraphOpHelper::graph_op_alters_history(&op, group_id);
+        if alters_history {
             self.no_redo();
-        } else {
-            println!("");
         }
-        let mut start = Self::get_opt_entry_op_end(self.entries.back());
+        let coalesced = !alters_history && self.helper.coalesces_graph_op(&op, group_id);
+        let start = self.combine_start(group_id);
         let end = self.ops.len();
-        if start >= end && self.open_options == OpenOptions::EntryIsOpen {
-            if let Some(last_entry) = self.entries.back() {
-                if last_entry.id == *group_id {
-                    // There needs to be entries.len(), because that is the only reliable way to get last entry
-                    // in case of non altering entries.
-                    start = self.get_entry_start(self.entries.len() - 1);
-                }
-            }
-        }
         if start < end {
             for owned_op in self.ops.range_mut(start..end).rev() {
                 match owned_op.try_combine(op) {
@@ -282,6 +315,7 @@ impl<
             Ok(op) => {
                 let index = self.ops.len();
                 self.ops.push_back(op);
+                self.coalesce_new_entry &= coalesced;
                 Some(index)
             }
             Err(e) => match e {
@@ -290,23 +324,66 @@ impl<
         }
     }
 
+    /// Returns whether the last entry was opened with given id and new ops can be added to it.
+    fn last_entry_is_open(&self, id: &OpGroupId) -> bool {
+        // Redo entries cannot be open, so the last entry is either regular undo entry
+        // or non altering entry.
+        let last_is_redo = self.undo_len < self.len && self.len == self.entries.len();
+        self.open_options == OpenOptions::EntryIsOpen
+            && !last_is_redo
+            && self.entries.back().is_some_and(|e| e.id == *id)
+    }
+
+    /// Returns start of ops, with which new op may be combined.
+    fn combine_start(&self, group_id: &OpGroupId) -> usize {
+        let start = Self::get_opt_entry_op_end(self.entries.back());
+        if start >= self.ops.len() && self.last_entry_is_open(group_id) {
+            // There needs to be entries.len(), because that is the only reliable way to get last entry
+            // in case of non altering entries.
+            self.get_entry_start(self.entries.len() - 1)
+        } else {
+            start
+        }
+    }
+
     fn close_new_entry(&mut self, id: OpGroupId) {
         let op_end = self.ops.len();
-        if op_end > self.entries.back().unwrap_or(&Default::default()).op_end {
-            let is_non_altering = self.undo_len < self.len;
-            if !is_non_altering && self.open_options == OpenOptions::EntryIsOpen {
+        let has_new_ops = op_end > Self::get_opt_entry_op_end(self.entries.back());
+        let coalesced = self.coalesce_new_entry;
+        self.coalesce_new_entry = true;
+        if has_new_ops {
+            let extends_last = self.last_entry_is_open(&id)
+                && self
+                    .entries
+                    .back()
+                    .is_some_and(|e| e.coalesced == coalesced);
+            if extends_last {
                 if let Some(last) = self.entries.back_mut() {
-                    if last.id == id {
-                        last.op_end = op_end;
-                        return;
-                    }
+                    last.op_end = op_end;
+                }
+            } else {
+                let is_non_altering = coalesced || self.undo_len < self.len;
+                if !is_non_altering {
+                    // Without redo entries, only coalesced entries can follow undo entries.
+                    self.entries.truncate(self.len);
+                }
+                self.entries.push_back(Entry {
+                    op_end,
+                    id,
+                    coalesced,
+                });
+                if !is_non_altering {
+                    self.undo_len += 1;
+                    self.len += 1;
                 }
             }
-            self.entries.push_back(Entry { op_end, id });
-            if !is_non_altering {
-                self.undo_len += 1;
-                self.len += 1;
-            }
+        }
+        if self.open_options == OpenOptions::OpenEntry {
+            self.open_options = if has_new_ops {
+                OpenOptions::EntryIsOpen
+            } else {
+                OpenOptions::None
+            };
         }
     }
 
@@ -319,22 +396,23 @@ impl<
 
     fn no_redo(&mut self) {
         if self.undo_len < self.len {
-            self.open_options = OpenOptions::None;
-            if self.len < self.entries.len() {
-                // Len is guaranteed to be >= 1, because undo_len, which is usize, is smaller.
-                /*
-                self.entries:
-                | undo entries | redo entries | non altering entries |
-                               ^              ^                      ^
-                          self.undo_len    self.len           self.entries.len()
-                 */
-                let op_redo_end = self.entries[self.len - 1].op_end;
-                self.entries.drain(self.undo_len..self.len);
-                self.ops.drain(self.get_new_entry_start()..op_redo_end);
-            } else {
-                self.entries.truncate(self.undo_len);
-                self.ops.truncate(self.get_new_entry_start());
+            // Len is guaranteed to be >= 1, because undo_len, which is usize, is smaller.
+            /*
+            self.entries:
+            | undo entries | redo entries | non altering entries | ops of new entry
+                           ^              ^                      ^
+                      self.undo_len    self.len           self.entries.len()
+             */
+            let op_redo_end = self.entries[self.len - 1].op_end;
+            let op_redo_start = self.get_new_entry_start();
+            self.entries.drain(self.undo_len..self.len);
+            self.ops.drain(op_redo_start..op_redo_end);
+            let removed = op_redo_end - op_redo_start;
+            for entry in self.entries.range_mut(self.undo_len..) {
+                entry.op_end -= removed;
             }
+            // Ops of coalesced entries become part of the entry after them.
+            self.entries.retain(|e| !e.coalesced);
             self.len = self.entries.len();
             self.undo_len = self.len;
         }
@@ -343,34 +421,34 @@ impl<
     fn no_non_altering_entries(&mut self) {
         if self.len < self.entries.len() {
             self.entries.truncate(self.len);
-            self.ops
-                .truncate(self.get_entry_start(self.len.saturating_sub(1)));
+            self.ops.truncate(self.get_entry_start(self.len));
         }
     }
 
     fn non_altering_op_range(&self) -> Range<usize> {
-        if self.len >= 1 {
-            self.entries[self.len - 1].op_end..Self::get_opt_entry_op_end(self.entries.back())
-        } else {
-            0..0
-        }
+        self.get_entry_start(self.len)..Self::get_opt_entry_op_end(self.entries.back())
     }
 
+    /// Non altering entries are reverted together with the undone entry and then forgotten.
     pub fn undo(
         &'a mut self,
-    ) -> Option<SharedUndoEntry<'a, OpGroupId, OpOwned, NonAlteringGraphOpHelper>> {
+    ) -> Option<UndoEntry<'a, OpGroupId, OpOwned, NonAlteringGraphOpHelper>> {
         self.open_options = OpenOptions::None;
-        self.undo_len = self.undo_len.saturating_sub(1);
-        SharedUndoEntry::new(self.undo_len, self)
+        if self.undo_len < 1 {
+            return None;
+        }
+        self.undo_len -= 1;
+        UndoEntry::new(self.undo_len, self)
     }
 
+    /// Non altering entries are reverted before the redone entry and then forgotten.
     pub fn redo(
         &'a mut self,
-    ) -> Option<SharedRedoEntry<'a, OpGroupId, OpOwned, NonAlteringGraphOpHelper>> {
-        if self.undo_len < self.len() {
+    ) -> Option<RedoEntry<'a, OpGroupId, OpOwned, NonAlteringGraphOpHelper>> {
+        self.open_options = OpenOptions::None;
+        if self.undo_len < self.len {
             self.undo_len += 1;
-            self.open_options = OpenOptions::None;
-            return SharedRedoEntry::new(self.undo_len - 1, self);
+            return RedoEntry::new(self.undo_len - 1, self);
         }
         None
     }
@@ -389,6 +467,7 @@ impl<
         non_altering_range
             .into_iter()
             .rev()
+            .filter(|i| !self.entries[*i].coalesced)
             .chain(range.into_iter().rev())
             .map(|i| SharedUndoEntry::new(i, self).unwrap())
     }
@@ -407,6 +486,7 @@ impl<
         non_altering_range
             .into_iter()
             .rev()
+            .filter(|i| !self.entries[*i].coalesced)
             .chain(range.into_iter())
             .map(|i| SharedRedoEntry::new(i, self).unwrap())
     }
@@ -421,11 +501,7 @@ impl<
     }
 
     fn non_atering_ops_count(&self) -> usize {
-        if self.len >= 1 {
-            self.ops.len() - self.entries[self.len - 1].op_end
-        } else {
-            0
-        }
+        self.non_altering_op_range().len()
     }
 
     pub fn push_entry(
@@ -440,11 +516,10 @@ impl<
         &mut self,
         id: OpGroupId,
     ) -> EntryBuilder<OpGroupId, OpOwned, NonAlteringGraphOpHelper> {
+        // Only undo entries can be forgotten, so when there are none, history may temporarily
+        // have more entries than max_size.
         if self.entries.len() >= self.max_size {
             self.pop_first();
-            if self.entries.len() >= self.max_size {
-                unreachable!("Too many entries!\nThis should never happen!");
-            }
         }
         EntryBuilder::new(self, id)
     }
@@ -453,14 +528,10 @@ impl<
         &mut self,
         id: OpGroupId,
     ) -> EntryBuilder<OpGroupId, OpOwned, NonAlteringGraphOpHelper> {
-        self.open_options = if self.open_options == OpenOptions::None {
-            OpenOptions::OpenEntry
-        } else {
-            OpenOptions::EntryIsOpen
-        };
-        if self.open_options == OpenOptions::EntryIsOpen {
+        if self.last_entry_is_open(&id) {
             EntryBuilder::new(self, id)
         } else {
+            self.open_options = OpenOptions::OpenEntry;
             self.build_new_entry(id)
         }
     }
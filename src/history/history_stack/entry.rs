@@ -2,4 +2,6 @@
 pub struct Entry<OpGroupId: Clone + Default> {
     pub op_end: usize,
     pub id: OpGroupId,
+    /// Entry of non altering ops, which is merged into the next altering entry
+    pub coalesced: bool,
 }
@@ -1,11 +1,16 @@
 pub mod change_active_funcs;
 
-use std::{convert::Infallible, iter::Copied, marker::PhantomData, slice::Iter};
+use std::{convert::Infallible, iter::Copied, marker::PhantomData, ops::Range, slice::Iter};
 
 use change_active_funcs::{ChangeActiveFuncs, ChangeActiveFuncsIter};
+use enumflags2::BitFlags;
 
 use crate::{
-    shared_op::{Delete, InsertPattern, MoveSelectBy, SharedOp, StretchY},
+    graph::GraphFuncState,
+    shared_op::{
+        Delete, FuncChange, FuncSelection, InsertPattern, InsertPolicy, MoveSelectBy,
+        ReplaceValues, SharedOp, StretchY,
+    },
     types::point::{Point, X, Y},
 };
 
@@ -49,14 +54,60 @@ impl<'a> Iterator for FuncIter<'a> {
 
 pub type OutSharedOp<'a> = SharedOp<ChangeActiveFuncsIter<'a>, FuncIter<'a>, Copied<Iter<'a, Y>>>;
 
+#[derive(Debug)]
+pub(super) struct OwnedFuncChange {
+    index: usize,
+    id: usize,
+    x: X,
+    values: Box<[Y]>,
+    state: GraphFuncState,
+    gaps: Box<[Range<X>]>,
+}
+
+impl OwnedFuncChange {
+    fn new<YIter: IntoIterator<Item = Y>>(change: FuncChange<YIter>) -> Self {
+        Self {
+            index: change.index,
+            id: change.id,
+            x: change.x,
+            values: change
+                .values
+                .into_iter()
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+            state: change.state,
+            gaps: change.gaps.into_boxed_slice(),
+        }
+    }
+
+    fn get_shared<'a>(&'a self) -> FuncChange<Copied<Iter<'a, Y>>> {
+        FuncChange {
+            index: self.index,
+            id: self.id,
+            x: self.x,
+            values: self.values.iter().copied(),
+            state: self.state,
+            gaps: self.gaps.to_vec(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(super) enum Op<OtherOp> {
     Delete(Box<[Box<[Y]>]>),
     StretchY(StretchY),
-    InsertValues(X, Box<[Box<[Y]>]>),
-    InsertPattern(X, Box<[Y]>),
+    /// x, policy, inserted values, replaced values
+    InsertValues(X, InsertPolicy, Box<[Box<[Y]>]>, Box<[Box<[Y]>]>),
+    /// x, policy, pattern, replaced values
+    InsertPattern(X, InsertPolicy, Box<[Y]>, Box<[Box<[Y]>]>),
     MoveSelectBy(f64, f64),
     ChangeActiveFuncs(ChangeActiveFuncs),
+    ChangeLockedFuncs(ChangeActiveFuncs),
+    ChangeFuncSelection(FuncSelection),
+    /// Function index, x, old values, new values
+    ReplaceValues(usize, X, Box<[Y]>, Box<[Y]>),
+    AddFunc(OwnedFuncChange),
+    RemoveFunc(OwnedFuncChange),
     Other(OtherOp),
 }
 
@@ -71,9 +122,20 @@ impl<'a, OpOther> Op<OpOther> {
     ) -> Result<(), OpCombineErr<SharedOp<IterChangeActiveFuncs, FuncIter, YExactIter>>> {
         match (self, &shared) {
             (Self::StretchY(op), SharedOp::StretchY(shared_stretch)) => {
-                if op.flags == shared_stretch.flags {
+                // One sided stretch with negative factor moves points to the other side,
+                // where following stretch would not affect them in the same way.
+                let keeps_sides = op.flags == BitFlags::all() || op.factor.is_sign_positive();
+                // Powers of factors with the same weights multiply as factors do.
+                // Stretches combine only around the same lines.
+                let same_profile =
+                    op.profile == shared_stretch.profile && op.baseline == shared_stretch.baseline;
+                if op.flags == shared_stretch.flags && keeps_sides && same_profile {
                     op.factor *= shared_stretch.factor;
-                } else if (op.factor - shared_stretch.factor).abs() < Y::EPSILON {
+                } else if same_profile
+                    && (op.factor - shared_stretch.factor).abs() < Y::EPSILON
+                    && (op.flags & shared_stretch.flags).is_empty()
+                    && op.factor.is_sign_positive()
+                {
                     op.flags |= shared_stretch.flags;
                 } else {
                     return Err(OpCombineErr::CannotCombine(shared));
@@ -91,6 +153,22 @@ impl<'a, OpOther> Op<OpOther> {
                     return Err(OpCombineErr::OpDoesNotHaveEffect);
                 }
             }
+            (Self::ChangeLockedFuncs(op), SharedOp::ChangeLockedFuncs(shared)) => {
+                for i in shared.clone() {
+                    op.toggle(i);
+                }
+                if op.len() < 1 {
+                    return Err(OpCombineErr::OpDoesNotHaveEffect);
+                }
+            }
+            (Self::ChangeFuncSelection(op), SharedOp::ChangeFuncSelection(shared))
+                if op.func == shared.func =>
+            {
+                op.new = shared.new.clone();
+                if op.old == op.new {
+                    return Err(OpCombineErr::OpDoesNotHaveEffect);
+                }
+            }
             _ => return Err(OpCombineErr::CannotCombine(shared)),
         }
         Ok(())
@@ -127,30 +205,50 @@ impl<'a, OtherOp> Op<OtherOp> {
                 g(op)
             }
             Op::StretchY(f) => g(SharedOp::StretchY(f.clone())),
-            Op::InsertValues(x, values) => {
+            Op::InsertValues(x, policy, values, replaced) => {
                 g(SharedOp::InsertValues(crate::shared_op::InsertValues {
                     x: *x,
                     values: FuncIter {
                         iter: values.iter(),
                     },
+                    policy: *policy,
+                    replaced: FuncIter {
+                        iter: replaced.iter(),
+                    },
+                }))
+            }
+            Self::InsertPattern(x, policy, pattern, replaced) => {
+                g(SharedOp::InsertPattern(InsertPattern {
+                    x: *x,
+                    values: pattern.iter().copied(),
+                    policy: *policy,
+                    replaced: FuncIter {
+                        iter: replaced.iter(),
+                    },
                 }))
             }
-            Self::InsertPattern(x, pattern) => g(SharedOp::InsertPattern(InsertPattern {
-                x: *x,
-                values: pattern.iter().copied(),
-            })),
             Op::MoveSelectBy(start, end) => g(SharedOp::MoveSelectBy(MoveSelectBy {
                 start_by: *start,
                 end_by: *end,
             })),
             Op::ChangeActiveFuncs(change) => g(SharedOp::ChangeActiveFuncs(change.iter())),
+            Op::ChangeLockedFuncs(change) => g(SharedOp::ChangeLockedFuncs(change.iter())),
+            Op::ChangeFuncSelection(change) => g(SharedOp::ChangeFuncSelection(change.clone())),
+            Op::ReplaceValues(func, x, old, new) => g(SharedOp::ReplaceValues(ReplaceValues {
+                func: *func,
+                x: *x,
+                old: old.iter().copied(),
+                new: new.iter().copied(),
+            })),
+            Op::AddFunc(change) => g(SharedOp::AddFunc(change.get_shared())),
+            Op::RemoveFunc(change) => g(SharedOp::RemoveFunc(change.get_shared())),
             Op::Other(op) => OrderMattersOp::Other(op),
         }
     }
 
     pub fn get_shared<SharedOtherOp>(&'a self) -> SharedOutOp<'a, SharedOtherOp>
     where
-        OtherOp: OwnedOp< SharedOtherOp>,
+        OtherOp: OwnedOp<SharedOtherOp>,
     {
         match self.get_order_matters_op() {
             OrderMattersOp::Graph(g) => SharedOutOp::Graph(g),
@@ -162,7 +260,7 @@ impl<'a, OtherOp> Op<OtherOp> {
         op: Option<&'a Self>,
     ) -> Option<SharedOutOp<'a, SharedOtherOp>>
     where
-        OtherOp: OwnedOp< SharedOtherOp>,
+        OtherOp: OwnedOp<SharedOtherOp>,
     {
         if let Some(op) = op {
             Some(op.get_shared())
@@ -172,6 +270,18 @@ impl<'a, OtherOp> Op<OtherOp> {
     }
 }
 
+impl<OtherOp> Op<OtherOp> {
+    fn collect_func_values<YIter: IntoIterator<Item = Y>>(
+        values: impl IntoIterator<Item = YIter>,
+    ) -> Box<[Box<[Y]>]> {
+        values
+            .into_iter()
+            .map(|vals| vals.into_iter().collect::<Vec<_>>().into_boxed_slice())
+            .collect::<Vec<_>>()
+            .into_boxed_slice()
+    }
+}
+
 impl<
         'a,
         IterChangeActiveFuncs: Iterator<Item = usize> + Clone,
@@ -212,19 +322,24 @@ impl<
                 if insert.len() < 1 {
                     return Err(OpCreateErr::OpDoesNotHaveEffect);
                 }
-                Self::InsertPattern(pattern.x, insert)
+                Self::InsertPattern(
+                    pattern.x,
+                    pattern.policy,
+                    insert,
+                    Self::collect_func_values(pattern.replaced),
+                )
             }
             SharedOp::InsertValues(points) => {
-                let insert = points
-                    .values
-                    .into_iter()
-                    .map(|vals| vals.into_iter().collect::<Vec<_>>().into_boxed_slice())
-                    .collect::<Vec<_>>()
-                    .into_boxed_slice();
+                let insert = Self::collect_func_values(points.values);
                 if insert.len() < 1 {
                     return Err(OpCreateErr::OpDoesNotHaveEffect);
                 }
-                Self::InsertValues(points.x, insert)
+                Self::InsertValues(
+                    points.x,
+                    points.policy,
+                    insert,
+                    Self::collect_func_values(points.replaced),
+                )
             }
             SharedOp::MoveSelectBy(move_by) => {
                 if !move_by.is_move() {
@@ -239,6 +354,29 @@ impl<
                     return Err(OpCreateErr::OpDoesNotHaveEffect);
                 }
             }
+            SharedOp::ChangeLockedFuncs(change) => {
+                if let Some(change) = ChangeActiveFuncs::new(change) {
+                    Self::ChangeLockedFuncs(change)
+                } else {
+                    return Err(OpCreateErr::OpDoesNotHaveEffect);
+                }
+            }
+            SharedOp::ChangeFuncSelection(change) => {
+                if change.old == change.new {
+                    return Err(OpCreateErr::OpDoesNotHaveEffect);
+                }
+                Self::ChangeFuncSelection(change)
+            }
+            SharedOp::ReplaceValues(replace) => {
+                let old = replace.old.collect::<Vec<_>>().into_boxed_slice();
+                let new = replace.new.collect::<Vec<_>>().into_boxed_slice();
+                if old == new {
+                    return Err(OpCreateErr::OpDoesNotHaveEffect);
+                }
+                Self::ReplaceValues(replace.func, replace.x, old, new)
+            }
+            SharedOp::AddFunc(change) => Self::AddFunc(OwnedFuncChange::new(change)),
+            SharedOp::RemoveFunc(change) => Self::RemoveFunc(OwnedFuncChange::new(change)),
         };
         Ok(res)
     }
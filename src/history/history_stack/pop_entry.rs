@@ -24,7 +24,8 @@ impl<
         index: usize,
         history: &'a mut HistoryStack<OpGroupId, OpOwned, NonAlteringGraphOpHelper>,
     ) -> Option<Self> {
-        if history.entries.get(index).is_some() {
+        // Only undo entries can be popped, redo entries depend on the entries before them.
+        if index < history.undo_len {
             Some(Self { history, index })
         } else {
             None
@@ -48,7 +49,7 @@ impl<
 {
     pub fn iter<OpOut>(&'a self) -> impl Iterator<Item = SharedOutOp<'a, OpOut>>
     where
-        OpOwned: OwnedOp< OpOut>,
+        OpOwned: OwnedOp<OpOut>,
     {
         let iter = self
             .history
@@ -66,9 +67,14 @@ impl<
     > Drop for PopEntry<'a, OpGroupId, OpOwned, NonAlteringGraphOpHelper>
 {
     fn drop(&mut self) {
-        self.history
-            .ops
-            .drain(self.history.get_entry_op_range(self.index));
-        self.history.entries.pop_front();
+        let range = self.history.get_entry_op_range(self.index);
+        let removed = range.len();
+        self.history.ops.drain(range);
+        self.history.entries.remove(self.index);
+        for entry in self.history.entries.range_mut(self.index..) {
+            entry.op_end -= removed;
+        }
+        self.history.len -= 1;
+        self.history.undo_len -= 1;
     }
 }
@@ -43,6 +43,11 @@ pub trait InOp<Owned, ActionId>: Sized + TryInto<Owned, Error = OpCreateErr> {
     }
 
     fn alters_history(&self, id: &ActionId) -> bool;
+
+    /// See `IsGraphOpNonAltering::coalesces_graph_op`.
+    fn coalesces(&self, _id: &ActionId) -> bool {
+        false
+    }
 }
 
 pub enum SharedInOp<IterChangeActiveFuncs, FuncIter, YExactIter, Other>
@@ -177,8 +182,7 @@ impl<
     ) -> bool {
         // index is always < usize::MAX, because number of entries is limited to usize::MAX,
         // so this should always work as |undo_len - index| <= 1
-        (history.undo_len.wrapping_sub(index)) <= 1
-            && history.treat_non_altering_entries_as_regular()
+        (history.undo_len.wrapping_sub(index)) <= 1 && history.non_atering_ops_count() > 0
     }
 
     fn include_non_altering_ops(&self) -> bool {
@@ -225,7 +229,12 @@ impl<
                 history.ops.range(0..0),
             )
         } else {
-            let non_altering_range = history.non_altering_op_range();
+            // Non altering ops are reverted only by the entry, which is undone or redone next.
+            let non_altering_range = if Self::get_include_non_altering_ops(history, index) {
+                history.non_altering_op_range()
+            } else {
+                0..0
+            };
             SharedEntryIter::new(
                 history.ops.range(non_altering_range),
                 Self::get_op_range_iter(&history, index),
@@ -235,7 +244,7 @@ impl<
 
     fn iter<OpOut, const UNDO: bool>(&'a self) -> impl Iterator<Item = ApplyOp<'a, OpOut>>
     where
-        OpOwned: OwnedOp< OpOut>,
+        OpOwned: OwnedOp<OpOut>,
     {
         Self::get_iter::<OpOut, UNDO>(&self.history, self.index)
     }
@@ -306,18 +315,15 @@ where
 {
     type Item = ApplyOp<'a, OpOut>;
 
+    /// Undone ops are returned from the newest, redone ops from the oldest.
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(op) = self.non_altering.next() {
-            Some(ApplyOp::new_undo(op.get_shared()))
-        } else if let Some(op) = self.next.next() {
-            let shared = op.get_shared();
-            Some(if UNDO {
-                ApplyOp::new_undo(shared)
-            } else {
-                ApplyOp::new_redo(shared)
-            })
+        if let Some(op) = self.non_altering.next_back() {
+            return Some(ApplyOp::new_undo(op.get_shared()));
+        }
+        if UNDO {
+            Some(ApplyOp::new_undo(self.next.next_back()?.get_shared()))
         } else {
-            None
+            Some(ApplyOp::new_redo(self.next.next()?.get_shared()))
         }
     }
 }
@@ -370,6 +376,14 @@ impl<
     {
         self.entry.undo_iter()
     }
+
+    /// Iterates only ops of this entry (without non altering ops) in order in which they were recorded.
+    pub fn ops<OpOut>(&self) -> impl Iterator<Item = SharedOutOp<'a, OpOut>>
+    where
+        OpOwned: OwnedOp<OpOut>,
+    {
+        self.entry.op_range_iter().map(|op| op.get_shared())
+    }
 }
 
 pub struct SharedRedoEntry<
@@ -472,7 +486,7 @@ trait SpecializedEntry<
 
     fn undo_iter<'b, OpOut>(&'b self) -> impl Iterator<Item = ApplyOp<'b, OpOut>>
     where
-        OpOwned: OwnedOp< OpOut> + 'b,
+        OpOwned: OwnedOp<OpOut> + 'b,
         NonAlteringGraphOpHelper: 'b,
         OpGroupId: 'b,
     {
@@ -482,7 +496,7 @@ trait SpecializedEntry<
 
     fn redo_iter<'b, OpOut>(&'b self) -> impl Iterator<Item = ApplyOp<'b, OpOut>>
     where
-        OpOwned: OwnedOp< OpOut> + 'b,
+        OpOwned: OwnedOp<OpOut> + 'b,
         NonAlteringGraphOpHelper: 'b,
         OpGroupId: 'b,
     {
@@ -583,6 +597,33 @@ impl<
     }
 }
 
+impl<
+        'a,
+        OpGroupId: Clone + Default + PartialEq,
+        OpOwned: OtherOp,
+        NonAlteringGraphOpHelper: IsGraphOpNonAltering<OpGroupId>,
+    > UndoEntry<'a, OpGroupId, OpOwned, NonAlteringGraphOpHelper>
+{
+    pub fn id(&self) -> &OpGroupId {
+        SpecializedEntry::id(self)
+    }
+
+    pub fn len(&self) -> usize {
+        SpecializedEntry::len(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter<OpOut>(&self) -> impl Iterator<Item = ApplyOp<'_, OpOut>>
+    where
+        OpOwned: OwnedOp<OpOut>,
+    {
+        self.undo_iter()
+    }
+}
+
 impl<
         'a,
         OpGroupId: Clone + Default + PartialEq,
@@ -642,6 +683,33 @@ impl<
     }
 }
 
+impl<
+        'a,
+        OpGroupId: Clone + Default + PartialEq,
+        OpOwned: OtherOp,
+        NonAlteringGraphOpHelper: IsGraphOpNonAltering<OpGroupId>,
+    > RedoEntry<'a, OpGroupId, OpOwned, NonAlteringGraphOpHelper>
+{
+    pub fn id(&self) -> &OpGroupId {
+        SpecializedEntry::id(self)
+    }
+
+    pub fn len(&self) -> usize {
+        SpecializedEntry::len(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter<OpOut>(&self) -> impl Iterator<Item = ApplyOp<'_, OpOut>>
+    where
+        OpOwned: OwnedOp<OpOut>,
+    {
+        self.redo_iter()
+    }
+}
+
 impl<
         'a,
         OpGroupId: Clone + Default + PartialEq,
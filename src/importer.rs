@@ -6,7 +6,7 @@ use std::{
     num::ParseFloatError,
 };
 
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime, ParseError};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, ParseError, TimeDelta};
 use csv::{Reader, StringRecord};
 use csv_sniffer::error::SnifferError;
 use eframe::Result;
@@ -19,7 +19,7 @@ use crate::{
     functions::function::func_builder::FuncBuilder,
     types::{
         datetime_f64_mapper::{DateTimeF64Mapper, DateTimePrecision},
-        point::Point,
+        point::{Point, X},
     },
 };
 
@@ -147,6 +147,15 @@ pub struct DateTimeInfo {
     pub mapper: DateTimeF64Mapper,
 }
 
+/// Distance of samples, which is recorded as gap.
+#[derive(Debug, Clone, Copy)]
+pub struct GapThreshold {
+    /// Used for x column with dates, converted to x by mapper of imported data
+    pub time: TimeDelta,
+    /// Used for x column with numbers
+    pub x: X,
+}
+
 pub struct Importer {
     pub names: Vec<String>,
     pub mapper: Option<DateTimeInfo>,
@@ -397,9 +406,12 @@ impl Importer {
         Self::parse_date_w_format(str, format, is_date_only_format).map(|d| (d, format))
     }
 
+    /// Imports columns of csv file at `path` into `functions`. Distances of samples longer
+    /// than `gap_threshold` are recorded as gaps, see [`FuncBuilder::with_gap_threshold`].
     pub fn import(
         path: String,
         functions: &mut Vec<FuncBuilder>,
+        gap_threshold: GapThreshold,
     ) -> Result<Importer, ImporterError> {
         static Y_MATCHER: Lazy<Regex> = Lazy::new(|| Regex::new(r"hodnota").unwrap());
 
@@ -408,10 +420,16 @@ impl Importer {
         let mut csv = Self::open_csv(path, StringRecord::new(), &Y_MATCHER, "Sloupec")?;
         functions.clear();
         for _i in 0..csv.columns.ys.len() {
-            functions.push(FuncBuilder::new());
+            functions.push(FuncBuilder::new().with_gap_threshold(gap_threshold.x));
         }
 
-        match Self::read_csv(&mut csv.reader, &mut csv.record, &csv.columns, functions) {
+        match Self::read_csv(
+            &mut csv.reader,
+            &mut csv.record,
+            &csv.columns,
+            functions,
+            gap_threshold.time,
+        ) {
             Ok(mapper) => Ok(Importer {
                 mapper,
                 names: csv.headers,
@@ -425,6 +443,7 @@ impl Importer {
         record: &mut StringRecord,
         columns: &Columns<TIter>,
         functions: &mut [FuncBuilder],
+        time_gap_threshold: TimeDelta,
     ) -> Result<Option<DateTimeInfo>, ImporterError>
     where
         TIter: IntoIterator<Item = usize> + Clone,
@@ -468,8 +487,18 @@ impl Importer {
                         let date = Self::parse_date(x)?.0;
 
                         let mapper = match &mapper {
-                            None => &mapper
-                                .insert(DateTimeF64Mapper::new(date, DateTimePrecision::Minutes)),
+                            None => {
+                                let mapper = mapper.insert(DateTimeF64Mapper::new(
+                                    date,
+                                    DateTimePrecision::Minutes,
+                                ));
+                                // Mapper is created at the first record, so no point is added yet.
+                                let threshold = mapper.time_delta_to_f64(&time_gap_threshold);
+                                for func in functions.iter_mut() {
+                                    *func = FuncBuilder::new().with_gap_threshold(threshold);
+                                }
+                                &*mapper
+                            }
                             Some(mapper) => mapper,
                         };
                         mapper.time_to_f64(&date)
@@ -484,7 +513,12 @@ impl Importer {
                 .map(|c| &record[c])
                 .zip(functions.iter_mut())
             {
-                let y = parse_f64(y_str)?;
+                // Empty cell is missing value.
+                let y = if y_str.trim().is_empty() {
+                    f64::NAN
+                } else {
+                    parse_f64(y_str)?
+                };
                 func.add_point(&Point { x, y }).unwrap();
             }
 
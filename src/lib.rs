@@ -1,10 +1,12 @@
 pub mod date_time_ctx;
+pub mod exporter;
 pub mod functions;
 pub mod graph;
 pub mod history;
 pub mod importer;
 pub mod layout;
 pub mod reversible_graph;
+pub mod selection_rule;
 pub mod shared_op;
 pub mod stretchers;
 pub mod types;
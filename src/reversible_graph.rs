@@ -1,4 +1,5 @@
 pub mod basic_reversible_graph;
+pub mod graph_macro;
 
 use std::{
     iter::{self},
@@ -6,7 +7,10 @@ use std::{
 };
 
 use crate::{
-    functions::function::{Func, StretchY, StretchYBounds, StretchYBoundsError},
+    functions::function::{
+        func_values_check_iter::FuncValuesCheckIter, resample::Resample, Func, StretchY,
+        StretchYBounds, StretchYBoundsError,
+    },
     graph::{Graph, GraphFuncState},
     history::{
         history_stack::{
@@ -16,7 +20,10 @@ use crate::{
         },
         History,
     },
-    shared_op::{Delete, InsertPattern, InsertValues, MoveSelectBy, SharedOp},
+    shared_op::{
+        Delete, FuncChange, FuncSelection, InsertPattern, InsertPolicy, InsertValues, MoveSelectBy,
+        ReplaceValues, SharedOp,
+    },
     types::point::{X, Y},
 };
 
@@ -34,6 +41,12 @@ pub type ActiveFuncsIterSharedOp<Iter> =
     SharedOp<Iter, iter::Empty<iter::Empty<Y>>, iter::Empty<Y>>;
 pub type NoIterSharedOp = ActiveFuncsIterSharedOp<std::iter::Empty<usize>>;
 
+fn copied_func_values(
+    values: &[Box<[Y]>],
+) -> impl Iterator<Item = iter::Copied<std::slice::Iter<'_, Y>>> + Clone {
+    values.iter().map(|values| values.iter().copied())
+}
+
 pub struct ActionBuilderBase<
     'a,
     ActionGroupID: Clone + Default + PartialEq,
@@ -67,34 +80,190 @@ impl<
     pub fn insert_values<YExactIter: ExactSizeIterator<Item = Y> + Clone>(
         &mut self,
         at: X,
-        points: impl IntoIterator<Item = YExactIter> + Clone,
+        points: impl IntoIterator<Item = YExactIter>,
+        policy: InsertPolicy,
         graph: &mut Graph,
     ) {
+        let values = points
+            .into_iter()
+            .map(|values| values.collect::<Vec<_>>().into_boxed_slice())
+            .collect::<Vec<_>>();
+        let replaced = graph.insert_values(at, copied_func_values(&values), policy);
         self.history
             .add_graph_op(SharedOp::<iter::Empty<usize>, _, _>::InsertValues(
                 InsertValues {
                     x: at,
-                    values: points.clone().into_iter(),
+                    values: copied_func_values(&values),
+                    policy,
+                    replaced: copied_func_values(&replaced),
                 },
             ));
-        graph.insert_values(at, points)
     }
 
-    pub fn insert_pattern<YExactIter: ExactSizeIterator<Item = Y> + Clone>(
+    pub fn insert_pattern(
         &mut self,
         at: X,
-        points: impl IntoIterator<IntoIter = YExactIter, Item = Y> + Clone,
+        points: impl IntoIterator<Item = Y>,
+        policy: InsertPolicy,
         graph: &mut Graph,
     ) {
-        self.history.add_graph_op(
-            SharedOp::<iter::Empty<usize>, iter::Empty<YExactIter>, _>::InsertPattern(
+        let values = points.into_iter().collect::<Vec<_>>();
+        let replaced = graph.insert_pattern(at, values.iter().copied(), policy);
+        self.history
+            .add_graph_op(SharedOp::<iter::Empty<usize>, _, _>::InsertPattern(
                 InsertPattern {
                     x: at,
-                    values: points.clone().into_iter(),
+                    values: values.iter().copied(),
+                    policy,
+                    replaced: copied_func_values(&replaced),
                 },
-            ),
-        );
-        graph.insert_pattern(at, points)
+            ));
+    }
+
+    pub fn replace_values(
+        &mut self,
+        func: usize,
+        at: X,
+        len: usize,
+        values: impl IntoIterator<Item = Y>,
+        graph: &mut Graph,
+    ) {
+        let values = values.into_iter().collect::<Vec<_>>();
+        if let Some((old, inserted)) = graph.replace_values(func, at, len, values.iter().copied()) {
+            self.history.add_graph_op(SharedOp::<
+                iter::Empty<usize>,
+                iter::Empty<iter::Copied<std::slice::Iter<Y>>>,
+                _,
+            >::ReplaceValues(ReplaceValues {
+                func,
+                x: at,
+                old: old.iter().copied(),
+                new: values[..inserted].iter().copied(),
+            }));
+        }
+    }
+
+    /// Appends new function with first point at `x` and returns its index.
+    pub fn add_func(
+        &mut self,
+        x: X,
+        values: impl IntoIterator<Item = Y>,
+        state: GraphFuncState,
+        graph: &mut Graph,
+    ) -> usize {
+        let index = graph.len();
+        let id = graph.next_func_id();
+        self.insert_func(index, id, Self::new_func(x, values), state, graph);
+        index
+    }
+
+    fn insert_func(
+        &mut self,
+        index: usize,
+        id: usize,
+        func: Func,
+        state: GraphFuncState,
+        graph: &mut Graph,
+    ) {
+        let x = func.points().first().map(|p| p.x).unwrap_or_default();
+        let values = func.points().iter().map(|p| p.y).collect::<Vec<_>>();
+        let gaps = func.gaps().to_vec();
+        if graph.insert_func(index, id, func, state) {
+            self.history
+                .add_graph_op(SharedOp::<iter::Empty<usize>, iter::Empty<_>, _>::AddFunc(
+                    FuncChange {
+                        index,
+                        id,
+                        x,
+                        values: values.iter().copied(),
+                        state,
+                        gaps,
+                    },
+                ));
+        }
+    }
+
+    fn new_func(x: X, values: impl IntoIterator<Item = Y>) -> Func {
+        Func::new_from(FuncValuesCheckIter::new(values.into_iter(), x).collect())
+    }
+
+    /// Replaces every function by function resampled by `resample`, regardless of whether
+    /// it is active or locked. Functions keep their ids, states and locks. Selections are
+    /// moved to resampled x, gaps of functions are dropped, undo gives them back.
+    pub fn resample(&mut self, resample: &Resample, graph: &mut Graph) {
+        if !resample.is_valid() {
+            return;
+        }
+        let map = |r: &RangeInclusive<X>| resample.x(*r.start())..=resample.x(*r.end());
+        let ranges = graph.extra_selections().iter().map(map).collect::<Vec<_>>();
+        let selection = map(graph.selection());
+        for (index, resampled) in graph.resampled(resample).into_iter().enumerate() {
+            let (Some((x, values)), Some(id), Some(state)) =
+                (resampled, graph.func_id(index), graph.get_func_state(index))
+            else {
+                continue;
+            };
+            let own_selection = graph.func_selection(index).map(map);
+            let locked = graph.is_locked(index) == Some(true);
+            self.change_func_lock(index, false, graph);
+            self.remove_func(index, graph);
+            self.insert_func(index, id, Self::new_func(x, values), state, graph);
+            self.change_func_selection(index, own_selection, graph);
+            self.change_func_lock(index, locked, graph);
+        }
+        self.change_selection(selection, graph);
+        graph.clear_selection_ranges();
+        for range in ranges {
+            graph.add_selection_range(range);
+        }
+    }
+
+    /// Replaces live functions, whose sources changed, by their recomputed values, so
+    /// undo and redo give back exactly the recorded values. See `Graph::live_updates`.
+    pub fn update_live(&mut self, graph: &mut Graph) {
+        for (index, x, values) in graph.live_updates() {
+            let (Some(id), Some(state)) = (graph.func_id(index), graph.get_func_state(index))
+            else {
+                continue;
+            };
+            let own_selection = graph.func_selection(index).cloned();
+            self.remove_func(index, graph);
+            self.insert_func(index, id, Self::new_func(x, values), state, graph);
+            self.change_func_selection(index, own_selection, graph);
+        }
+    }
+
+    /// Appends copy of function at `index` with its gaps and returns index of the copy.
+    pub fn duplicate_func(&mut self, index: usize, graph: &mut Graph) -> Option<usize> {
+        let state = graph.get_func_state(index)?;
+        let func = graph.functions().nth(index)?;
+        let x = func.points().first().map(|p| p.x).unwrap_or_default();
+        let mut copy = Self::new_func(x, func.points().iter().map(|p| p.y));
+        copy.set_gaps(func.gaps().to_vec());
+        let (index, id) = (graph.len(), graph.next_func_id());
+        self.insert_func(index, id, copy, state, graph);
+        Some(index)
+    }
+
+    /// Removes function, which is not locked. Its own selection is dropped first,
+    /// so undo gives it back.
+    pub fn remove_func(&mut self, index: usize, graph: &mut Graph) {
+        if graph.is_locked(index) != Some(false) {
+            return;
+        }
+        self.change_func_selection(index, None, graph);
+        if let Some((id, func, state)) = graph.remove_func(index) {
+            self.history.add_graph_op(
+                SharedOp::<iter::Empty<usize>, iter::Empty<_>, _>::RemoveFunc(FuncChange {
+                    index,
+                    id,
+                    x: func.points().first().map(|p| p.x).unwrap_or_default(),
+                    values: func.points().iter().map(|p| p.y),
+                    state,
+                    gaps: func.gaps().to_vec(),
+                }),
+            );
+        }
     }
 
     pub fn other(&mut self, other: impl InOp<OpOwned, ActionGroupID>) {
@@ -172,6 +341,36 @@ impl<
         });
     }
 
+    pub fn change_func_lock(&mut self, index: usize, locked: bool, graph: &mut Graph) {
+        if let Some(was_locked) = graph.set_locked(index, locked) {
+            if was_locked != locked {
+                self.history
+                    .add_graph_op(ActiveFuncsIterSharedOp::ChangeLockedFuncs(
+                        [index].into_iter(),
+                    ));
+            }
+        }
+    }
+
+    /// Changes own selection of function, `None` makes it follow selection of graph.
+    pub fn change_func_selection(
+        &mut self,
+        index: usize,
+        selection: Option<RangeInclusive<X>>,
+        graph: &mut Graph,
+    ) {
+        if let Some(old) = graph.set_func_selection(index, selection.clone()) {
+            if old != selection {
+                self.history
+                    .add_graph_op(NoIterSharedOp::ChangeFuncSelection(FuncSelection {
+                        func: index,
+                        old,
+                        new: selection,
+                    }));
+            }
+        }
+    }
+
     pub fn stretch_y_bounds(
         &mut self,
         bounds: &StretchYBounds,
@@ -190,9 +389,67 @@ impl<
     }
 
     pub fn stretch_y_with_factor(&mut self, stretch: &StretchY, graph: &mut Graph) {
-        if graph.stretch_y_with_factor(stretch) {
-            self.history
-                .add_graph_op(NoIterSharedOp::StretchY(stretch.clone()));
+        let stretch = graph.resolve_stretch(stretch);
+        if graph.stretch_y_with_factor(&stretch) {
+            self.history.add_graph_op(NoIterSharedOp::StretchY(stretch));
         }
     }
+
+    /// Like `stretch_y_with_factor`, but functions in `excluded` are not stretched.
+    /// Stretched values are recorded for each function.
+    pub fn stretch_y_with_factor_excluding(
+        &mut self,
+        stretch: &StretchY,
+        excluded: &[usize],
+        graph: &mut Graph,
+    ) {
+        if excluded.is_empty() {
+            return self.stretch_y_with_factor(stretch, graph);
+        }
+        let stretch = graph.resolve_stretch(stretch);
+        for fi in Self::editable_excluding(excluded, graph) {
+            let Some((x, old)) = Self::selected_values(fi, graph) else {
+                continue;
+            };
+            if graph.stretch_func_y_with_factor(fi, &stretch) {
+                let (_, new) = Self::selected_values(fi, graph).unwrap_or_default();
+                self.history.add_graph_op(SharedOp::<
+                    iter::Empty<usize>,
+                    iter::Empty<iter::Copied<std::slice::Iter<Y>>>,
+                    _,
+                >::ReplaceValues(ReplaceValues {
+                    func: fi,
+                    x,
+                    old: old.iter().copied(),
+                    new: new.iter().copied(),
+                }));
+            }
+        }
+    }
+
+    /// Like `delete`, but selected values of functions in `excluded` are kept.
+    /// Deleted values are recorded for each function.
+    pub fn delete_excluding(&mut self, excluded: &[usize], graph: &mut Graph) {
+        if excluded.is_empty() {
+            return self.delete(graph);
+        }
+        for fi in Self::editable_excluding(excluded, graph) {
+            if let Some((x, old)) = Self::selected_values(fi, graph) {
+                self.replace_values(fi, x, old.len(), iter::empty(), graph);
+            }
+        }
+    }
+
+    fn editable_excluding(excluded: &[usize], graph: &Graph) -> Vec<usize> {
+        graph
+            .active_func_indexes()
+            .filter(|fi| !excluded.contains(fi) && graph.is_locked(*fi) == Some(false))
+            .collect()
+    }
+
+    /// X of the first selected point and selected values of function.
+    fn selected_values(index: usize, graph: &Graph) -> Option<(X, Vec<Y>)> {
+        let points = graph.get_func(index)?.points_selection();
+        Some((points.first()?.x, points.iter().map(|p| p.y).collect()))
+    }
 }
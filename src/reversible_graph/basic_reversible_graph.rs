@@ -1,10 +1,16 @@
 use std::{
     iter::{self},
-    ops::RangeInclusive,
+    ops::{Range, RangeInclusive},
 };
 
 use crate::{
-    functions::function::{Func, StretchY, StretchYBounds, StretchYBoundsError},
+    functions::{
+        expression::Expression,
+        function::{
+            resample::Resample, spectrum::Filter, transform::Transform, Func, StretchY,
+            StretchYBounds, StretchYBoundsError,
+        },
+    },
     graph::{Graph, GraphFuncState},
     history::{
         history_stack::{
@@ -16,11 +22,11 @@ use crate::{
         },
         History,
     },
-    shared_op::{Delete, InsertPattern, InsertValues, MoveSelectBy, SharedOp},
+    shared_op::{Delete, InsertPattern, InsertPolicy, InsertValues, MoveSelectBy, SharedOp},
     types::point::{X, Y},
 };
 
-use super::{ActionBuilderBase, ActiveFuncsIterSharedOp, GraphMutProvider};
+use super::{graph_macro::GraphMacro, ActionBuilderBase};
 pub struct BasicReversibleGraph<
     ActionGroupID: Clone + Default + PartialEq,
     OpOwned: OtherOp,
@@ -48,6 +54,7 @@ impl<
         id: impl Into<ActionGroupID>,
     ) -> ActionBuilder<'a, 'b, ActionGroupID, OpOwned, NonAlteringGraphOpHelper> {
         ActionBuilder {
+            revision: self.graph.revision(),
             graph: &mut self.graph,
             history: ActionBuilderBase::new(self.history.add_entry(id.into())),
         }
@@ -67,11 +74,43 @@ impl<
         &self.graph
     }
 
+    /// See `Graph::set_live_expression`, live expressions are not part of history.
+    /// Function is recomputed by the next action, recomputed values are recorded into it.
+    pub fn set_live_expression(&mut self, id: usize, expression: Option<Expression>) {
+        self.graph.set_live_expression(id, expression);
+    }
+
+    /// See `Graph::add_selection_range`, additional selection ranges are not part of history.
+    pub fn add_selection_range(&mut self, range: RangeInclusive<X>) {
+        self.graph.add_selection_range(range);
+    }
+
+    pub fn remove_selection_range_at(&mut self, x: X) -> bool {
+        self.graph.remove_selection_range_at(x)
+    }
+
+    pub fn clear_selection_ranges(&mut self) {
+        self.graph.clear_selection_ranges();
+    }
+
+    /// Selects the first of `ranges` as selection of graph, recorded with `id`,
+    /// and replaces additional selection ranges with the rest.
+    pub fn select_ranges(&mut self, id: impl Into<ActionGroupID>, ranges: &[RangeInclusive<X>]) {
+        if let Some((first, rest)) = ranges.split_first() {
+            self.action(id).change_selection(first.clone());
+            self.graph.clear_selection_ranges();
+            for range in rest {
+                self.graph.add_selection_range(range.clone());
+            }
+        }
+    }
+
     pub fn open_action<'a: 'b, 'b>(
         &'a mut self,
         id: impl Into<ActionGroupID>,
     ) -> ActionBuilder<'a, 'b, ActionGroupID, OpOwned, NonAlteringGraphOpHelper> {
         ActionBuilder {
+            revision: self.graph.revision(),
             graph: &mut self.graph,
             history: ActionBuilderBase::new(self.history.open_entry(id.into())),
         }
@@ -80,6 +119,20 @@ impl<
     pub fn close_action<'a>(&'a mut self, id: impl Into<ActionGroupID>) {
         self.history.close_entry(id.into());
     }
+
+    /// Records macro from undo entries.
+    /// `entries` - range of indexes into `History::undo_iter`, so 0 is the newest entry.
+    pub fn record_macro<OpOut>(&self, name: String, entries: Range<usize>) -> GraphMacro
+    where
+        OpOwned: OwnedOp<OpOut>,
+    {
+        GraphMacro::record(name, &self.history, entries, self.graph.selection())
+    }
+
+    /// Replays macro on current selection as one new history entry.
+    pub fn replay_macro(&mut self, id: impl Into<ActionGroupID>, graph_macro: &GraphMacro) {
+        graph_macro.replay(&mut self.action(id));
+    }
 }
 impl<
         ActionGroupID: Clone + Default + PartialEq,
@@ -131,6 +184,8 @@ pub struct ActionBuilder<
 > {
     graph: &'a mut Graph,
     history: ActionBuilderBase<'b, ActionGroupID, OpOwned, NonAlteringGraphOpHelper>,
+    /// Revision of graph, when action started
+    revision: u64,
 }
 impl<
         'a,
@@ -140,24 +195,118 @@ impl<
         NonAlteringGraphOpHelper: IsGraphOpNonAltering<ActionGroupID>,
     > ActionBuilder<'a, 'b, ActionGroupID, OpOwned, NonAlteringGraphOpHelper>
 {
+    pub fn graph(&self) -> &Graph {
+        self.graph
+    }
+
     pub fn delete(&mut self) {
-        self.history.delete(&mut self.graph);
+        self.history.delete(self.graph);
+    }
+
+    pub fn delete_excluding(&mut self, excluded: &[usize]) {
+        self.history.delete_excluding(excluded, self.graph);
     }
 
     pub fn insert_values<YExactIter: ExactSizeIterator<Item = Y> + Clone>(
         &mut self,
         at: X,
-        points: impl IntoIterator<Item = YExactIter> + Clone,
+        points: impl IntoIterator<Item = YExactIter>,
+        policy: InsertPolicy,
     ) {
-        self.history.insert_values(at, points, &mut self.graph);
+        self.history.insert_values(at, points, policy, self.graph);
     }
 
-    pub fn insert_pattern<YExactIter: ExactSizeIterator<Item = Y> + Clone>(
+    pub fn insert_pattern(
         &mut self,
         at: X,
-        points: impl IntoIterator<IntoIter = YExactIter, Item = Y> + Clone,
+        points: impl IntoIterator<Item = Y>,
+        policy: InsertPolicy,
     ) {
-        self.history.insert_pattern(at, points, &mut self.graph);
+        self.history.insert_pattern(at, points, policy, self.graph);
+    }
+
+    pub fn replace_values(
+        &mut self,
+        func: usize,
+        at: X,
+        len: usize,
+        values: impl IntoIterator<Item = Y>,
+    ) {
+        self.history
+            .replace_values(func, at, len, values, self.graph);
+    }
+
+    pub fn add_func(
+        &mut self,
+        x: X,
+        values: impl IntoIterator<Item = Y>,
+        state: GraphFuncState,
+    ) -> usize {
+        self.history.add_func(x, values, state, self.graph)
+    }
+
+    /// Replaces all values of editable functions by values transformed by `transform`.
+    /// Returns id of each transformed function with transform, which turns it back.
+    pub fn transform(&mut self, transform: &Transform, x_unit: X) -> Vec<(usize, Transform)> {
+        let mut inverse = Vec::new();
+        for (fi, x, values) in self.graph.transformed(transform, x_unit) {
+            if let (Some(id), Some(func)) = (self.graph.func_id(fi), self.graph.get_func(fi)) {
+                inverse.push((id, transform.inverse(func.points())));
+            }
+            self.replace_values(fi, x, values.len(), values);
+        }
+        inverse
+    }
+
+    /// Replaces all values of unlocked functions with given ids by values transformed by
+    /// their transforms, e.g. by inverse transforms returned by [`Self::transform`].
+    /// Returns inverse transforms of transformed functions like [`Self::transform`].
+    pub fn transform_funcs(
+        &mut self,
+        transforms: &[(usize, Transform)],
+        x_unit: X,
+    ) -> Vec<(usize, Transform)> {
+        let mut inverse = Vec::new();
+        for (id, transform) in transforms {
+            let Some(fi) = self.graph.func_index(*id) else {
+                continue;
+            };
+            if let Some((x, values)) = self.graph.func_transformed(fi, transform, x_unit) {
+                if let Some(func) = self.graph.get_func(fi) {
+                    inverse.push((*id, transform.inverse(func.points())));
+                }
+                self.replace_values(fi, x, values.len(), values);
+            }
+        }
+        inverse
+    }
+
+    /// Replaces selected values of editable functions by values filtered by `filter`.
+    pub fn filter(&mut self, filter: &Filter) {
+        for (fi, x, values) in self.graph.filtered(filter) {
+            self.replace_values(fi, x, values.len(), values);
+        }
+    }
+
+    pub fn resample(&mut self, resample: &Resample) {
+        self.history.resample(resample, self.graph);
+    }
+
+    pub fn duplicate_func(&mut self, index: usize) -> Option<usize> {
+        self.history.duplicate_func(index, self.graph)
+    }
+
+    pub fn remove_func(&mut self, index: usize) {
+        self.history.remove_func(index, self.graph);
+    }
+
+    pub fn change_func_lock(&mut self, index: usize, locked: bool) {
+        self.history.change_func_lock(index, locked, self.graph);
+    }
+
+    pub fn change_func_selection(&mut self, index: usize, selection: Option<RangeInclusive<X>>) {
+        self.history
+            .change_func_selection(index, selection, self.graph);
     }
 
     pub fn other(&mut self, other: impl InOp<OpOwned, ActionGroupID>) {
@@ -165,18 +314,15 @@ impl<
     }
 
     pub fn change_selection(&mut self, new_selection: impl Into<RangeInclusive<X>>) {
-        self.history
-            .change_selection(new_selection, &mut self.graph);
+        self.history.change_selection(new_selection, self.graph);
     }
 
     pub fn change_func_state(&mut self, index: usize, new_state: GraphFuncState) {
-        self.history
-            .change_func_state(index, new_state, &mut self.graph);
+        self.history.change_func_state(index, new_state, self.graph);
     }
 
     pub fn set_func_state_for_all(&mut self, new_state: GraphFuncState) {
-        self.history
-            .set_func_state_for_all(new_state, &mut self.graph);
+        self.history.set_func_state_for_all(new_state, self.graph);
     }
 
     pub fn change_each_active_func_state(
@@ -184,17 +330,83 @@ impl<
         retain: impl FnMut(usize, &Func) -> GraphFuncState,
     ) {
         self.history
-            .change_each_active_func_state(retain, &mut self.graph);
+            .change_each_active_func_state(retain, self.graph);
     }
 
     pub fn stretch_y_bounds(
         &mut self,
         bounds: &StretchYBounds,
     ) -> Result<StretchY, StretchYBoundsError> {
-        self.history.stretch_y_bounds(bounds, &mut self.graph)
+        self.history.stretch_y_bounds(bounds, self.graph)
     }
 
     pub fn stretch_y_with_factor(&mut self, stretch: &StretchY) {
-        self.history.stretch_y_with_factor(stretch, &mut self.graph);
+        self.history.stretch_y_with_factor(stretch, self.graph);
+    }
+
+    pub fn stretch_y_with_factor_excluding(&mut self, stretch: &StretchY, excluded: &[usize]) {
+        self.history
+            .stretch_y_with_factor_excluding(stretch, excluded, self.graph);
+    }
+
+    /// Like `stretch_y_bounds`, but stretches all selection ranges by the same factor.
+    pub fn stretch_y_bounds_in_ranges(
+        &mut self,
+        bounds: &StretchYBounds,
+    ) -> Result<StretchY, StretchYBoundsError> {
+        if self.graph.extra_selections().is_empty() {
+            return self.stretch_y_bounds(bounds);
+        }
+        let stretch = self.graph.stretch_y_for_ranges(bounds)?;
+        if stretch.stretches() {
+            self.for_each_selection(|b, excluded| {
+                b.stretch_y_with_factor_excluding(&stretch, excluded)
+            });
+        }
+        Ok(stretch)
+    }
+
+    /// Calls `apply` once for each of `Graph::selection_ranges`, with selection of graph
+    /// changed to that range, from the last range to the first one, so deleting
+    /// values does not move ranges not yet applied. Functions with own selection are
+    /// passed to `apply` as excluded, except for range containing selection of graph,
+    /// so they are applied only once.
+    /// Selection of graph is restored afterwards. Everything is recorded into the action.
+    pub fn for_each_selection(&mut self, mut apply: impl FnMut(&mut Self, &[usize])) {
+        if self.graph.extra_selections().is_empty() {
+            apply(self, &[]);
+            return;
+        }
+        let primary = self.graph.selection().clone();
+        let own = (0..self.graph.len())
+            .filter(|fi| self.graph.func_selection(*fi).is_some())
+            .collect::<Vec<_>>();
+        for range in self.graph.selection_ranges().into_iter().rev() {
+            let excluded = if range.contains(primary.start()) {
+                &[][..]
+            } else {
+                &own[..]
+            };
+            self.change_selection(range);
+            apply(self, excluded);
+        }
+        self.change_selection(primary);
+    }
+}
+
+/// Live functions are recomputed as part of the action, which changed their sources.
+impl<
+        'a,
+        'b,
+        ActionGroupID: Clone + Default + PartialEq,
+        OpOwned: OtherOp,
+        NonAlteringGraphOpHelper: IsGraphOpNonAltering<ActionGroupID>,
+    > Drop for ActionBuilder<'a, 'b, ActionGroupID, OpOwned, NonAlteringGraphOpHelper>
+{
+    /// Live functions are recomputed only after action changed values of graph.
+    fn drop(&mut self) {
+        if self.graph.revision() != self.revision {
+            self.history.update_live(self.graph);
+        }
     }
 }
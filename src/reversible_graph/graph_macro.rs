@@ -0,0 +1,179 @@
+use std::ops::{Range, RangeInclusive};
+
+use crate::{
+    functions::function::StretchY,
+    history::{
+        history_stack::{
+            shared_entry::{OtherOp, OwnedOp, SharedOutOp},
+            IsGraphOpNonAltering,
+        },
+        History,
+    },
+    shared_op::{InsertPolicy, MoveSelectBy, SharedOp},
+    types::point::{X, Y},
+};
+
+use super::basic_reversible_graph::ActionBuilder;
+
+#[derive(Debug, Clone)]
+pub enum MacroStep {
+    Delete,
+    StretchY(StretchY),
+    /// Offset is relative to start of selection at the time of insertion.
+    InsertValues {
+        offset: X,
+        values: Box<[Box<[Y]>]>,
+        policy: InsertPolicy,
+    },
+    /// Offset is relative to start of selection at the time of insertion.
+    InsertPattern {
+        offset: X,
+        values: Box<[Y]>,
+        policy: InsertPolicy,
+    },
+    MoveSelectBy {
+        start_by: X,
+        end_by: X,
+    },
+}
+
+/// Sequence of graph operations taken from history,
+/// which can be replayed on any selection of any graph.
+#[derive(Debug, Clone)]
+pub struct GraphMacro {
+    pub name: String,
+    steps: Vec<MacroStep>,
+}
+
+impl GraphMacro {
+    pub fn new(name: String, steps: Vec<MacroStep>) -> Self {
+        Self { name, steps }
+    }
+
+    pub fn steps(&self) -> &[MacroStep] {
+        &self.steps
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Records macro from undo entries.
+    /// `entries` - range of indexes into `History::undo_iter`, so 0 is the newest entry.
+    /// `selection` - current selection of graph, which history belongs to.
+    pub fn record<
+        OpGroupId: Clone + Default + PartialEq,
+        OpOwned: OtherOp + OwnedOp<OpOut>,
+        OpOut,
+        NonAlteringGraphOpHelper: IsGraphOpNonAltering<OpGroupId>,
+    >(
+        name: String,
+        history: &History<OpGroupId, OpOwned, NonAlteringGraphOpHelper>,
+        entries: Range<usize>,
+        selection: &RangeInclusive<X>,
+    ) -> Self {
+        let mut start = *selection.start();
+        let mut recorded = Vec::new();
+        for (i, entry) in history.undo_iter().enumerate() {
+            if i >= entries.end {
+                break;
+            }
+            let mut entry_steps = Vec::new();
+            for op in entry.ops::<OpOut>() {
+                let op = match op {
+                    SharedOutOp::Graph(op) => op,
+                    SharedOutOp::Other(_) => continue,
+                };
+                let step = match op {
+                    SharedOp::Delete(_) => MacroStep::Delete,
+                    SharedOp::StretchY(stretch) => MacroStep::StretchY(stretch.unresolved()),
+                    SharedOp::InsertValues(insert) => MacroStep::InsertValues {
+                        offset: insert.x,
+                        values: insert
+                            .values
+                            .map(|values| values.collect::<Vec<_>>().into_boxed_slice())
+                            .collect::<Vec<_>>()
+                            .into_boxed_slice(),
+                        policy: insert.policy,
+                    },
+                    SharedOp::InsertPattern(insert) => MacroStep::InsertPattern {
+                        offset: insert.x,
+                        values: insert.values.collect::<Vec<_>>().into_boxed_slice(),
+                        policy: insert.policy,
+                    },
+                    SharedOp::MoveSelectBy(move_by) => MacroStep::MoveSelectBy {
+                        start_by: move_by.start_by,
+                        end_by: move_by.end_by,
+                    },
+                    // Functions of the graph, on which macro is replayed, can be different.
+                    SharedOp::ChangeActiveFuncs(_)
+                    | SharedOp::ChangeLockedFuncs(_)
+                    | SharedOp::ChangeFuncSelection(_)
+                    | SharedOp::ReplaceValues(_)
+                    | SharedOp::AddFunc(_)
+                    | SharedOp::RemoveFunc(_) => continue,
+                };
+                entry_steps.push(step);
+            }
+            // Entries are visited from the newest one, so selection has to be rewound
+            // to the state before the entry, to make insert positions relative.
+            for step in entry_steps.iter_mut().rev() {
+                match step {
+                    MacroStep::MoveSelectBy { start_by, .. } => start -= *start_by,
+                    MacroStep::InsertValues { offset, .. }
+                    | MacroStep::InsertPattern { offset, .. } => *offset -= start,
+                    MacroStep::Delete | MacroStep::StretchY(_) => (),
+                }
+            }
+            if entries.contains(&i) {
+                recorded.push(entry_steps);
+            }
+        }
+        Self {
+            name,
+            steps: recorded.into_iter().rev().flatten().collect(),
+        }
+    }
+
+    pub fn replay<
+        ActionGroupID: Clone + Default + PartialEq,
+        OpOwned: OtherOp,
+        NonAlteringGraphOpHelper: IsGraphOpNonAltering<ActionGroupID>,
+    >(
+        &self,
+        action: &mut ActionBuilder<'_, '_, ActionGroupID, OpOwned, NonAlteringGraphOpHelper>,
+    ) {
+        for step in &self.steps {
+            let selection = action.graph().selection().clone();
+            match step {
+                MacroStep::Delete => action.delete(),
+                MacroStep::StretchY(stretch) => action.stretch_y_with_factor(stretch),
+                MacroStep::InsertValues {
+                    offset,
+                    values,
+                    policy,
+                } => action.insert_values(
+                    selection.start() + offset,
+                    values.iter().map(|values| values.iter().copied()),
+                    *policy,
+                ),
+                MacroStep::InsertPattern {
+                    offset,
+                    values,
+                    policy,
+                } => action.insert_pattern(
+                    selection.start() + offset,
+                    values.iter().copied(),
+                    *policy,
+                ),
+                MacroStep::MoveSelectBy { start_by, end_by } => action.change_selection(
+                    MoveSelectBy {
+                        start_by: *start_by,
+                        end_by: *end_by,
+                    }
+                    .move_selection(&selection),
+                ),
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::{fmt, fs, io, ops::RangeInclusive, path::Path};
+
+use chrono::{Datelike, NaiveTime, Weekday};
+
+use crate::{
+    graph::Graph,
+    types::{
+        datetime_f64_mapper::DateTimeF64Mapper,
+        point::{Point, X, Y},
+    },
+};
+
+/// Condition, from which selection ranges are built.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionRule {
+    /// Values of the first active function above threshold
+    Above(Y),
+    /// Values of the first active function below threshold
+    Below(Y),
+    /// Saturdays and Sundays
+    Weekends,
+    /// Time of each day from start up to end, end before start wraps over midnight
+    DailyTime(NaiveTime, NaiveTime),
+    /// Number of samples on both sides of each local maximum of the first active function
+    AroundMaxima(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionRuleError {
+    NoActiveFunction,
+    /// Calendar rule used on graph without date and time x axis
+    NoTimeAxis,
+    NoMatch,
+}
+
+impl fmt::Display for SelectionRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoActiveFunction => write!(f, "no active function"),
+            Self::NoTimeAxis => write!(f, "x axis is not date and time"),
+            Self::NoMatch => write!(f, "no value matches the rule"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SelectionRulesError {
+    Io(io::Error),
+    /// Line number and the line, which is not a rule
+    InvalidLine(usize, String),
+}
+
+impl fmt::Display for SelectionRulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "can not read selection rules: {}", e),
+            Self::InvalidLine(line, text) => {
+                write!(f, "line {}: '{}' is not selection rule", line, text)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for SelectionRulesError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl fmt::Display for SelectionRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Above(threshold) => write!(f, "value > {}", threshold),
+            Self::Below(threshold) => write!(f, "value < {}", threshold),
+            Self::Weekends => write!(f, "weekends"),
+            Self::DailyTime(start, end) => {
+                write!(f, "daily {}-{}", start.format("%H:%M"), end.format("%H:%M"))
+            }
+            Self::AroundMaxima(samples) => write!(f, "{} samples around maxima", samples),
+        }
+    }
+}
+
+impl SelectionRule {
+    /// Sorted disjoint ranges of x, which match the rule. `mapper` is needed by calendar rules.
+    pub fn ranges(
+        &self,
+        graph: &Graph,
+        mapper: Option<&DateTimeF64Mapper>,
+    ) -> Result<Vec<RangeInclusive<X>>, SelectionRuleError> {
+        let ranges = match self {
+            Self::Above(threshold) => {
+                runs(first_active(graph)?.iter().map(|p| (p.x, p.y > *threshold)))
+            }
+            Self::Below(threshold) => {
+                runs(first_active(graph)?.iter().map(|p| (p.x, p.y < *threshold)))
+            }
+            Self::Weekends => {
+                let mapper = mapper.ok_or(SelectionRuleError::NoTimeAxis)?;
+                runs(xs(graph).map(|x| {
+                    let weekday = mapper.f64_to_time(x).weekday();
+                    (x, matches!(weekday, Weekday::Sat | Weekday::Sun))
+                }))
+            }
+            Self::DailyTime(start, end) => {
+                let mapper = mapper.ok_or(SelectionRuleError::NoTimeAxis)?;
+                runs(xs(graph).map(|x| {
+                    let time = mapper.f64_to_time(x).time();
+                    let inside = if start <= end {
+                        *start <= time && time < *end
+                    } else {
+                        *start <= time || time < *end
+                    };
+                    (x, inside)
+                }))
+            }
+            Self::AroundMaxima(samples) => around_maxima(first_active(graph)?, *samples as X),
+        };
+        if ranges.is_empty() {
+            Err(SelectionRuleError::NoMatch)
+        } else {
+            Ok(ranges)
+        }
+    }
+
+    /// Parses rule written by `to_line`.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        let rule = match words.next()? {
+            "above" => Self::Above(words.next()?.parse().ok()?),
+            "below" => Self::Below(words.next()?.parse().ok()?),
+            "weekends" => Self::Weekends,
+            "daily" => {
+                let mut time = || NaiveTime::parse_from_str(words.next()?, "%H:%M").ok();
+                Self::DailyTime(time()?, time()?)
+            }
+            "around_maxima" => Self::AroundMaxima(words.next()?.parse().ok()?),
+            _ => return None,
+        };
+        words.next().is_none().then_some(rule)
+    }
+
+    /// Rule as one line of rules file.
+    pub fn to_line(&self) -> String {
+        match self {
+            Self::Above(threshold) => format!("above {}", threshold),
+            Self::Below(threshold) => format!("below {}", threshold),
+            Self::Weekends => "weekends".to_string(),
+            Self::DailyTime(start, end) => {
+                format!("daily {} {}", start.format("%H:%M"), end.format("%H:%M"))
+            }
+            Self::AroundMaxima(samples) => format!("around_maxima {}", samples),
+        }
+    }
+
+    /// Rules, one per line. Empty lines and lines starting with `#` are skipped.
+    pub fn parse_all(text: &str) -> Result<Vec<Self>, SelectionRulesError> {
+        text.lines()
+            .enumerate()
+            .map(|(i, line)| (i, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| {
+                Self::parse(line)
+                    .ok_or_else(|| SelectionRulesError::InvalidLine(i + 1, line.to_string()))
+            })
+            .collect()
+    }
+
+    /// Rules saved by `save_all`, missing file has no rules.
+    pub fn load_all(path: impl AsRef<Path>) -> Result<Vec<Self>, SelectionRulesError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse_all(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save_all(path: impl AsRef<Path>, rules: &[Self]) -> io::Result<()> {
+        let text = rules.iter().map(|r| r.to_line() + "\n").collect::<String>();
+        fs::write(path, text)
+    }
+}
+
+fn first_active(graph: &Graph) -> Result<&[Point], SelectionRuleError> {
+    graph
+        .active_func_indexes()
+        .next()
+        .and_then(|fi| graph.get_func(fi))
+        .map(|f| f.points())
+        .ok_or(SelectionRuleError::NoActiveFunction)
+}
+
+/// Sorted x of points of all functions of graph.
+fn xs(graph: &Graph) -> impl Iterator<Item = X> {
+    let mut xs = graph
+        .functions()
+        .flat_map(|f| f.points().iter().map(|p| p.x))
+        .collect::<Vec<_>>();
+    xs.sort_by(|a, b| a.total_cmp(b));
+    xs.dedup();
+    xs.into_iter()
+}
+
+/// Ranges of consecutive x, which match.
+fn runs(matches: impl Iterator<Item = (X, bool)>) -> Vec<RangeInclusive<X>> {
+    let mut ranges = Vec::new();
+    let mut run: Option<RangeInclusive<X>> = None;
+    for (x, matches) in matches {
+        run = match (run, matches) {
+            (Some(r), true) => Some(*r.start()..=x),
+            (None, true) => Some(x..=x),
+            (Some(r), false) => {
+                ranges.push(r);
+                None
+            }
+            (None, false) => None,
+        };
+    }
+    ranges.extend(run);
+    ranges
+}
+
+/// Ranges of `samples` points on both sides of each local maximum, overlapping ranges are merged.
+fn around_maxima(points: &[Point], samples: X) -> Vec<RangeInclusive<X>> {
+    let (first, last) = match (points.first(), points.last()) {
+        (Some(first), Some(last)) => (first.x, last.x),
+        _ => return Vec::new(),
+    };
+    let mut ranges: Vec<RangeInclusive<X>> = Vec::new();
+    for w in points.windows(3) {
+        // Strict on the left side, so plateau is counted once.
+        if w[1].y > w[0].y && w[1].y >= w[2].y {
+            let range = (w[1].x - samples).max(first)..=(w[1].x + samples).min(last);
+            match ranges.last_mut() {
+                Some(prev) if *range.start() <= prev.end() + 1.0 => {
+                    *prev = *prev.start()..=*range.end()
+                }
+                _ => ranges.push(range),
+            }
+        }
+    }
+    ranges
+}
@@ -1,6 +1,7 @@
-use std::ops::{Add, RangeInclusive};
+use std::ops::{Add, Range, RangeInclusive};
 
 use crate::{
+    graph::GraphFuncState,
     history::history_stack::shared_entry::InOp,
     types::point::{X, Y},
 };
@@ -14,9 +15,15 @@ pub enum SharedOp<
     Delete(Delete<FuncIter, YExactIter>),
     StretchY(StretchY),
     InsertValues(InsertValues<FuncIter, YExactIter>),
-    InsertPattern(InsertPattern<YExactIter>),
+    InsertPattern(InsertPattern<FuncIter, YExactIter>),
     MoveSelectBy(MoveSelectBy),
     ChangeActiveFuncs(IterChangeActiveFuncs),
+    /// Toggles lock of functions
+    ChangeLockedFuncs(IterChangeActiveFuncs),
+    ChangeFuncSelection(FuncSelection),
+    ReplaceValues(ReplaceValues<YExactIter>),
+    AddFunc(FuncChange<YExactIter>),
+    RemoveFunc(FuncChange<YExactIter>),
 }
 
 impl<
@@ -45,6 +52,7 @@ impl<
 }
 
 pub type StretchY = crate::functions::function::StretchY;
+pub type InsertPolicy = crate::functions::function::InsertPolicy;
 
 #[derive(Debug)]
 pub struct Delete<
@@ -59,12 +67,52 @@ pub struct InsertValues<
 > {
     pub x: X,
     pub values: Iter,
+    pub policy: InsertPolicy,
+    /// Values replaced in each editable function, empty if policy is `InsertPolicy::Shift`.
+    pub replaced: Iter,
 }
 
 #[derive(Debug)]
-pub struct InsertPattern<Iter: IntoIterator<Item = Y>> {
+pub struct InsertPattern<
+    Iter: IntoIterator<Item = YExactIter>,
+    YExactIter: ExactSizeIterator<Item = Y> + Clone,
+> {
+    pub x: X,
+    pub values: YExactIter,
+    pub policy: InsertPolicy,
+    /// Values replaced in each editable function, empty if policy is `InsertPolicy::Shift`.
+    pub replaced: Iter,
+}
+
+/// Replaces values of one function, regardless of whether it is active.
+#[derive(Debug)]
+pub struct ReplaceValues<Iter: IntoIterator<Item = Y>> {
+    pub func: usize,
+    pub x: X,
+    pub old: Iter,
+    pub new: Iter,
+}
+
+/// Function, which is added to or removed from graph at `index`.
+#[derive(Debug)]
+pub struct FuncChange<Iter: IntoIterator<Item = Y>> {
+    pub index: usize,
+    /// Stable id of function, see `Graph::func_id`
+    pub id: usize,
+    /// X of first point
     pub x: X,
     pub values: Iter,
+    pub state: GraphFuncState,
+    /// See `Func::gaps`
+    pub gaps: Vec<Range<X>>,
+}
+
+/// Change of own selection of function, `None` is selection of graph.
+#[derive(Debug, Clone)]
+pub struct FuncSelection {
+    pub func: usize,
+    pub old: Option<RangeInclusive<X>>,
+    pub new: Option<RangeInclusive<X>>,
 }
 
 #[derive(Debug)]
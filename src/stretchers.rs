@@ -1,3 +1,4 @@
+pub mod stretch_profile;
 pub mod x_stretcher;
 pub mod y_stretcher;
 
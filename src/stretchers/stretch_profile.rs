@@ -0,0 +1,76 @@
+use std::f64::consts::PI;
+
+/// Weight of stretch factor across stretched range. Weight 1 stretches by the whole factor,
+/// weight 0 keeps the point, so the profile blends stretched part into the rest of function.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum StretchProfile {
+    #[default]
+    Constant,
+    /// Weight rises linearly from the edges to 1 at relative position `peak`
+    Triangular { peak: f64 },
+    /// Raised cosine, which is 1 in the middle
+    Cosine,
+    /// Gaussian with `width` relative to stretched range, which is 1 in the middle
+    Bell { width: f64 },
+    /// Weight rises smoothly from 0 at the start to 1 at the end
+    EaseIn,
+    /// Weight falls smoothly from 1 at the start to 0 at the end
+    EaseOut,
+    /// Weights at evenly spaced positions from the start to the end, linearly interpolated
+    Envelope(Box<[f64]>),
+}
+
+impl StretchProfile {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Constant => "Constant",
+            Self::Triangular { .. } => "Triangular",
+            Self::Cosine => "Cosine",
+            Self::Bell { .. } => "Bell",
+            Self::EaseIn => "Ease in",
+            Self::EaseOut => "Ease out",
+            Self::Envelope(_) => "Envelope",
+        }
+    }
+
+    pub fn is_constant(&self) -> bool {
+        *self == Self::Constant
+    }
+
+    /// Weight at relative position `t`, 0 is the start and 1 is the end of stretched range.
+    pub fn weight(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        let smooth = t * t * (3.0 - 2.0 * t);
+        let weight = match self {
+            Self::Constant => 1.0,
+            Self::Triangular { peak } => {
+                let peak = peak.clamp(0.0, 1.0);
+                if t <= peak && peak > 0.0 {
+                    t / peak
+                } else if t > peak && peak < 1.0 {
+                    (1.0 - t) / (1.0 - peak)
+                } else {
+                    1.0
+                }
+            }
+            Self::Cosine => 0.5 - 0.5 * (2.0 * PI * t).cos(),
+            Self::Bell { width } => {
+                let d = (t - 0.5) / width.max(f64::EPSILON);
+                (-0.5 * d * d).exp()
+            }
+            Self::EaseIn => smooth,
+            Self::EaseOut => 1.0 - smooth,
+            Self::Envelope(weights) => match weights.len() {
+                0 => 1.0,
+                1 => weights[0],
+                len => {
+                    let at = t * (len - 1) as f64;
+                    let i = (at.floor() as usize).min(len - 2);
+                    let frac = at - i as f64;
+                    weights[i] + (weights[i + 1] - weights[i]) * frac
+                }
+            },
+        };
+        weight.clamp(0.0, 1.0)
+    }
+}
@@ -1,6 +1,9 @@
-use crate::types::{
-    func_line::FuncLine,
-    point::{Point, X, Y},
+use crate::{
+    functions::function::fit::FitModel,
+    types::{
+        func_line::FuncLine,
+        point::{Point, X, Y},
+    },
 };
 use enumflags2::{bitflags, BitFlags};
 
@@ -14,6 +17,105 @@ pub enum YStretcherFlags {
     Top = 2,
 }
 
+/// Line, from which distances of points are stretched.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum StretchBaseline {
+    /// Line through the first and the last selected point
+    #[default]
+    Chord,
+    /// Horizontal line at mean of selected values
+    Mean,
+    /// Horizontal line at median of selected values
+    Median,
+    /// Horizontal line at minimum of selected values
+    Min,
+    /// Horizontal line at given value
+    Value(Y),
+    /// Least squares regression line of selected points
+    Regression,
+    /// Lines `(k, q)` of `mode` computed for functions by their indexes, so stretch can
+    /// be reverted after points moved the baseline.
+    Resolved {
+        mode: Box<StretchBaseline>,
+        lines: Box<[Option<(Y, Y)>]>,
+    },
+}
+
+impl StretchBaseline {
+    pub const MODES: [Self; 6] = [
+        Self::Chord,
+        Self::Mean,
+        Self::Median,
+        Self::Min,
+        Self::Value(0.0),
+        Self::Regression,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Chord => "Chord",
+            Self::Mean => "Mean",
+            Self::Median => "Median",
+            Self::Min => "Minimum",
+            Self::Value(_) => "Value",
+            Self::Regression => "Regression",
+            Self::Resolved { mode, .. } => mode.label(),
+        }
+    }
+
+    /// Whether line does not change, when points are stretched around it.
+    /// Chord goes through the first and the last point, which are kept by stretch.
+    pub fn is_fixed(&self) -> bool {
+        matches!(self, Self::Chord | Self::Value(_) | Self::Resolved { .. })
+    }
+
+    /// Baseline mode, which was resolved.
+    pub fn mode(&self) -> &Self {
+        match self {
+            Self::Resolved { mode, .. } => mode,
+            _ => self,
+        }
+    }
+
+    /// Line of selected `points`, `None` for resolved baseline or if there are not enough points.
+    /// Missing values are skipped.
+    pub fn line(&self, points: &[Point]) -> Option<FuncLine> {
+        let points = points
+            .iter()
+            .filter(|p| !p.y.is_nan())
+            .copied()
+            .collect::<Vec<_>>();
+        let horizontal = |y: Y| {
+            Some(y)
+                .filter(|y| y.is_finite())
+                .map(|y| FuncLine::from_k_q(0.0, y))
+        };
+        match self {
+            Self::Chord => FuncLine::new(points.first()?, points.last()?).ok(),
+            Self::Mean => {
+                if points.is_empty() {
+                    return None;
+                }
+                horizontal(points.iter().map(|p| p.y).sum::<Y>() / points.len() as Y)
+            }
+            Self::Median => {
+                let mut values = points.iter().map(|p| p.y).collect::<Vec<_>>();
+                values.sort_by(|a, b| a.total_cmp(b));
+                let mid = values.len() / 2;
+                match values.len() {
+                    0 => None,
+                    len if len % 2 == 0 => horizontal((values[mid - 1] + values[mid]) / 2.0),
+                    _ => horizontal(values[mid]),
+                }
+            }
+            Self::Min => horizontal(points.iter().map(|p| p.y).min_by(|a, b| a.total_cmp(b))?),
+            Self::Value(y) => horizontal(*y),
+            Self::Regression => FitModel::Polynomial(1).fit(&points).ok()?.curve.line(),
+            Self::Resolved { .. } => None,
+        }
+    }
+}
+
 /// Stretches distance of point's y component from y component of point on given line with same x component as given point
 pub struct YStretcher {
     x_factor: X,
@@ -7,7 +7,7 @@ pub enum DateTimePrecision {
     Days,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DateTimeF64Mapper {
     zero: NaiveDateTime,
     precision: f64,
@@ -29,6 +29,22 @@ impl DateTimeF64Mapper {
         }
     }
 
+    /// Mapper, whose x increases by one each `step`, with zero at the latest multiple
+    /// of `step` since Unix epoch, which is not after zero of this mapper.
+    pub fn aligned(&self, step: TimeDelta) -> Self {
+        let step_abs = Self::delta_to_abs(&step);
+        let zero_abs = Self::time_to_abs(&self.zero);
+        Self {
+            zero: Self::abs_to_time(zero_abs - zero_abs.rem_euclid(step_abs)),
+            precision: Self::NANOS_IN_SECOND_F64 / step_abs as f64,
+        }
+    }
+
+    /// Time between x one apart.
+    pub fn step(&self) -> TimeDelta {
+        self.f64_to_time_delta(1.0)
+    }
+
     pub fn f64_to_time_delta(&self, mut f: f64) -> TimeDelta {
         f /= self.precision;
         let seconds = f.trunc();
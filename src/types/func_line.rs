@@ -28,6 +28,10 @@ impl FuncLine {
         Ok(Self { k, q })
     }
 
+    pub fn from_k_q(k: Y, q: Y) -> Self {
+        Self { k, q }
+    }
+
     pub fn k(&self) -> Y {
         self.k
     }
@@ -1,56 +1,85 @@
+pub mod diff_view;
 pub mod history;
+pub mod keymap;
+pub mod minimap;
+pub mod navigation;
+pub mod outlier_review;
 pub mod plotter;
+pub mod profile_editor;
+pub mod snap;
+pub mod spectrum_view;
 pub mod utils;
+pub mod view_recording;
+
+mod command_palette;
+mod derived;
+mod export;
+mod fit;
+mod functions;
+mod gaps;
+mod macros;
+mod resample;
+mod selection_rules;
+mod stretch;
+mod transform;
 
 use core::f64;
-use std::{
-    fmt::Write,
-    mem::swap,
-    ops::{Add, RangeInclusive},
-    str::FromStr,
-};
+use std::{fmt::Write, io, mem::swap, ops::RangeInclusive, str::FromStr};
 
-use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use chrono::TimeDelta;
+use command_palette::CommandPalette;
+use derived::DerivedInput;
+use diff_view::DiffView;
+use fit::FitInput;
 use history::{
     plot_bounds_change::{change::PlotBoundsChange, PlotBoundsChangeOp},
+    resample_change::ResampleChange,
     ApplyDataOp, OwnedHistoryOp, SharedDataOp,
 };
+use keymap::{Command, Keymap, KeymapError};
+use navigation::Navigation;
+use outlier_review::OutlierReview;
 use plotter::Plotter;
+use selection_rules::RuleInput;
+use snap::Snap;
+use spectrum_view::SpectrumView;
+use transform::InverseTransform;
+use view_recording::ViewRecording;
 // it's an example
 use crate::{
-    date_time_ctx::DateTimeCtx,
-    functions::function::{StretchY, StretchYBounds},
+    date_time_ctx::{CalendarUnit, DateTimeCtx},
+    functions::function::{
+        gap_fill::GapFill, resample::Aggregation, transform::Transform, InsertPolicy,
+    },
     graph::{Graph, GraphFuncState},
     history::{
         history_stack::{
-            shared_entry::{ApplyOp, ApplyOtherOp, OtherOp, OwnedOp, SharedOutOp},
+            shared_entry::{ApplyOp, ApplyOtherOp},
             HistoryOption, IsGraphOpNonAltering,
         },
         History,
     },
-    importer::{DateTimeInfo, Importer},
+    importer::{GapThreshold, Importer},
     layout::calculator::{self},
-    reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    reversible_graph::{basic_reversible_graph::BasicReversibleGraph, graph_macro::GraphMacro},
+    selection_rule::SelectionRule,
     shared_op::SharedOp,
-    stretchers::y_stretcher::YStretcherFlags,
-    types::{
-        self,
-        datetime_f64_mapper::{DateTimeF64Mapper, DateTimePrecision},
-        point::{X, Y},
-    },
-    ui,
+    stretchers::{stretch_profile::StretchProfile, y_stretcher::StretchBaseline},
+    types::point::{X, Y},
     utils::Changeable,
-    widgets::{
-        legend_widget::{
-            simple_legend_entries::{LegendActionId, SimpleLegendEntries, SimpleLegendEntry},
-            LegendEntries, LegendEntry, LegendWidget,
-        },
-        slider_widget::{SliderOptionalData, SliderWidget},
+    widgets::legend_widget::{
+        simple_legend_entries::{LegendActionId, SimpleLegendEntries, SimpleLegendEntry},
+        LegendWidget,
     },
 };
-use eframe::egui::{self, Color32, Id, Key, Rangef, Response, ScrollArea, Slider, Ui, Widget};
-use egui_plot::{CoordinatesFormatter, Corner, HLine, Legend, Plot, PlotBounds, PlotUi, VLine};
-use enumflags2::BitFlags;
+use eframe::egui::{self, Color32, Id, Response, ScrollArea, Slider, Ui};
+use egui_plot::{HLine, Legend, LineStyle, PlotBounds, PlotPoint, PlotUi, Polygon, VLine};
+
+/// Change of additional selection ranges made by shift dragging or clicking in plot.
+enum SelectionRangeGesture {
+    Add(RangeInclusive<X>),
+    Remove(X),
+}
 
 #[derive(Debug, Default, Clone, PartialEq)]
 enum ActionId {
@@ -60,12 +89,30 @@ enum ActionId {
     InsertPattern,
     DeleteSelection,
     StretchY,
+    Macro,
+    RevertRegion,
+    Legend,
+    Functions,
+    FillGaps,
+    Outliers,
+    Resample,
+    Transform,
+    Fit,
+    Filter,
 }
 
 impl LegendActionId for ActionId {
     fn change_active_funcs() -> Self {
         ActionId::Conditions
     }
+
+    fn change_legend() -> Self {
+        ActionId::Legend
+    }
+
+    fn change_funcs() -> Self {
+        ActionId::Functions
+    }
 }
 
 impl Into<usize> for ActionId {
@@ -79,7 +126,81 @@ struct SelectionVLines {
     end: Id,
 }
 
-struct NonAlteringGraphOpHelper;
+impl SelectionVLines {
+    /// Distance in points from line, at which the line can be grabbed.
+    const GRAB_DISTANCE: f32 = 6.0;
+
+    fn hovered_id() -> Id {
+        Id::new("selection_vline_hovered")
+    }
+
+    fn dragged_id() -> Id {
+        Id::new("selection_vline_dragged")
+    }
+
+    /// Whether pointer was over a line or a line is dragged in the last frame,
+    /// so plot should not be panned.
+    fn grabbed(&self, ui: &Ui) -> bool {
+        ui.data(|r| {
+            r.get_temp(Self::hovered_id()).unwrap_or(false)
+                || r.get_temp::<Id>(Self::dragged_id()).is_some()
+        })
+    }
+
+    /// Selection with line dragged to pointer, or `None`, if no line is dragged.
+    /// Lines are not dragged with shift, which drags selection ranges.
+    fn drag(
+        &self,
+        plot_ui: &mut PlotUi,
+        selection: &RangeInclusive<X>,
+    ) -> Option<RangeInclusive<X>> {
+        let response = plot_ui.response().clone();
+        let shift = plot_ui.ctx().input(|i| i.modifiers.shift);
+        let pointer = plot_ui.pointer_coordinate();
+        let hovered = pointer.filter(|_| !shift).and_then(|pointer| {
+            let pointer_x = plot_ui.screen_from_plot(pointer).x;
+            let near = |x: X| {
+                let x = plot_ui.screen_from_plot(PlotPoint::new(x, pointer.y)).x;
+                (x - pointer_x).abs() <= Self::GRAB_DISTANCE
+            };
+            if near(*selection.start()) {
+                Some(self.start)
+            } else if near(*selection.end()) {
+                Some(self.end)
+            } else {
+                None
+            }
+        });
+        let ctx = plot_ui.ctx().clone();
+        ctx.data_mut(|w| w.insert_temp(Self::hovered_id(), hovered.is_some()));
+        if hovered.is_some() {
+            ctx.set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+        }
+        if response.drag_started() {
+            if let Some(line) = hovered {
+                ctx.data_mut(|w| w.insert_temp(Self::dragged_id(), line));
+            }
+        }
+        let line: Id = ctx.data(|r| r.get_temp(Self::dragged_id()))?;
+        if response.drag_stopped() || !response.dragged() {
+            ctx.data_mut(|w| w.remove::<Id>(Self::dragged_id()));
+        }
+        let x = pointer?.x;
+        if line == self.start {
+            Some(x..=*selection.end())
+        } else {
+            Some(*selection.start()..=x)
+        }
+    }
+}
+
+/// Recording of selection moves and changes of visible functions,
+/// which do not alter history with `ActionId::Conditions`.
+#[derive(Default)]
+struct NonAlteringGraphOpHelper {
+    selection: ViewRecording,
+    visibility: ViewRecording,
+}
 
 impl IsGraphOpNonAltering<ActionId> for NonAlteringGraphOpHelper {
     fn graph_op_alters_history<IterChangeActiveFuncs, FuncIter, YExactIter>(
@@ -97,6 +218,26 @@ impl IsGraphOpNonAltering<ActionId> for NonAlteringGraphOpHelper {
                 SharedOp::ChangeActiveFuncs(_) | SharedOp::MoveSelectBy(_)
             )
     }
+
+    /// Changes kept on undo are coalesced too, because undo of edits depends
+    /// on selection and active functions. `MyApp::undo_redo` then restores the view.
+    fn coalesces_graph_op<IterChangeActiveFuncs, FuncIter, YExactIter>(
+        &self,
+        g_op: &SharedOp<IterChangeActiveFuncs, FuncIter, YExactIter>,
+        _group_id: &ActionId,
+    ) -> bool
+    where
+        IterChangeActiveFuncs: Iterator<Item = usize> + Clone,
+        FuncIter: Iterator<Item = YExactIter>,
+        YExactIter: ExactSizeIterator<Item = Y> + Clone,
+    {
+        let recording = match g_op {
+            SharedOp::MoveSelectBy(_) => self.selection,
+            SharedOp::ChangeActiveFuncs(_) => self.visibility,
+            _ => return false,
+        };
+        recording != ViewRecording::Undoable
+    }
 }
 
 pub struct MyApp {
@@ -104,17 +245,105 @@ pub struct MyApp {
     selection_range: RangeInclusive<X>,
     selection: SelectionVLines,
     legend_entries: Vec<SimpleLegendEntry>,
+    /// Indexes of legend entries in order, in which they are shown.
+    legend_order: Vec<usize>,
     plot: Plotter,
+    macros: Vec<GraphMacro>,
+    /// Newest and oldest undo entry recorded into macro, 0 is the newest entry
+    macro_entries: (usize, usize),
+    diff_view: DiffView,
+    insert_policy: InsertPolicy,
+    /// Comma separated values inserted into every editable function
+    pattern: String,
+    /// Value of new constant function
+    constant_value: Y,
+    derived: DerivedInput,
+    export_path: String,
+    /// Result of the last export
+    export_status: Option<Result<(), String>>,
+    rule: RuleInput,
+    /// Selection rules saved for reuse
+    selection_rules: Vec<SelectionRule>,
+    /// Snapping of selection edges, holding Alt disables it
+    snap: Snap,
+    gap_fill: GapFill,
+    outliers: OutlierReview,
+    resample_aggregation: Aggregation,
+    /// Interval of resampled functions without dates, in units of x
+    resample_step: X,
+    /// Interval of resampled functions with dates
+    resample_time_step: TimeDelta,
+    transform: Transform,
+    /// Unit of time, per which derivative is given and by which integral is multiplied
+    transform_unit: TimeDelta,
+    transform_inverse: InverseTransform,
+    fit: FitInput,
+    spectrum: SpectrumView,
+    /// Unit of time, per which frequencies of spectrum are given
+    spectrum_unit: TimeDelta,
+    keymap: Keymap,
+    /// Error of keymap file, default keymap is used instead
+    keymap_error: Option<KeymapError>,
+    palette: CommandPalette,
+    navigation: Navigation,
+    /// Recording of plot bounds changes in history, selection and visibility are
+    /// set in `NonAlteringGraphOpHelper`
+    viewport_recording: ViewRecording,
+    /// Profile of stretching by factor, stretching to bounds is constant
+    stretch_profile: StretchProfile,
+    stretch_baseline: StretchBaseline,
 }
 
+/// Units of time offered for rates and frequencies.
+const TIME_UNITS: [(&str, TimeDelta); 4] = [
+    ("second", TimeDelta::seconds(1)),
+    ("minute", TimeDelta::minutes(1)),
+    ("hour", TimeDelta::hours(1)),
+    ("day", TimeDelta::days(1)),
+];
+
+fn time_unit_label(unit: TimeDelta) -> &'static str {
+    TIME_UNITS
+        .iter()
+        .find(|(_, u)| *u == unit)
+        .map_or("", |(label, _)| label)
+}
+
+/// File with shortcuts, see `Keymap::parse`.
+const KEYMAP_PATH: &str = "keymap.txt";
+
+/// File with saved selection rules, see `SelectionRule::parse_all`.
+const SELECTION_RULES_PATH: &str = "selection_rules.txt";
+
+/// Factor of one step of stretching selection by keyboard.
+const STRETCH_STEP: Y = 1.1;
+
+/// Imported samples further apart are recorded as gap.
+const GAP_THRESHOLD: GapThreshold = GapThreshold {
+    time: TimeDelta::hours(1),
+    x: 60.0,
+};
+
 impl Default for MyApp {
     fn default() -> Self {
         let path = r#"C:\Users\Jindra\Downloads\1124-graph.txt"#;
         let mut funcs = Vec::new();
-        let res = Importer::import(path.to_string(), &mut funcs).unwrap();
+        let res = Importer::import(path.to_string(), &mut funcs, GAP_THRESHOLD).unwrap();
         let graph = Graph::new(funcs.into_iter().map(|b| b.into()).collect());
+        let diff_view = DiffView::new(&graph);
         let mut history = History::new();
         history.with_options(HistoryOption::TreatNonAlteringEntriesAsRegular);
+        let (selection_rules, rules_error) = match SelectionRule::load_all(SELECTION_RULES_PATH) {
+            Ok(rules) => (rules, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        let (keymap, keymap_error) = match Keymap::load(KEYMAP_PATH) {
+            Ok(keymap) => (keymap, None),
+            Err(KeymapError::Io(e)) if e.kind() == io::ErrorKind::NotFound => {
+                (Keymap::default(), None)
+            }
+            Err(e) => (Keymap::default(), Some(e)),
+        };
         Self {
             selection_range: graph.selection().clone(),
             graph: BasicReversibleGraph::new(graph, history),
@@ -130,12 +359,50 @@ impl Default for MyApp {
                     None => None,
                 },
             ),
+            legend_order: (0..res.names.len()).collect(),
             legend_entries: res
                 .names
                 .into_iter()
                 .enumerate()
                 .map(|(i, name)| SimpleLegendEntry::new(name, utils::auto_color(i), false))
                 .collect(),
+            macros: Vec::new(),
+            macro_entries: (0, 0),
+            diff_view,
+            insert_policy: InsertPolicy::default(),
+            pattern: String::new(),
+            constant_value: 0.0,
+            derived: DerivedInput::default(),
+            export_path: "export.csv".to_string(),
+            export_status: None,
+            rule: RuleInput {
+                error: rules_error,
+                ..RuleInput::default()
+            },
+            selection_rules,
+            snap: Snap::default(),
+            gap_fill: GapFill::default(),
+            outliers: OutlierReview::default(),
+            resample_aggregation: Aggregation::default(),
+            resample_step: 10.0,
+            resample_time_step: TimeDelta::hours(1),
+            transform: Transform::Derivative,
+            transform_unit: TimeDelta::hours(1),
+            transform_inverse: InverseTransform::default(),
+            fit: FitInput::default(),
+            spectrum: SpectrumView::default(),
+            spectrum_unit: TimeDelta::hours(1),
+            keymap,
+            // Palette shows error of keymap.
+            palette: CommandPalette {
+                open: keymap_error.is_some(),
+                ..CommandPalette::default()
+            },
+            keymap_error,
+            navigation: Navigation::default(),
+            viewport_recording: ViewRecording::KeptOnUndo,
+            stretch_profile: StretchProfile::Constant,
+            stretch_baseline: StretchBaseline::Chord,
         }
     }
 }
@@ -168,6 +435,31 @@ fn tinput<'a, T: FromStr + std::fmt::Display>(
     None
 }
 
+/// Applies undone or redone op, which is not a graph op. Resampling also moves
+/// `selection`, which is restored after undo or redo.
+fn apply_history_op(
+    op: &ApplyOtherOp<history::SharedHistoryOp>,
+    plot: &mut Plotter,
+    legend_entries: &mut Vec<SimpleLegendEntry>,
+    legend_order: &mut Vec<usize>,
+    selection: &mut RangeInclusive<X>,
+) {
+    match SharedDataOp::new(op) {
+        SharedDataOp::ChangePlotBounds(change) => plot.bounds.apply_change(change),
+        SharedDataOp::ChangeLegend(ApplyDataOp::Undo(change)) => {
+            change.undo(legend_entries, legend_order)
+        }
+        SharedDataOp::ChangeLegend(ApplyDataOp::Redo(change)) => {
+            change.redo(legend_entries, legend_order)
+        }
+        SharedDataOp::Resample(change) => {
+            plot.resample(&change);
+            *selection = ResampleChange::map_x(&change, *selection.start())
+                ..=ResampleChange::map_x(&change, *selection.end());
+        }
+    }
+}
+
 fn owerwrite_str<T: std::fmt::Display>(str: &mut String, value: T) -> Result<(), std::fmt::Error> {
     str.clear();
     write!(str, "{}", value)
@@ -181,24 +473,44 @@ impl MyApp {
                 .button(format!("Undo {}", self.graph.history().undo_len()))
                 .clicked()
             {
-                self.graph.undo(|op| {
-                    let op = ApplyDataOp::new(&op, |shared| match shared {
-                        history::SharedHistoryOp::ChangePlotBounds(bounds) => bounds,
-                    });
-                    self.plot.bounds.apply_change(op);
-                });
+                self.run_command(Command::Undo);
             }
             if ui
                 .button(format!("Redo {}", self.graph.history().redo_len()))
                 .clicked()
             {
-                self.graph.redo(|op| {
-                    let op = ApplyDataOp::new(&op, |shared| match shared {
-                        history::SharedHistoryOp::ChangePlotBounds(bounds) => bounds,
-                    });
-                    self.plot.bounds.apply_change(op);
-                });
+                self.run_command(Command::Redo);
+            }
+            if ui.button("Commands").clicked() {
+                self.run_command(Command::CommandPalette);
             }
+            ui.horizontal(|ui| {
+                let back = self.navigation.back_len();
+                let forward = self.navigation.forward_len();
+                for (command, text, enabled) in [
+                    (Command::Back, format!("Back {}", back), back > 0),
+                    (
+                        Command::Forward,
+                        format!("Forward {}", forward),
+                        forward > 0,
+                    ),
+                    (
+                        Command::ZoomToSelection,
+                        "Zoom to selection".to_string(),
+                        true,
+                    ),
+                    (Command::ZoomToFit, "Fit all".to_string(), true),
+                    (
+                        Command::ZoomToFunction,
+                        "Zoom to function".to_string(),
+                        true,
+                    ),
+                ] {
+                    if ui.add_enabled(enabled, egui::Button::new(text)).clicked() {
+                        self.run_command(command);
+                    }
+                }
+            });
             ui.label(format!("{:#?}", self.graph.graph().selection()));
             ui.horizontal(|ui| {
                 ui.label(format!(
@@ -213,6 +525,238 @@ impl MyApp {
         });
     }
 
+    fn run_command(&mut self, command: Command) {
+        match command {
+            Command::Undo => self.undo_redo(true),
+            Command::Redo => self.undo_redo(false),
+            Command::DeleteSelection => {
+                {
+                    let mut builder = self.graph.action(ActionId::DeleteSelection);
+                    builder.for_each_selection(|b, excluded| b.delete_excluding(excluded));
+                    builder.change_selection(self.plot.bounds.range_x());
+                }
+                self.graph.clear_selection_ranges();
+            }
+            Command::SelectAll => {
+                if let Some(range) = self.graph.graph().x_range() {
+                    self.graph.clear_selection_ranges();
+                    self.graph
+                        .action(ActionId::Conditions)
+                        .change_selection(range);
+                }
+            }
+            Command::NudgeLeft => self.nudge_selection(|_, x| x - 1.0),
+            Command::NudgeRight => self.nudge_selection(|_, x| x + 1.0),
+            Command::NudgeUnitLeft => self.nudge_selection(|unit, x| unit(x, -1)),
+            Command::NudgeUnitRight => self.nudge_selection(|unit, x| unit(x, 1)),
+            Command::Back => {
+                if let Some(bounds) = self.navigation.back(self.plot.bounds) {
+                    let old = std::mem::replace(&mut self.plot.bounds, bounds);
+                    self.record_bounds_change(&old);
+                }
+            }
+            Command::Forward => {
+                if let Some(bounds) = self.navigation.forward(self.plot.bounds) {
+                    let old = std::mem::replace(&mut self.plot.bounds, bounds);
+                    self.record_bounds_change(&old);
+                }
+            }
+            Command::ZoomToSelection => {
+                let selection = self.graph.graph().selection().clone();
+                let y = self.value_range_in(&selection);
+                self.zoom_to(selection, y);
+            }
+            Command::ZoomToFit => {
+                let graph = self.graph.graph();
+                if let Some(x) = graph.x_range() {
+                    let y = graph.global_min().zip(graph.global_max());
+                    self.zoom_to(x, y.map(|(min, max)| min..=max));
+                }
+            }
+            Command::ZoomToFunction => {
+                let graph = self.graph.graph();
+                let points = self
+                    .focused_func()
+                    .and_then(|fi| graph.get_func(fi))
+                    .map(|f| f.points())
+                    .filter(|points| !points.is_empty());
+                if let Some(points) = points {
+                    let x = points[0].x..=points[points.len() - 1].x;
+                    let y = points.iter().fold(Y::INFINITY..=Y::NEG_INFINITY, |r, p| {
+                        r.start().min(p.y)..=r.end().max(p.y)
+                    });
+                    self.zoom_to(x, Some(y));
+                }
+            }
+            Command::ZoomIn => self.zoom(2.0),
+            Command::ZoomOut => self.zoom(0.5),
+            Command::ClearSelectionRanges => self.graph.clear_selection_ranges(),
+            Command::DuplicateSelection => self.duplicate_selection(),
+            Command::NewEmptyFunction => self.new_function(Vec::new()),
+            Command::ToggleDiffView => self.diff_view.enabled = !self.diff_view.enabled,
+            Command::ReplayLastMacro => {
+                if let Some(graph_macro) = self.macros.last() {
+                    self.graph.replay_macro(ActionId::Macro, graph_macro);
+                }
+            }
+            Command::StretchUp => self.stretch_selection(STRETCH_STEP),
+            Command::StretchDown => self.stretch_selection(1.0 / STRETCH_STEP),
+            Command::InsertPattern => self.insert_pattern(),
+            Command::RecordMacro => self.record_macro(),
+            Command::FillGaps => self.fill_gaps(&self.selected_gaps()),
+            Command::DetectOutliers => self.detect_outliers(),
+            Command::NextOutlier => self.review_outlier(true),
+            Command::PreviousOutlier => self.review_outlier(false),
+            Command::Resample => self.resample(),
+            Command::Transform => self.transform_in_place(),
+            Command::InverseTransform => self.inverse_transform(),
+            Command::Fit => self.fit_focused(),
+            Command::Filter => self.apply_filter(),
+            Command::Export => self.export(),
+            Command::ToggleLockFunction => self.toggle_lock_focused(),
+            Command::DeleteFunction => self.delete_focused(),
+            Command::CommandPalette => {
+                self.palette.open = !self.palette.open;
+                self.palette.filter.clear();
+            }
+        }
+    }
+
+    /// Moves both edges of selection by `nudge`, which gets function moving x by calendar
+    /// units. Calendar unit is the one used for snapping or day, graph without date and
+    /// time axis moves by samples.
+    fn nudge_selection(&mut self, nudge: impl Fn(&dyn Fn(X, i32) -> X, X) -> X) {
+        let unit = match self.snap {
+            Snap::Calendar(unit) => unit,
+            _ => CalendarUnit::Day,
+        };
+        let time_ctx = self.plot.time_ctx.as_ref();
+        let by_unit = |x: X, count: i32| match time_ctx {
+            Some(ctx) => ctx.shift(x, unit, count).round(),
+            None => x + count as X,
+        };
+        let selection = self.graph.graph().selection();
+        let selection = nudge(&by_unit, *selection.start())..=nudge(&by_unit, *selection.end());
+        self.graph
+            .open_action(ActionId::Conditions)
+            .change_selection(selection);
+    }
+
+    /// Zooms x axis around center of plot, `factor` above 1 zooms in.
+    fn zoom(&mut self, factor: f64) {
+        let old = self.plot.bounds;
+        let range = old.range_x();
+        self.plot.bounds.set_x_center_width(
+            (range.start() + range.end()) / 2.0,
+            (range.end() - range.start()) / factor,
+        );
+        self.navigation.jump(old);
+        self.record_bounds_change(&old);
+    }
+
+    /// Records change of plot bounds from `old` in history, unless viewport is kept on undo.
+    fn record_bounds_change(&mut self, old: &PlotBounds) {
+        if self.viewport_recording != ViewRecording::KeptOnUndo && *old != self.plot.bounds {
+            let change = PlotBoundsChange::from_old_new(old, &self.plot.bounds);
+            self.graph.open_action(ActionId::Conditions).other(
+                PlotBoundsChangeOp::new(&change)
+                    .coalesced(self.viewport_recording == ViewRecording::Coalesced),
+            );
+        }
+    }
+
+    /// Undoes or redoes the last entry. Selection and visible functions, which are kept
+    /// on undo, are restored afterwards by a new entry.
+    fn undo_redo(&mut self, undo: bool) {
+        let graph = self.graph.graph();
+        let mut selection = graph.selection().clone();
+        let states = (0..graph.len())
+            .map(|fi| graph.get_func_state(fi))
+            .collect::<Vec<_>>();
+        let apply = |op: ApplyOtherOp<_>| {
+            apply_history_op(
+                &op,
+                &mut self.plot,
+                &mut self.legend_entries,
+                &mut self.legend_order,
+                &mut selection,
+            )
+        };
+        if undo {
+            self.graph.undo(apply);
+        } else {
+            self.graph.redo(apply);
+        }
+        let helper = self.graph.history().helper();
+        let (keep_selection, keep_visibility) = (
+            helper.selection == ViewRecording::KeptOnUndo,
+            helper.visibility == ViewRecording::KeptOnUndo,
+        );
+        if !keep_selection && !keep_visibility {
+            return;
+        }
+        let mut builder = self.graph.action(ActionId::Conditions);
+        if keep_selection {
+            builder.change_selection(selection);
+        }
+        if keep_visibility {
+            for (fi, state) in states.into_iter().enumerate() {
+                if let Some(state) = state {
+                    builder.change_func_state(fi, state);
+                }
+            }
+        }
+    }
+
+    /// Shows `x` range and `y` range, if any, with a margin. Previous view can be returned
+    /// to by `Command::Back`.
+    fn zoom_to(&mut self, x: RangeInclusive<X>, y: Option<RangeInclusive<Y>>) {
+        let margin = |range: &RangeInclusive<f64>| {
+            let margin = ((range.end() - range.start()) * 0.05).max(0.5);
+            [range.start() - margin, range.end() + margin]
+        };
+        let [x_min, x_max] = margin(&x);
+        let [y_min, y_max] = y.map_or_else(
+            || [self.plot.bounds.min()[1], self.plot.bounds.max()[1]],
+            |y| margin(&y),
+        );
+        let old = self.plot.bounds;
+        self.navigation.jump(old);
+        self.plot.bounds = PlotBounds::from_min_max([x_min, y_min], [x_max, y_max]);
+        self.record_bounds_change(&old);
+    }
+
+    /// Value range of active functions within `x`.
+    fn value_range_in(&self, x: &RangeInclusive<X>) -> Option<RangeInclusive<Y>> {
+        let graph = self.graph.graph();
+        graph
+            .active_func_indexes()
+            .filter_map(|fi| graph.get_func(fi))
+            .flat_map(|f| f.points().iter().filter(|p| x.contains(&p.x)).map(|p| p.y))
+            .fold(None, |range, y| match range {
+                Some(r) => Some(y.min(*r.start())..=y.max(*r.end())),
+                None => Some(y..=y),
+            })
+    }
+
+    fn time_unit_combo(ui: &mut Ui, label: &str, unit: &mut TimeDelta) {
+        egui::ComboBox::from_label(label)
+            .selected_text(time_unit_label(*unit))
+            .show_ui(ui, |ui| {
+                for (label, u) in TIME_UNITS {
+                    ui.selectable_value(unit, u, label);
+                }
+            });
+    }
+
+    /// Function hovered in legend or the first active function.
+    fn focused_func(&self) -> Option<usize> {
+        self.legend_entries
+            .iter()
+            .position(|e| e.hovered)
+            .or_else(|| self.graph.graph().active_func_indexes().next())
+    }
+
     fn selection(plot_ui: &mut PlotUi, selection: &RangeInclusive<f64>) {
         plot_ui.vline(
             VLine::new(*selection.start())
@@ -228,6 +772,68 @@ impl MyApp {
         );
     }
 
+    /// Shows additional selection ranges and range being dragged.
+    fn selection_ranges(plot_ui: &mut PlotUi, ranges: &[RangeInclusive<X>]) {
+        let bounds = plot_ui.plot_bounds();
+        let (bottom, top) = (bounds.min()[1], bounds.max()[1]);
+        for range in ranges {
+            let (start, end) = (*range.start(), *range.end());
+            plot_ui.polygon(
+                Polygon::new(vec![
+                    [start, bottom],
+                    [end, bottom],
+                    [end, top],
+                    [start, top],
+                ])
+                .fill_color(Color32::LIGHT_RED.gamma_multiply(0.15))
+                .stroke((1.0, Color32::LIGHT_RED))
+                .allow_hover(false),
+            );
+        }
+    }
+
+    /// Shift dragging adds selection range, shift clicking removes ranges under pointer.
+    fn selection_range_gesture(plot_ui: &mut PlotUi) -> Option<SelectionRangeGesture> {
+        let drag_start_id = Id::new("selection_range_drag_start");
+        let shift = plot_ui.ctx().input(|i| i.modifiers.shift);
+        let response = plot_ui.response().clone();
+        let pointer = plot_ui.pointer_coordinate().map(|p| p.x);
+        if shift && response.drag_started() {
+            if let Some(x) = pointer {
+                plot_ui.ctx().data_mut(|w| w.insert_temp(drag_start_id, x));
+            }
+        }
+        let start: Option<X> = plot_ui.ctx().data(|r| r.get_temp(drag_start_id));
+        if let Some(start) = start {
+            if response.drag_stopped() {
+                plot_ui.ctx().data_mut(|w| w.remove::<X>(drag_start_id));
+                let end = pointer?;
+                return Some(SelectionRangeGesture::Add(start.min(end)..=start.max(end)));
+            }
+            if let Some(end) = pointer {
+                Self::selection_ranges(plot_ui, &[start.min(end)..=start.max(end)]);
+            }
+        } else if shift && response.clicked() {
+            return Some(SelectionRangeGesture::Remove(pointer?));
+        }
+        None
+    }
+
+    /// Shows own selections of functions in color of their legend entries.
+    fn func_selections(plot_ui: &mut PlotUi, graph: &Graph, entries: &[SimpleLegendEntry]) {
+        for (index, entry) in entries.iter().enumerate() {
+            if let Some(selection) = graph.func_selection(index) {
+                for x in [*selection.start(), *selection.end()] {
+                    plot_ui.vline(
+                        VLine::new(x)
+                            .color(entry.color)
+                            .style(LineStyle::dashed_dense()),
+                    );
+                }
+            }
+        }
+    }
+
     fn selection_control(selection: &mut RangeInclusive<f64>, bounds: &RangeInclusive<f64>) {
         if !bounds.contains(&selection.start()) && !bounds.contains(&selection.end()) {
             *selection = bounds.clone();
@@ -299,175 +905,36 @@ impl MyApp {
     fn aspect_ratio_slider(ui: &mut Ui, ratio: &mut f32) -> Response {
         ui.add(Slider::new(ratio, 1.0..=100.0).max_decimals(0))
     }
-
-    fn stretch_y_controls(
-        ui: &mut Ui,
-        bounds: &RangeInclusive<f64>,
-        graph: &mut BasicReversibleGraph<ActionId, OwnedHistoryOp, NonAlteringGraphOpHelper>,
-    ) {
-        ui.vertical(|ui| {
-            let mut stretch_flags = BitFlags::empty();
-            ui.horizontal(|ui| {
-                let stretch_y_mode_id = Id::new("stretch_y_mode");
-                let old_both = ui.data(|r| r.get_temp(stretch_y_mode_id).unwrap_or(false));
-                let mut both = old_both;
-
-                ui.checkbox(&mut both, "Stretch both").rect.height();
-                if old_both != both {
-                    ui.data_mut(|w| w.insert_temp(stretch_y_mode_id, both));
-                }
-                if both {
-                    stretch_flags = BitFlags::all();
-                }
-                ui.group(|ui| {
-                    if stretch_flags.is_all() {
-                        ui.disable();
-                    }
-                    let stretch_factor_mode_id = Id::new("stretch_factor_mode");
-                    let mut is_top =
-                        ui.data(|r| r.get_temp(stretch_factor_mode_id).unwrap_or(true));
-                    let mut new_is_top = is_top;
-                    ui.toggle_value(&mut new_is_top, if is_top { "Top" } else { "Bottom" });
-
-                    stretch_flags.insert(if new_is_top {
-                        YStretcherFlags::Top
-                    } else {
-                        YStretcherFlags::Bottom
-                    });
-                    if new_is_top != is_top {
-                        ui.data_mut(|w| w.insert_temp(stretch_factor_mode_id, new_is_top));
-                    }
-                    is_top = new_is_top;
-                });
-            });
-            let mut stretch_bounds = if stretch_flags.is_all() {
-                StretchYBounds::new(Y::NEG_INFINITY, Y::INFINITY)
-            } else {
-                StretchYBounds::new(Y::NAN, Y::NAN)
-            };
-
-            let value_range = match graph.graph().value_range() {
-                Some(r) => r,
-                None => return,
-            };
-            let overflow_height_id = Id::new("overwlow/underflow_of_height_of_stretch_y_controls");
-            let height =
-                ui.available_height() - ui.data(|r| r.get_temp(overflow_height_id).unwrap_or(0.0));
-            ui.spacing_mut().slider_width = height;
-            let mut min_height = 0.0_f32;
-            let range =
-                value_range.start().min(*bounds.start())..=value_range.end().max(*bounds.end());
-
-            ui.horizontal_top(|ui| {
-                let max = *value_range.end();
-                let mut new_max = max;
-
-                let resp = ui.add(SliderWidget {
-                    num: &mut new_max,
-                    range: range.clone(),
-                    data: SliderOptionalData {
-                        orientation: egui::SliderOrientation::Vertical,
-                        ..Default::default()
-                    },
-                });
-                min_height = min_height.max(resp.rect.height());
-
-                fn is_action_end(response: &Response) -> bool {
-                    response.drag_stopped() || response.lost_focus()
-                }
-
-                let mut action_end = false;
-                if (new_max - max).abs() >= Y::EPSILON {
-                    action_end = is_action_end(&resp);
-                    stretch_bounds.set_max(new_max);
-                }
-
-                let min = *value_range.start();
-                let mut new_min = min;
-                let resp = ui.add(SliderWidget {
-                    num: &mut new_min,
-                    range: range.clone(),
-                    data: SliderOptionalData {
-                        orientation: egui::SliderOrientation::Vertical,
-                        ..Default::default()
-                    },
-                });
-                min_height = min_height.max(resp.rect.height());
-                if (new_min - min).abs() >= Y::EPSILON {
-                    if action_end {
-                        action_end = is_action_end(&resp);
-                    }
-                    stretch_bounds.set_min(new_min);
-                }
-                let stretch_factor_zp_id = Id::new("stretch_factor_zp");
-
-                let mut factor: f64 = ui.data(|r| r.get_temp(stretch_factor_zp_id).unwrap_or(1.0));
-                let mut new_factor = factor;
-                let resp = ui.add(SliderWidget {
-                    num: &mut new_factor,
-                    range: -1.5..=1.5,
-                    data: SliderOptionalData {
-                        step: 0.01,
-                        clamp_to_range: false,
-                        orientation: egui::SliderOrientation::Vertical,
-                        ..Default::default()
-                    },
-                });
-                min_height = min_height.max(resp.rect.height());
-
-                let mut is_factor_action_end = false;
-                if (new_factor - factor).abs() >= Y::EPSILON {
-                    let factor_action_end = is_action_end(&resp);
-                    if action_end {
-                        action_end = factor_action_end;
-                    }
-                    is_factor_action_end = factor_action_end;
-                }
-                if !is_factor_action_end && (resp.dragged() || resp.drag_started()) {
-                    if new_factor.abs() < f64::EPSILON {
-                        new_factor = new_factor.signum() * 0.01;
-                    }
-                    if (new_factor - factor).abs() >= f64::EPSILON {
-                        ui.data_mut(|w| w.insert_temp(stretch_factor_zp_id, new_factor));
-                    }
-                } else {
-                    ui.data_mut(|w| w.remove::<f64>(stretch_factor_zp_id));
-                }
-                factor = new_factor / factor;
-                if factor.abs() > 10.0 {
-                    println!("factor {} is too large!", factor);
-                }
-                if (factor - 1.0).abs() < 0.0001 {
-                    factor = 1.0;
-                }
-                {
-                    let mut b = graph.open_action(ActionId::StretchY);
-                    let _ = b.stretch_y_bounds(&stretch_bounds);
-                    if let Some(stretch) = StretchY::new(factor, stretch_flags) {
-                        let _ = b.stretch_y_with_factor(&stretch);
-                    }
-                }
-
-                if action_end {
-                    graph.close_action(ActionId::StretchY);
-                }
-            });
-            ui.data_mut(|w| {
-                w.insert_temp(
-                    overflow_height_id,
-                    min_height.max(ui.min_rect().height()) - height,
-                )
-            });
-        });
-    }
 }
 
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if !ctx.wants_keyboard_input() {
+            for command in ctx.input_mut(|i| self.keymap.pressed(i)) {
+                self.run_command(command);
+            }
+        }
+        self.command_palette(ctx);
         self.top(ctx, _frame);
         let y_bounds = self.plot.bounds.range_y();
         egui::SidePanel::right("history_side_panel").show(ctx, |ui| {
             ui.vertical(|ui| {
+                self.macro_controls(ui);
+                self.snap_controls(ui);
+                self.gap_controls(ui);
+                self.outlier_controls(ui);
+                self.resample_controls(ui);
+                self.transform_controls(ui);
+                self.fit_controls(ui);
+                self.spectrum_controls(ui);
+                self.view_recording_controls(ui);
+                self.stretch_profile_controls(ui);
+                self.selection_rule_controls(ui);
+                self.insert_controls(ui);
+                self.functions_controls(ui);
+                self.derived_controls(ui);
+                self.diff_controls(ui);
+                self.export_controls(ui);
                 ui.label("REDO");
                 ui.separator();
 
@@ -531,7 +998,13 @@ impl eframe::App for MyApp {
             });
         });
         egui::SidePanel::right("y_stretch_controls_side_panel").show(ctx, |ui| {
-            Self::stretch_y_controls(ui, &y_bounds, &mut self.graph)
+            Self::stretch_y_controls(
+                ui,
+                &y_bounds,
+                &self.stretch_profile,
+                &mut self.stretch_baseline,
+                &mut self.graph,
+            )
         });
 
         let selection = self.graph.graph().selection();
@@ -546,9 +1019,15 @@ impl eframe::App for MyApp {
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.vertical(|ui| {
                 let x_bounds = self.plot.bounds.range_x();
+                let frame_bounds = self.plot.bounds;
 
-                let mut selection = self.graph.graph().selection().clone();
+                let old_selection = self.graph.graph().selection().clone();
+                let mut selection = old_selection.clone();
                 Self::selection_slider(ui, &mut selection, &x_bounds);
+                let snapping = !ui.input(|i| i.modifiers.alt);
+                if snapping && selection != old_selection {
+                    selection = self.snap_selection(&old_selection, selection);
+                }
 
                 let hoverflow_id = Id::new("height_oweflow_plot");
                 let available_height = ui.available_height();
@@ -556,16 +1035,55 @@ impl eframe::App for MyApp {
                 let height = available_height - howeflow;
                 ui.horizontal(|ui| {
                     calculator::width(ui, |ui, width| {
-                        let lines = self.graph.graph().active_functions_index().map(|(f, fi)| {
-                            f.line()
-                                .name(&self.legend_entries[fi].name)
-                                .color(self.legend_entries[fi].color)
-                                .highlight(self.legend_entries[fi].hovered)
-                        });
+                        let graph = self.graph.graph();
+                        // Lines are drawn in order of legend entries.
+                        let lines = self
+                            .legend_order
+                            .iter()
+                            .filter(|fi| graph.get_func_state(**fi) == Some(GraphFuncState::Active))
+                            .filter_map(|fi| {
+                                Some((graph.get_func(*fi)?, &self.legend_entries[*fi]))
+                            })
+                            .flat_map(|(f, entry)| {
+                                f.lines().into_iter().map(|line| {
+                                    line.name(&entry.name)
+                                        .color(entry.color)
+                                        .highlight(entry.hovered)
+                                        .width(entry.width)
+                                        .style(if entry.dashed {
+                                            LineStyle::dashed_loose()
+                                        } else {
+                                            LineStyle::Solid
+                                        })
+                                })
+                            });
                         let old_bounds = self.plot.bounds;
+                        self.plot.allow_drag =
+                            !ui.input(|i| i.modifiers.shift) && !self.selection.grabbed(ui);
                         let response = self.plot.show(ui, "my_plot", width, height, |plot_ui| {
                             let mut i = 0;
                             Self::selection(plot_ui, &selection);
+                            Self::selection_ranges(plot_ui, graph.extra_selections());
+                            let gesture = Self::selection_range_gesture(plot_ui);
+                            let dragged = self.selection.drag(plot_ui, &selection);
+                            Self::func_selections(
+                                plot_ui,
+                                self.graph.graph(),
+                                &self.legend_entries,
+                            );
+                            Self::gaps(plot_ui, self.graph.graph(), &self.legend_entries);
+                            Self::outliers(plot_ui, &self.outliers, &self.legend_entries);
+                            Self::fit_curve(
+                                plot_ui,
+                                self.graph.graph(),
+                                &self.fit,
+                                &self.legend_entries,
+                            );
+                            if self.diff_view.enabled {
+                                self.diff_view.show(plot_ui, self.graph.graph(), |fi| {
+                                    self.legend_entries[fi].color
+                                });
+                            }
                             if let Some(range) = self.graph.graph().value_range() {
                                 plot_ui.hline(
                                     HLine::new(*range.start()).name("Min").allow_hover(true),
@@ -577,35 +1095,45 @@ impl eframe::App for MyApp {
                                 plot_ui.line(line);
                                 i += 1;
                             });
+                            (gesture, dragged)
                         });
+                        let (gesture, dragged) = response.inner;
+                        if let Some(dragged) = dragged {
+                            selection = if snapping {
+                                self.snap_selection(&selection, dragged)
+                            } else {
+                                dragged
+                            };
+                        }
+                        match gesture {
+                            Some(SelectionRangeGesture::Add(range)) => {
+                                let range = if snapping {
+                                    self.snap_x(*range.start())..=self.snap_x(*range.end())
+                                } else {
+                                    range
+                                };
+                                self.graph.add_selection_range(range)
+                            }
+                            Some(SelectionRangeGesture::Remove(x)) => {
+                                self.graph.remove_selection_range_at(x);
+                            }
+                            None => {}
+                        }
 
                         let new_bounds = response.transform.bounds();
 
                         if *new_bounds != old_bounds {
                             let x_bounds = new_bounds.range_x();
                             Self::selection_control(&mut selection, &x_bounds);
-                            self.graph.open_action(ActionId::Conditions).other(
-                                PlotBoundsChangeOp::new(&PlotBoundsChange::from_old_new(
-                                    &old_bounds,
-                                    new_bounds,
-                                )),
-                            );
                         }
 
-                        ui.input(|r| {
-                            if r.key_pressed(Key::Delete) {
-                                dbg!("plot has focus and delete");
-                                let mut builder = self.graph.action(ActionId::DeleteSelection);
-                                builder.delete();
-                                builder.change_selection(x_bounds.clone());
-                            }
-                        });
                         if let Some(mut legend) = LegendWidget::try_new(
                             response.response.rect,
                             Legend::default(),
                             &mut SimpleLegendEntries::new(
                                 &mut self.graph,
                                 &mut self.legend_entries,
+                                &mut self.legend_order,
                             ),
                         ) {
                             ui.put(response.response.rect, &mut legend);
@@ -618,6 +1146,30 @@ impl eframe::App for MyApp {
                     .open_action(ActionId::Conditions)
                     .change_selection(selection.clone());
                 self.selection_range = selection;
+                let graph = self.graph.graph();
+                if let Some(x_range) = graph.x_range() {
+                    let lines = self
+                        .legend_order
+                        .iter()
+                        .filter(|fi| graph.get_func_state(**fi) == Some(GraphFuncState::Active))
+                        .filter_map(|fi| {
+                            Some((graph.get_func(*fi)?, self.legend_entries[*fi].color))
+                        });
+                    if let Some(x) = minimap::minimap(
+                        ui,
+                        40.0,
+                        &x_range,
+                        lines,
+                        &self.plot.bounds,
+                        graph.selection(),
+                    ) {
+                        let width = self.plot.bounds.width();
+                        self.plot.bounds.set_x_center_width(x, width);
+                    }
+                }
+                self.navigation
+                    .view_changed(frame_bounds, self.plot.bounds != frame_bounds);
+                self.record_bounds_change(&frame_bounds);
                 Self::aspect_ratio_slider(ui, &mut self.plot.data_aspect);
                 let ah = ui.min_rect().height();
                 ui.data_mut(|w| w.insert_temp(hoverflow_id, ah - height));
@@ -0,0 +1,68 @@
+use eframe::egui::{self, Color32, Key, ScrollArea};
+
+use super::{keymap::Command, MyApp, KEYMAP_PATH};
+
+/// Searchable list of commands.
+#[derive(Default)]
+pub(super) struct CommandPalette {
+    pub(super) open: bool,
+    pub(super) filter: String,
+}
+
+impl MyApp {
+    pub(super) fn command_palette(&mut self, ctx: &egui::Context) {
+        if !self.palette.open {
+            return;
+        }
+        let mut run = None;
+        let mut open = true;
+        egui::Window::new("Commands")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                if let Some(e) = &self.keymap_error {
+                    ui.colored_label(
+                        Color32::LIGHT_RED,
+                        format!("{}: {}, using default keymap", KEYMAP_PATH, e),
+                    );
+                }
+                let response = ui.text_edit_singleline(&mut self.palette.filter);
+                response.request_focus();
+                let filter = self.palette.filter.to_lowercase();
+                let commands = Command::ALL
+                    .into_iter()
+                    .filter(|c| {
+                        c.label().to_lowercase().contains(&filter) || c.name().contains(&filter)
+                    })
+                    .collect::<Vec<_>>();
+                if ui.input(|i| i.key_pressed(Key::Enter)) {
+                    run = commands.first().copied();
+                }
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for command in commands {
+                        let shortcuts = self
+                            .keymap
+                            .shortcuts(command)
+                            .map(|s| ctx.format_shortcut(s))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        ui.horizontal(|ui| {
+                            if ui.button(command.label()).clicked() {
+                                run = Some(command);
+                            }
+                            ui.weak(shortcuts);
+                        });
+                    }
+                });
+            });
+        if !open || ctx.input(|i| i.key_pressed(Key::Escape)) {
+            self.palette.open = false;
+        }
+        if let Some(command) = run {
+            self.palette.open = false;
+            if command != Command::CommandPalette {
+                self.run_command(command);
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use eframe::egui::{Color32, Ui};
+
+use crate::{
+    functions::expression::Expression,
+    widgets::legend_widget::simple_legend_entries::{SimpleLegendEntries, SimpleLegendEntry},
+};
+
+use super::{utils, MyApp};
+
+/// Input of new derived function.
+#[derive(Default)]
+pub(super) struct DerivedInput {
+    name: String,
+    expression: String,
+    live: bool,
+    error: Option<String>,
+}
+
+impl MyApp {
+    pub(super) fn derived_controls(&mut self, ui: &mut Ui) {
+        ui.label("DERIVED");
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut self.derived.name);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Expression");
+            ui.text_edit_singleline(&mut self.derived.expression)
+                .on_hover_text("e.g. A - 0.5 * \"B 2\" + sqrt(abs(C))");
+        });
+        ui.checkbox(&mut self.derived.live, "Recompute when sources change");
+        if ui.button("Create").clicked() {
+            let graph = self.graph.graph();
+            let resolve = |name: &str| {
+                let index = self.legend_entries.iter().position(|e| e.name == name)?;
+                graph.func_id(index)
+            };
+            let result = Expression::parse(&self.derived.expression, resolve)
+                .and_then(|expression| Ok((graph.derive(&expression)?, expression)));
+            match result {
+                Ok(((x, values), expression)) => {
+                    let id = graph.next_func_id();
+                    let name = match self.derived.name.trim() {
+                        "" => expression.text().to_string(),
+                        name => name.to_string(),
+                    };
+                    let style = SimpleLegendEntry::new(name, utils::auto_color(id), false).style();
+                    SimpleLegendEntries::new(
+                        &mut self.graph,
+                        &mut self.legend_entries,
+                        &mut self.legend_order,
+                    )
+                    .add_func(style, x, values);
+                    if self.derived.live {
+                        self.graph.set_live_expression(id, Some(expression));
+                    }
+                    self.derived.error = None;
+                }
+                Err(e) => self.derived.error = Some(e.to_string()),
+            }
+        }
+        if let Some(error) = &self.derived.error {
+            ui.colored_label(Color32::LIGHT_RED, error);
+        }
+        let graph = self.graph.graph();
+        let mut detach = None;
+        for (index, entry) in self.legend_entries.iter().enumerate() {
+            let id = match graph.func_id(index) {
+                Some(id) if graph.live_expression(id).is_some() => id,
+                _ => continue,
+            };
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{} = {}",
+                    entry.name,
+                    graph
+                        .live_expression(id)
+                        .map(|e| e.text())
+                        .unwrap_or_default()
+                ));
+                if ui.button("Detach").clicked() {
+                    detach = Some(id);
+                }
+            });
+        }
+        if let Some(id) = detach {
+            self.graph.set_live_expression(id, None);
+        }
+        ui.separator();
+    }
+}
@@ -0,0 +1,165 @@
+use eframe::egui::{Color32, ScrollArea, Ui};
+use egui_plot::{Line, LineStyle, PlotPoints, PlotUi, Points};
+
+use crate::{
+    functions::function::func_diff::{DiffRegion, FuncDiff},
+    graph::Graph,
+    types::point::{Point, X, Y},
+};
+
+use super::{ActionId, MyApp};
+
+/// Keeps copy of functions as they were imported and differences of current functions to them.
+pub struct DiffView {
+    pub enabled: bool,
+    /// Imported functions indexed by their ids, see `Graph::func_id`
+    originals: Vec<Box<[Point]>>,
+    /// Ids of current functions, as they were at last update
+    ids: Vec<usize>,
+    /// Differences of current functions, functions added after import have none
+    diffs: Vec<Option<FuncDiff>>,
+    revision: Option<u64>,
+}
+
+impl DiffView {
+    pub fn new(graph: &Graph) -> Self {
+        Self {
+            enabled: false,
+            originals: graph
+                .functions()
+                .map(|f| f.points().to_vec().into_boxed_slice())
+                .collect(),
+            ids: Vec::new(),
+            diffs: Vec::new(),
+            revision: None,
+        }
+    }
+
+    /// Recomputes differences, if graph was modified since last update.
+    pub fn update(&mut self, graph: &Graph) {
+        if self.revision == Some(graph.revision()) {
+            return;
+        }
+        self.revision = Some(graph.revision());
+        self.ids = (0..graph.len()).filter_map(|i| graph.func_id(i)).collect();
+        self.diffs = graph
+            .functions()
+            .zip(self.ids.iter())
+            .map(|(f, id)| Some(FuncDiff::new(self.originals.get(*id)?, f.points())))
+            .collect();
+    }
+
+    pub fn original(&self, index: usize) -> Option<&[Point]> {
+        self.originals.get(*self.ids.get(index)?).map(|o| &**o)
+    }
+
+    pub fn diff(&self, index: usize) -> Option<&FuncDiff> {
+        self.diffs.get(index)?.as_ref()
+    }
+
+    /// Original values of region and x, at which they should replace current values.
+    pub fn revert_args(
+        &self,
+        index: usize,
+        region: &DiffRegion,
+        current: &[Point],
+    ) -> Option<(X, usize, Vec<Y>)> {
+        let original = self.original(index)?;
+        let x = match (current.get(region.current.start), current.last()) {
+            (Some(p), _) => p.x,
+            (None, Some(last)) => last.x + 1.0,
+            (None, None) => original.first()?.x,
+        };
+        let values = original[region.original.clone()]
+            .iter()
+            .map(|p| p.y)
+            .collect();
+        Some((x, region.current.len(), values))
+    }
+
+    pub fn show(&self, plot_ui: &mut PlotUi, graph: &Graph, colors: impl Fn(usize) -> Color32) {
+        for (f, fi) in graph.active_functions_index() {
+            let (original, diff) = match (self.original(fi), self.diff(fi)) {
+                (Some(original), Some(diff)) if !diff.is_empty() => (original, diff),
+                _ => continue,
+            };
+            let color = colors(fi);
+            plot_ui.line(
+                Line::new(PlotPoints::Owned(original.to_vec()))
+                    .color(color.gamma_multiply(0.35))
+                    .style(LineStyle::dashed_loose()),
+            );
+            for region in diff.regions() {
+                // Deleted region is not visible in current function, so it is highlighted in original.
+                let points = if region.current.is_empty() {
+                    &original[region.original.clone()]
+                } else {
+                    &f.points()[region.current.clone()]
+                };
+                let highlight = Color32::YELLOW.gamma_multiply(0.6);
+                let points = PlotPoints::Owned(points.to_vec());
+                if region.original.len().max(region.current.len()) > 1 {
+                    plot_ui.line(Line::new(points).color(highlight).width(4.0));
+                } else {
+                    plot_ui.points(Points::new(points).color(highlight).radius(4.0));
+                }
+            }
+        }
+    }
+}
+
+impl MyApp {
+    pub(super) fn diff_controls(&mut self, ui: &mut Ui) {
+        ui.label("DIFF");
+        ui.separator();
+        ui.checkbox(&mut self.diff_view.enabled, "Show differences to original");
+        if !self.diff_view.enabled {
+            ui.separator();
+            return;
+        }
+        self.diff_view.update(self.graph.graph());
+        let mut revert = None;
+        ScrollArea::vertical()
+            .id_source("diff_scroll_area")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for (f, fi) in self.graph.graph().active_functions_index() {
+                    let diff = match self.diff_view.diff(fi) {
+                        Some(diff) if !diff.is_empty() => diff,
+                        _ => continue,
+                    };
+                    let summary = diff.summary();
+                    ui.label(format!(
+                        "{}: changed {}, inserted {}, deleted {}, max deviation {}",
+                        self.legend_entries[fi].name,
+                        summary.changed,
+                        summary.inserted,
+                        summary.deleted,
+                        summary.max_abs_deviation
+                    ));
+                    for region in diff.regions() {
+                        ui.horizontal(|ui| {
+                            ui.label(format!(
+                                "{:?} {:?} -> {:?}",
+                                region.kind(),
+                                region.original,
+                                region.current
+                            ));
+                            if ui.button("Revert").clicked() {
+                                revert = self
+                                    .diff_view
+                                    .revert_args(fi, region, f.points())
+                                    .map(|(x, len, values)| (fi, x, len, values));
+                            }
+                        });
+                    }
+                }
+            });
+        if let Some((fi, x, len, values)) = revert {
+            self.graph
+                .action(ActionId::RevertRegion)
+                .replace_values(fi, x, len, values);
+        }
+        ui.separator();
+    }
+}
@@ -0,0 +1,43 @@
+use eframe::egui::{Color32, Ui};
+
+use crate::{exporter::Exporter, graph::GraphFuncState};
+
+use super::MyApp;
+
+impl MyApp {
+    pub(super) fn export_controls(&mut self, ui: &mut Ui) {
+        ui.label("EXPORT");
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.export_path);
+            if ui.button("Export").clicked() {
+                self.export();
+            }
+        });
+        match &self.export_status {
+            Some(Ok(())) => {
+                ui.label("Active functions were exported.");
+            }
+            Some(Err(error)) => {
+                ui.colored_label(Color32::LIGHT_RED, error);
+            }
+            None => {}
+        }
+        ui.separator();
+    }
+
+    /// Exports active functions into file at export path.
+    pub(super) fn export(&mut self) {
+        let graph = self.graph.graph();
+        // Columns are in order of legend entries.
+        let columns = self
+            .legend_order
+            .iter()
+            .filter(|fi| graph.get_func_state(**fi) == Some(GraphFuncState::Active))
+            .filter_map(|fi| Some((self.legend_entries[*fi].name.as_str(), graph.get_func(*fi)?)));
+        let date_time = self.plot.time_ctx.as_ref().map(|ctx| &ctx.info);
+        self.export_status = Some(
+            Exporter::export(&self.export_path, columns, date_time).map_err(|e| e.to_string()),
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use eframe::egui::{self, Color32, Ui};
+use egui_plot::{Line, LineStyle, PlotPoints, PlotUi};
+
+use crate::{
+    functions::function::fit::{Fit, FitError, FitModel},
+    graph::Graph,
+    widgets::legend_widget::simple_legend_entries::SimpleLegendEntry,
+};
+
+use super::{ActionId, MyApp};
+
+/// Model fitted to selection of function and the last fitted curve.
+pub(super) struct FitInput {
+    model: FitModel,
+    /// Index of function, revision of graph and result of the last fit
+    result: Option<(usize, u64, Result<Fit, FitError>)>,
+}
+
+impl Default for FitInput {
+    fn default() -> Self {
+        Self {
+            model: FitModel::Polynomial(1),
+            result: None,
+        }
+    }
+}
+
+impl MyApp {
+    pub(super) fn fit_controls(&mut self, ui: &mut Ui) {
+        ui.label("FIT");
+        ui.separator();
+        let model = &mut self.fit.model;
+        egui::ComboBox::from_label("Model")
+            .selected_text(model.label())
+            .show_ui(ui, |ui| {
+                for m in FitModel::ALL {
+                    if ui
+                        .selectable_label(m.label() == model.label(), m.label())
+                        .clicked()
+                        && m.label() != model.label()
+                    {
+                        *model = m;
+                    }
+                }
+            });
+        match model {
+            FitModel::Polynomial(degree) => {
+                ui.horizontal(|ui| {
+                    ui.label("Degree");
+                    ui.add(egui::DragValue::new(degree).range(0..=10));
+                });
+            }
+            FitModel::PiecewiseLinear(segments) => {
+                ui.horizontal(|ui| {
+                    ui.label("Segments");
+                    ui.add(egui::DragValue::new(segments).range(1..=100));
+                });
+            }
+            _ => {}
+        }
+        let revision = self.graph.graph().revision();
+        if matches!(self.fit.result, Some((_, r, _)) if r != revision) {
+            self.fit.result = None;
+        }
+        let focused = self.focused_func();
+        let fit = ui.add_enabled(focused.is_some(), egui::Button::new("Fit selection"));
+        if fit.clicked() {
+            self.fit_focused();
+        }
+        let (mut replace, mut clear) = (None, false);
+        match &self.fit.result {
+            Some((fi, _, Ok(fit))) => {
+                let name = self.legend_entries.get(*fi).map_or("", |e| &e.name);
+                ui.label(format!("{}: R² {:.4}", name, fit.stats.r_squared));
+                ui.label(format!(
+                    "RMSE {:.4}, max residual {:.4}",
+                    fit.stats.rmse, fit.stats.max_residual
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Replace selection").clicked() {
+                        replace = Some(*fi);
+                    }
+                    clear = ui.button("Clear").clicked();
+                });
+            }
+            Some((_, _, Err(e))) => {
+                ui.colored_label(Color32::LIGHT_RED, e.to_string());
+            }
+            None => {}
+        }
+        if clear {
+            self.fit.result = None;
+        } else if let Some(fi) = replace {
+            self.replace_with_fit(fi);
+        }
+        ui.separator();
+    }
+
+    /// Fits chosen model to selection of function hovered in legend or the first active one.
+    pub(super) fn fit_focused(&mut self) {
+        let graph = self.graph.graph();
+        if let Some(fi) = self.focused_func() {
+            if let Some(func) = graph.get_func(fi) {
+                let result = func.fit_selection(&self.fit.model);
+                self.fit.result = Some((fi, graph.revision(), result));
+            }
+        }
+    }
+
+    /// Replaces selected values of function at `index` by the fitted curve, as one history entry.
+    pub(super) fn replace_with_fit(&mut self, index: usize) {
+        let Some((_, _, Ok(fit))) = self.fit.result.take() else {
+            return;
+        };
+        let graph = self.graph.graph();
+        let Some(points) = graph.get_func(index).map(|f| f.points_selection()) else {
+            return;
+        };
+        let Some(x) = points.first().map(|p| p.x) else {
+            return;
+        };
+        let values = points.iter().map(|p| fit.curve.at(p.x)).collect::<Vec<_>>();
+        {
+            let mut b = self.graph.open_action(ActionId::Fit);
+            b.replace_values(index, x, values.len(), values);
+        }
+        self.graph.close_action(ActionId::Fit);
+    }
+
+    /// Curve fitted to selection of function, dashed in color of the function.
+    pub(super) fn fit_curve(
+        plot_ui: &mut PlotUi,
+        graph: &Graph,
+        fit: &FitInput,
+        entries: &[SimpleLegendEntry],
+    ) {
+        let Some((fi, _, Ok(fit))) = &fit.result else {
+            return;
+        };
+        let Some(func) = graph.get_func(*fi) else {
+            return;
+        };
+        let points = func
+            .points_selection()
+            .iter()
+            .map(|p| [p.x, fit.curve.at(p.x)])
+            .collect::<Vec<_>>();
+        let color = entries.get(*fi).map_or(Color32::GRAY, |e| e.color);
+        plot_ui.line(
+            Line::new(PlotPoints::new(points))
+                .color(color)
+                .style(LineStyle::dashed_loose())
+                .allow_hover(false),
+        );
+    }
+}
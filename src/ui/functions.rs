@@ -0,0 +1,166 @@
+use eframe::egui::{self, Ui};
+
+use crate::{
+    functions::function::InsertPolicy,
+    types::point::Y,
+    widgets::legend_widget::{
+        simple_legend_entries::{SimpleLegendEntries, SimpleLegendEntry},
+        LegendEntries,
+    },
+};
+
+use super::{utils, ActionId, MyApp};
+
+impl MyApp {
+    pub(super) fn insert_controls(&mut self, ui: &mut Ui) {
+        ui.label("INSERT");
+        ui.separator();
+        egui::ComboBox::from_label("Policy")
+            .selected_text(format!("{:?}", self.insert_policy))
+            .show_ui(ui, |ui| {
+                for (policy, text) in [
+                    (InsertPolicy::Shift, "Shift later data"),
+                    (InsertPolicy::Overwrite, "Overwrite in place"),
+                    (InsertPolicy::Stretch, "Stretch into selection"),
+                ] {
+                    ui.selectable_value(&mut self.insert_policy, policy, text);
+                }
+            });
+        let graph = self.graph.graph();
+        let at = *graph.selection().start();
+        let count = graph.selection_len();
+        if let Some(time_ctx) = &self.plot.time_ctx {
+            ui.label(format!("At {}", time_ctx.label(at)));
+            if self.insert_policy == InsertPolicy::Shift && count > 0 {
+                ui.label(format!(
+                    "Later data moves by {}",
+                    time_ctx.insert_shift(count)
+                ));
+            }
+        }
+        if ui
+            .add_enabled(count > 0, egui::Button::new("Duplicate selection"))
+            .clicked()
+        {
+            self.duplicate_selection();
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.pattern).desired_width(80.0));
+            let pattern = self.parse_pattern();
+            if ui
+                .add_enabled(pattern.is_some(), egui::Button::new("Insert pattern"))
+                .on_hover_text("Comma separated values inserted into every editable function")
+                .clicked()
+            {
+                self.insert_pattern();
+            }
+        });
+        ui.separator();
+    }
+
+    /// Values of pattern, `None` if it is empty or any value is not a number.
+    fn parse_pattern(&self) -> Option<Vec<Y>> {
+        let values = self
+            .pattern
+            .split(',')
+            .map(|v| v.trim().parse::<Y>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        (!values.is_empty()).then_some(values)
+    }
+
+    /// Inserts pattern at start of selection into every editable function.
+    pub(super) fn insert_pattern(&mut self) {
+        if let Some(pattern) = self.parse_pattern() {
+            let at = *self.graph.graph().selection().start();
+            self.graph.action(ActionId::InsertPattern).insert_pattern(
+                at,
+                pattern,
+                self.insert_policy,
+            );
+        }
+    }
+
+    /// Inserts copy of selected values at start of selection.
+    pub(super) fn duplicate_selection(&mut self) {
+        let graph = self.graph.graph();
+        let at = *graph.selection().start();
+        let values = graph
+            .selection_points()
+            .map(|values| values.collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        if values.iter().any(|v| !v.is_empty()) {
+            self.graph.action(ActionId::InsertValues).insert_values(
+                at,
+                values.iter().map(|v| v.iter().copied()),
+                self.insert_policy,
+            );
+        }
+    }
+
+    pub(super) fn functions_controls(&mut self, ui: &mut Ui) {
+        ui.label("FUNCTIONS");
+        ui.separator();
+        let graph = self.graph.graph();
+        let range = graph.x_range().unwrap_or_else(|| graph.selection().clone());
+        let mut add = None;
+        if ui.button("New empty function").clicked() {
+            add = Some(Vec::new());
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut self.constant_value).speed(0.1));
+            if ui.button("New constant function").clicked() {
+                let len = (range.end() - range.start()) as usize + 1;
+                add = Some(vec![self.constant_value; len]);
+            }
+        });
+        if let Some(values) = add {
+            self.new_function(values);
+        }
+        ui.label("Duplicate or delete function from its legend context menu.");
+        ui.separator();
+    }
+
+    /// Adds function starting at start of graph.
+    pub(super) fn new_function(&mut self, values: Vec<Y>) {
+        let graph = self.graph.graph();
+        let range = graph.x_range().unwrap_or_else(|| graph.selection().clone());
+        let style = SimpleLegendEntry::new(
+            format!("Function {}", graph.len() + 1),
+            utils::auto_color(graph.next_func_id()),
+            false,
+        )
+        .style();
+        SimpleLegendEntries::new(
+            &mut self.graph,
+            &mut self.legend_entries,
+            &mut self.legend_order,
+        )
+        .add_func(style, *range.start(), values);
+    }
+
+    /// Locks or unlocks function hovered in legend or the first active function.
+    pub(super) fn toggle_lock_focused(&mut self) {
+        if let Some(fi) = self.focused_func() {
+            let mut entries = SimpleLegendEntries::new(
+                &mut self.graph,
+                &mut self.legend_entries,
+                &mut self.legend_order,
+            );
+            if let Some(locked) = entries.get_locked(fi) {
+                entries.set_locked(fi, !locked);
+            }
+        }
+    }
+
+    /// Deletes function hovered in legend or the first active function.
+    pub(super) fn delete_focused(&mut self) {
+        if let Some(fi) = self.focused_func() {
+            SimpleLegendEntries::new(
+                &mut self.graph,
+                &mut self.legend_entries,
+                &mut self.legend_order,
+            )
+            .remove(fi);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::ops::Range;
+
+use eframe::egui::{self, Color32, Ui};
+use egui_plot::{PlotUi, Polygon};
+
+use crate::{
+    date_time_ctx::CalendarUnit, functions::function::gap_fill::GapFill, graph::Graph,
+    types::point::X, widgets::legend_widget::simple_legend_entries::SimpleLegendEntry,
+};
+
+use super::{ActionId, MyApp};
+
+impl MyApp {
+    /// Gaps of editable functions, which intersect their selection.
+    pub(super) fn selected_gaps(&self) -> Vec<(usize, Range<X>)> {
+        let graph = self.graph.graph();
+        graph
+            .active_func_indexes()
+            .filter(|fi| graph.is_locked(*fi) == Some(false))
+            .flat_map(|fi| {
+                let selection = graph.func_selection(fi).unwrap_or(graph.selection());
+                graph.get_func(fi).into_iter().flat_map(move |f| {
+                    f.gaps()
+                        .iter()
+                        .filter(|g| g.start < *selection.end() && g.end > *selection.start())
+                        .map(move |g| (fi, g.clone()))
+                })
+            })
+            .collect()
+    }
+
+    pub(super) fn gap_controls(&mut self, ui: &mut Ui) {
+        ui.label("GAPS");
+        ui.separator();
+        let day = self
+            .plot
+            .time_ctx
+            .as_ref()
+            .map(|ctx| ctx.shift(0.0, CalendarUnit::Day, 1));
+        let mut fills = vec![
+            ("Leave empty", GapFill::Empty),
+            ("Linear", GapFill::Linear),
+            ("Previous value", GapFill::Previous),
+        ];
+        if let Some(day) = day {
+            fills.extend([
+                ("Previous day", GapFill::PreviousPeriod(day)),
+                ("Previous week", GapFill::PreviousPeriod(7.0 * day)),
+                ("Daily average", GapFill::Seasonal(day)),
+            ]);
+        }
+        let selected = fills
+            .iter()
+            .find(|(_, fill)| *fill == self.gap_fill)
+            .map_or(self.gap_fill.label(), |(label, _)| label);
+        egui::ComboBox::from_label("Fill")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for (label, fill) in fills {
+                    ui.selectable_value(&mut self.gap_fill, fill, label);
+                }
+            });
+        let gaps = self.selected_gaps();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} gaps in selection", gaps.len()));
+            let fill = ui.add_enabled(!gaps.is_empty(), egui::Button::new("Fill"));
+            if fill.clicked() {
+                self.fill_gaps(&gaps);
+            }
+        });
+        ui.separator();
+    }
+
+    /// Replaces values inside `gaps` by values of chosen strategy, as one history entry.
+    pub(super) fn fill_gaps(&mut self, gaps: &[(usize, Range<X>)]) {
+        let fills = gaps
+            .iter()
+            .filter_map(|(fi, gap)| {
+                let func = self.graph.graph().get_func(*fi)?;
+                let (x, values) = self.gap_fill.values(func, gap)?;
+                Some((*fi, x, values))
+            })
+            .collect::<Vec<_>>();
+        {
+            let mut b = self.graph.open_action(ActionId::FillGaps);
+            for (fi, x, values) in fills {
+                b.replace_values(fi, x, values.len(), values);
+            }
+        }
+        self.graph.close_action(ActionId::FillGaps);
+    }
+
+    /// Shades ranges, where active functions have no samples, in lighter color of their
+    /// legend entries.
+    pub(super) fn gaps(plot_ui: &mut PlotUi, graph: &Graph, entries: &[SimpleLegendEntry]) {
+        let bounds = plot_ui.plot_bounds();
+        let (bottom, top) = (bounds.min()[1], bounds.max()[1]);
+        for (func, index) in graph.active_functions_index() {
+            let color = entries.get(index).map_or(Color32::GRAY, |e| e.color);
+            for gap in func.gaps() {
+                plot_ui.polygon(
+                    Polygon::new(vec![
+                        [gap.start, bottom],
+                        [gap.end, bottom],
+                        [gap.end, top],
+                        [gap.start, top],
+                    ])
+                    .fill_color(color.gamma_multiply(0.1))
+                    .stroke((0.0, color))
+                    .allow_hover(false),
+                );
+            }
+        }
+    }
+}
@@ -1,20 +1,26 @@
+pub mod legend_change;
 pub mod plot_bounds_change;
+pub mod resample_change;
 
 use std::{
     collections::{vec_deque, VecDeque},
     fmt::Debug,
 };
 
-use plot_bounds_change::{change::PlotBoundsChange, PlotBoundsChangeOp};
+use plot_bounds_change::change::PlotBoundsChange;
+use resample_change::ResampleChange;
 
 use crate::{
     history::history_stack::shared_entry::{ApplyOtherOp, OtherOp, OwnedOp},
     utils::Change,
+    widgets::legend_widget::simple_legend_entries::LegendChange,
 };
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum OwnedHistoryOp {
     ChangePlotBounds(PlotBoundsChange),
+    ChangeLegend(LegendChange),
+    Resample(ResampleChange),
 }
 
 impl OtherOp for OwnedHistoryOp {}
@@ -23,6 +29,8 @@ impl OwnedOp<SharedHistoryOp> for OwnedHistoryOp {
     fn get_shared(&self) -> SharedHistoryOp {
         match self {
             Self::ChangePlotBounds(u) => SharedHistoryOp::ChangePlotBounds(u.clone()),
+            Self::ChangeLegend(c) => SharedHistoryOp::ChangeLegend(c.clone()),
+            Self::Resample(c) => SharedHistoryOp::Resample(c.clone()),
         }
     }
 }
@@ -30,11 +38,32 @@ impl OwnedOp<SharedHistoryOp> for OwnedHistoryOp {
 #[derive(Debug, Clone, PartialEq)]
 pub enum SharedHistoryOp {
     ChangePlotBounds(PlotBoundsChange),
+    ChangeLegend(LegendChange),
+    Resample(ResampleChange),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum SharedDataOp<'a> {
     ChangePlotBounds(ApplyDataOp<&'a PlotBoundsChange>),
+    ChangeLegend(ApplyDataOp<&'a LegendChange>),
+    Resample(ApplyDataOp<&'a ResampleChange>),
+}
+
+impl<'a> SharedDataOp<'a> {
+    pub fn new(op: &'a ApplyOtherOp<SharedHistoryOp>) -> Self {
+        let shared = match op {
+            ApplyOtherOp::Undo(shared) | ApplyOtherOp::Redo(shared) => shared,
+        };
+        match shared {
+            SharedHistoryOp::ChangePlotBounds(change) => {
+                Self::ChangePlotBounds(ApplyDataOp::new(op, |_| change))
+            }
+            SharedHistoryOp::ChangeLegend(change) => {
+                Self::ChangeLegend(ApplyDataOp::new(op, |_| change))
+            }
+            SharedHistoryOp::Resample(change) => Self::Resample(ApplyDataOp::new(op, |_| change)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
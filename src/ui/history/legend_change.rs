@@ -0,0 +1,39 @@
+use crate::{
+    history::history_stack::shared_entry::{InOp, OpCombineErr, OpCreateErr},
+    ui::ActionId,
+    widgets::legend_widget::simple_legend_entries::{LegendChange, LegendChangeOp},
+};
+
+use super::OwnedHistoryOp;
+
+impl TryInto<OwnedHistoryOp> for LegendChangeOp {
+    type Error = OpCreateErr;
+
+    fn try_into(self) -> Result<OwnedHistoryOp, Self::Error> {
+        match &self.0 {
+            LegendChange::Style(_, old, new) if old == new => Err(OpCreateErr::OpDoesNotHaveEffect),
+            LegendChange::Move(from, to) if from == to => Err(OpCreateErr::OpDoesNotHaveEffect),
+            _ => Ok(OwnedHistoryOp::ChangeLegend(self.0)),
+        }
+    }
+}
+
+impl InOp<OwnedHistoryOp, ActionId> for LegendChangeOp {
+    fn try_combine(self, owned: &mut OwnedHistoryOp) -> Result<(), OpCombineErr<Self>> {
+        match (owned, &self.0) {
+            // Editing style of the same entry is combined into one change.
+            (
+                OwnedHistoryOp::ChangeLegend(LegendChange::Style(index, _, new)),
+                LegendChange::Style(other_index, _, other_new),
+            ) if index == other_index => {
+                *new = other_new.clone();
+                Ok(())
+            }
+            _ => Err(OpCombineErr::CannotCombine(self)),
+        }
+    }
+
+    fn alters_history(&self, _id: &ActionId) -> bool {
+        true
+    }
+}
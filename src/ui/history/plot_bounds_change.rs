@@ -1,7 +1,5 @@
 pub mod change;
 
-use std::{collections::VecDeque, ops::Add};
-
 use change::PlotBoundsChange;
 use egui_plot::PlotBounds;
 
@@ -19,14 +17,14 @@ impl Changeable<PlotBoundsChange> for PlotBounds {
     }
 
     fn change(&mut self, change: &PlotBoundsChange) {
-        *self = change.undo_from(&self);
+        *self = change.undo_from(self);
     }
 
     fn apply_change(&mut self, change: ApplyDataOp<&PlotBoundsChange>) {
         match change {
             ApplyDataOp::Undo(change) => self.change(change),
             ApplyDataOp::Redo(change) => {
-                *self = change.redo_from(&self);
+                *self = change.redo_from(self);
             }
         }
     }
@@ -34,11 +32,21 @@ impl Changeable<PlotBoundsChange> for PlotBounds {
 
 pub struct PlotBoundsChangeOp<'b> {
     change: &'b PlotBoundsChange,
+    coalesced: bool,
 }
 
 impl<'b> PlotBoundsChangeOp<'b> {
     pub fn new(change: &'b PlotBoundsChange) -> Self {
-        Self { change }
+        Self {
+            change,
+            coalesced: false,
+        }
+    }
+
+    /// Change is undone together with the next edit, see `InOp::coalesces`.
+    pub fn coalesced(mut self, coalesced: bool) -> Self {
+        self.coalesced = coalesced;
+        self
     }
 
     pub fn has_effect(&self) -> bool {
@@ -69,10 +77,15 @@ impl<'b> InOp<OwnedHistoryOp, ActionId> for PlotBoundsChangeOp<'b> {
                     Err(OpCombineErr::OpDoesNotHaveEffect)
                 }
             }
+            _ => Err(OpCombineErr::CannotCombine(self)),
         }
     }
 
     fn alters_history(&self, id: &ActionId) -> bool {
         *id != ActionId::Conditions
     }
+
+    fn coalesces(&self, _id: &ActionId) -> bool {
+        self.coalesced
+    }
 }
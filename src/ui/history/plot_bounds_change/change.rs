@@ -36,10 +36,6 @@ impl PlotBoundsChange {
         a[0].abs() < f64::EPSILON && a[1].abs() < f64::EPSILON
     }
 
-    fn not_f64x2(a: &[f64; 2]) -> [f64; 2] {
-        [-a[0], -a[1]]
-    }
-
     fn not_assign_f64x2(a: &mut [f64; 2]) {
         a[0] = -a[0];
         a[1] = -a[1];
@@ -57,11 +53,6 @@ impl PlotBoundsChange {
     fn subf64x2(a: &[f64; 2], b: &[f64; 2]) -> [f64; 2] {
         [a[0] - b[0], a[1] - b[1]]
     }
-
-    fn subf64x2_assign(a: &mut [f64; 2], b: &[f64; 2]) {
-        a[0] -= b[0];
-        a[1] -= b[1];
-    }
 }
 
 impl Change for PlotBoundsChange {
@@ -0,0 +1,70 @@
+use crate::{
+    functions::function::resample::Resample,
+    history::history_stack::shared_entry::{InOp, OpCombineErr, OpCreateErr},
+    types::{datetime_f64_mapper::DateTimeF64Mapper, point::X},
+    ui::ActionId,
+};
+
+use super::{ApplyDataOp, OwnedHistoryOp};
+
+/// Change of x by resampling of functions and of mapping between x and time.
+/// Functions themselves are changed by graph ops of the same entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResampleChange {
+    /// New interval in units of old x
+    pub step: X,
+    /// Old x of new x zero
+    pub offset: X,
+    /// Old and new mapping between x and time
+    pub mappers: Option<(DateTimeF64Mapper, DateTimeF64Mapper)>,
+}
+
+impl ResampleChange {
+    pub fn new(
+        resample: &Resample,
+        mappers: Option<(DateTimeF64Mapper, DateTimeF64Mapper)>,
+    ) -> Self {
+        Self {
+            step: resample.step,
+            offset: resample.offset,
+            mappers,
+        }
+    }
+
+    /// X after applying `change`, which was x before it.
+    pub fn map_x(change: &ApplyDataOp<&Self>, x: X) -> X {
+        match change {
+            ApplyDataOp::Undo(change) => x * change.step + change.offset,
+            ApplyDataOp::Redo(change) => (x - change.offset) / change.step,
+        }
+    }
+
+    /// Mapping between x and time after applying `change`.
+    pub fn mapper<'a>(change: &ApplyDataOp<&'a Self>) -> Option<&'a DateTimeF64Mapper> {
+        match change {
+            ApplyDataOp::Undo(change) => change.mappers.as_ref().map(|(old, _)| old),
+            ApplyDataOp::Redo(change) => change.mappers.as_ref().map(|(_, new)| new),
+        }
+    }
+}
+
+/// Change of x by resampling, which is recorded into history.
+pub struct ResampleChangeOp(pub ResampleChange);
+
+impl TryInto<OwnedHistoryOp> for ResampleChangeOp {
+    type Error = OpCreateErr;
+
+    fn try_into(self) -> Result<OwnedHistoryOp, Self::Error> {
+        Ok(OwnedHistoryOp::Resample(self.0))
+    }
+}
+
+impl InOp<OwnedHistoryOp, ActionId> for ResampleChangeOp {
+    fn try_combine(self, _owned: &mut OwnedHistoryOp) -> Result<(), OpCombineErr<Self>> {
+        Err(OpCombineErr::CannotCombine(self))
+    }
+
+    fn alters_history(&self, _id: &ActionId) -> bool {
+        true
+    }
+}
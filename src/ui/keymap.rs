@@ -0,0 +1,369 @@
+use std::{fmt, fs, io, path::Path};
+
+use eframe::egui::{InputState, Key, KeyboardShortcut, Modifiers};
+
+/// Action, which can be bound to a shortcut and run from command palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Undo,
+    Redo,
+    DeleteSelection,
+    SelectAll,
+    /// Moves selection by one sample
+    NudgeLeft,
+    NudgeRight,
+    /// Moves selection by one calendar unit
+    NudgeUnitLeft,
+    NudgeUnitRight,
+    /// Returns to previous view
+    Back,
+    Forward,
+    ZoomToSelection,
+    ZoomToFit,
+    /// Shows whole function hovered in legend or the first active function
+    ZoomToFunction,
+    ZoomIn,
+    ZoomOut,
+    ClearSelectionRanges,
+    DuplicateSelection,
+    NewEmptyFunction,
+    ToggleDiffView,
+    ReplayLastMacro,
+    /// Stretches selection by chosen profile and baseline
+    StretchUp,
+    StretchDown,
+    InsertPattern,
+    RecordMacro,
+    FillGaps,
+    DetectOutliers,
+    NextOutlier,
+    PreviousOutlier,
+    Resample,
+    Transform,
+    InverseTransform,
+    /// Fits selection of function hovered in legend or the first active function
+    Fit,
+    Filter,
+    Export,
+    ToggleLockFunction,
+    DeleteFunction,
+    CommandPalette,
+}
+
+impl Command {
+    pub const ALL: [Self; 37] = [
+        Self::Undo,
+        Self::Redo,
+        Self::DeleteSelection,
+        Self::SelectAll,
+        Self::NudgeLeft,
+        Self::NudgeRight,
+        Self::NudgeUnitLeft,
+        Self::NudgeUnitRight,
+        Self::Back,
+        Self::Forward,
+        Self::ZoomToSelection,
+        Self::ZoomToFit,
+        Self::ZoomToFunction,
+        Self::ZoomIn,
+        Self::ZoomOut,
+        Self::ClearSelectionRanges,
+        Self::DuplicateSelection,
+        Self::NewEmptyFunction,
+        Self::ToggleDiffView,
+        Self::ReplayLastMacro,
+        Self::StretchUp,
+        Self::StretchDown,
+        Self::InsertPattern,
+        Self::RecordMacro,
+        Self::FillGaps,
+        Self::DetectOutliers,
+        Self::NextOutlier,
+        Self::PreviousOutlier,
+        Self::Resample,
+        Self::Transform,
+        Self::InverseTransform,
+        Self::Fit,
+        Self::Filter,
+        Self::Export,
+        Self::ToggleLockFunction,
+        Self::DeleteFunction,
+        Self::CommandPalette,
+    ];
+
+    /// Name of command in keymap file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+            Self::DeleteSelection => "delete_selection",
+            Self::SelectAll => "select_all",
+            Self::NudgeLeft => "nudge_left",
+            Self::NudgeRight => "nudge_right",
+            Self::NudgeUnitLeft => "nudge_unit_left",
+            Self::NudgeUnitRight => "nudge_unit_right",
+            Self::Back => "back",
+            Self::Forward => "forward",
+            Self::ZoomToSelection => "zoom_to_selection",
+            Self::ZoomToFit => "zoom_to_fit",
+            Self::ZoomToFunction => "zoom_to_function",
+            Self::ZoomIn => "zoom_in",
+            Self::ZoomOut => "zoom_out",
+            Self::ClearSelectionRanges => "clear_selection_ranges",
+            Self::DuplicateSelection => "duplicate_selection",
+            Self::NewEmptyFunction => "new_empty_function",
+            Self::ToggleDiffView => "toggle_diff_view",
+            Self::ReplayLastMacro => "replay_last_macro",
+            Self::StretchUp => "stretch_up",
+            Self::StretchDown => "stretch_down",
+            Self::InsertPattern => "insert_pattern",
+            Self::RecordMacro => "record_macro",
+            Self::FillGaps => "fill_gaps",
+            Self::DetectOutliers => "detect_outliers",
+            Self::NextOutlier => "next_outlier",
+            Self::PreviousOutlier => "previous_outlier",
+            Self::Resample => "resample",
+            Self::Transform => "transform",
+            Self::InverseTransform => "inverse_transform",
+            Self::Fit => "fit",
+            Self::Filter => "filter",
+            Self::Export => "export",
+            Self::ToggleLockFunction => "toggle_lock_function",
+            Self::DeleteFunction => "delete_function",
+            Self::CommandPalette => "command_palette",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Undo => "Undo",
+            Self::Redo => "Redo",
+            Self::DeleteSelection => "Delete selection",
+            Self::SelectAll => "Select all",
+            Self::NudgeLeft => "Move selection left by one sample",
+            Self::NudgeRight => "Move selection right by one sample",
+            Self::NudgeUnitLeft => "Move selection left by one calendar unit",
+            Self::NudgeUnitRight => "Move selection right by one calendar unit",
+            Self::Back => "Back to previous view",
+            Self::Forward => "Forward to next view",
+            Self::ZoomToSelection => "Zoom to selection",
+            Self::ZoomToFit => "Zoom to fit all",
+            Self::ZoomToFunction => "Zoom to function",
+            Self::ZoomIn => "Zoom in",
+            Self::ZoomOut => "Zoom out",
+            Self::ClearSelectionRanges => "Clear additional selection ranges",
+            Self::DuplicateSelection => "Duplicate selection",
+            Self::NewEmptyFunction => "New empty function",
+            Self::ToggleDiffView => "Show or hide differences to original",
+            Self::ReplayLastMacro => "Replay last macro",
+            Self::StretchUp => "Stretch selection up",
+            Self::StretchDown => "Stretch selection down",
+            Self::InsertPattern => "Insert pattern",
+            Self::RecordMacro => "Record macro",
+            Self::FillGaps => "Fill gaps in selection",
+            Self::DetectOutliers => "Detect outliers in selection",
+            Self::NextOutlier => "Show next outlier",
+            Self::PreviousOutlier => "Show previous outlier",
+            Self::Resample => "Resample all functions",
+            Self::Transform => "Apply derivative or integral in place",
+            Self::InverseTransform => "Inverse last transform in place",
+            Self::Fit => "Fit selection",
+            Self::Filter => "Apply filter to selection",
+            Self::Export => "Export active functions",
+            Self::ToggleLockFunction => "Lock or unlock function",
+            Self::DeleteFunction => "Delete function",
+            Self::CommandPalette => "Command palette",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.name() == name)
+    }
+}
+
+#[derive(Debug)]
+pub enum KeymapError {
+    Io(io::Error),
+    /// Line number and the line, which is not `command = shortcut`
+    InvalidLine(usize, String),
+    /// Line number and name of command
+    UnknownCommand(usize, String),
+    /// Line number and shortcut
+    InvalidShortcut(usize, String),
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "can not read keymap: {}", e),
+            Self::InvalidLine(line, text) => {
+                write!(
+                    f,
+                    "line {}: expected 'command = shortcut', got '{}'",
+                    line, text
+                )
+            }
+            Self::UnknownCommand(line, name) => {
+                write!(f, "line {}: unknown command '{}'", line, name)
+            }
+            Self::InvalidShortcut(line, text) => {
+                write!(f, "line {}: invalid shortcut '{}'", line, text)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for KeymapError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Shortcuts of commands.
+pub struct Keymap {
+    /// Sorted, so shortcuts with more modifiers are matched first
+    bindings: Vec<(KeyboardShortcut, Command)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let ctrl = Modifiers::COMMAND;
+        let shift = Modifiers::SHIFT;
+        let alt = Modifiers::ALT;
+        let none = Modifiers::NONE;
+        let bind = |modifiers, key, command| (KeyboardShortcut::new(modifiers, key), command);
+        Self::new(vec![
+            bind(ctrl, Key::Z, Command::Undo),
+            bind(ctrl | shift, Key::Z, Command::Redo),
+            bind(ctrl, Key::Y, Command::Redo),
+            bind(none, Key::Delete, Command::DeleteSelection),
+            bind(ctrl, Key::A, Command::SelectAll),
+            bind(none, Key::ArrowLeft, Command::NudgeLeft),
+            bind(none, Key::ArrowRight, Command::NudgeRight),
+            bind(shift, Key::ArrowLeft, Command::NudgeUnitLeft),
+            bind(shift, Key::ArrowRight, Command::NudgeUnitRight),
+            bind(alt, Key::ArrowLeft, Command::Back),
+            bind(alt, Key::ArrowRight, Command::Forward),
+            bind(ctrl, Key::E, Command::ZoomToSelection),
+            bind(ctrl, Key::Num0, Command::ZoomToFit),
+            bind(ctrl, Key::F, Command::ZoomToFunction),
+            bind(none, Key::Plus, Command::ZoomIn),
+            bind(none, Key::Equals, Command::ZoomIn),
+            bind(none, Key::Minus, Command::ZoomOut),
+            bind(none, Key::Escape, Command::ClearSelectionRanges),
+            bind(ctrl, Key::D, Command::DuplicateSelection),
+            bind(ctrl, Key::N, Command::NewEmptyFunction),
+            bind(none, Key::F2, Command::ToggleDiffView),
+            bind(ctrl, Key::M, Command::ReplayLastMacro),
+            bind(ctrl, Key::ArrowUp, Command::StretchUp),
+            bind(ctrl, Key::ArrowDown, Command::StretchDown),
+            bind(ctrl, Key::I, Command::InsertPattern),
+            bind(ctrl | shift, Key::M, Command::RecordMacro),
+            bind(ctrl, Key::G, Command::FillGaps),
+            bind(ctrl, Key::O, Command::DetectOutliers),
+            bind(alt, Key::ArrowDown, Command::NextOutlier),
+            bind(alt, Key::ArrowUp, Command::PreviousOutlier),
+            bind(ctrl, Key::R, Command::Resample),
+            bind(ctrl, Key::T, Command::Transform),
+            bind(ctrl | shift, Key::T, Command::InverseTransform),
+            bind(ctrl | shift, Key::F, Command::Fit),
+            bind(ctrl, Key::K, Command::Filter),
+            bind(ctrl, Key::S, Command::Export),
+            bind(ctrl, Key::L, Command::ToggleLockFunction),
+            bind(shift, Key::Delete, Command::DeleteFunction),
+            bind(ctrl, Key::P, Command::CommandPalette),
+            bind(none, Key::F1, Command::CommandPalette),
+        ])
+    }
+}
+
+impl Keymap {
+    fn new(mut bindings: Vec<(KeyboardShortcut, Command)>) -> Self {
+        bindings.sort_by_key(|(s, _)| {
+            let m = s.modifiers;
+            std::cmp::Reverse(m.alt as u8 + m.ctrl as u8 + m.shift as u8 + m.command as u8)
+        });
+        Self { bindings }
+    }
+
+    /// Parses shortcut like `Ctrl+Shift+Z`, `Ctrl` is `Cmd` on Mac. Plus key is written
+    /// as `+` or `Plus`.
+    pub fn parse_shortcut(text: &str) -> Option<KeyboardShortcut> {
+        let text = text.trim();
+        let (modifier_names, key) = match text.strip_suffix('+') {
+            Some(rest) if rest.is_empty() || rest.ends_with('+') => {
+                (rest.strip_suffix('+').unwrap_or(rest), "+")
+            }
+            _ => text.rsplit_once('+').unwrap_or(("", text)),
+        };
+        let mut modifiers = Modifiers::NONE;
+        for name in modifier_names.split('+').filter(|n| !n.trim().is_empty()) {
+            modifiers = modifiers
+                | match name.trim().to_ascii_lowercase().as_str() {
+                    "ctrl" | "cmd" | "command" => Modifiers::COMMAND,
+                    "shift" => Modifiers::SHIFT,
+                    "alt" | "option" => Modifiers::ALT,
+                    _ => return None,
+                };
+        }
+        let key = key.trim();
+        let mut chars = key.chars();
+        let capitalized = chars
+            .next()
+            .map_or(String::new(), |c| c.to_uppercase().chain(chars).collect());
+        let key = Key::from_name(key)
+            .or_else(|| Key::from_name(&capitalized))
+            .or_else(|| Key::from_name(&key.to_ascii_uppercase()))?;
+        Some(KeyboardShortcut::new(modifiers, key))
+    }
+
+    /// Default keymap changed by lines `command = shortcut, shortcut`. Commands listed in
+    /// `text` lose their default shortcuts, so `command =` unbinds command.
+    /// Empty lines and lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Self, KeymapError> {
+        let mut bindings = Self::default().bindings;
+        let mut replaced = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, shortcuts) = line
+                .split_once('=')
+                .ok_or_else(|| KeymapError::InvalidLine(i + 1, line.to_string()))?;
+            let name = name.trim();
+            let command = Command::from_name(name)
+                .ok_or_else(|| KeymapError::UnknownCommand(i + 1, name.to_string()))?;
+            if !replaced.contains(&command) {
+                replaced.push(command);
+                bindings.retain(|(_, c)| *c != command);
+            }
+            for shortcut in shortcuts.split(',').filter(|s| !s.trim().is_empty()) {
+                let shortcut = Self::parse_shortcut(shortcut)
+                    .ok_or_else(|| KeymapError::InvalidShortcut(i + 1, shortcut.to_string()))?;
+                bindings.push((shortcut, command));
+            }
+        }
+        Ok(Self::new(bindings))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, KeymapError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn shortcuts(&self, command: Command) -> impl Iterator<Item = &KeyboardShortcut> {
+        self.bindings
+            .iter()
+            .filter(move |(_, c)| *c == command)
+            .map(|(s, _)| s)
+    }
+
+    /// Commands, whose shortcuts were pressed. Pressed keys are consumed.
+    pub fn pressed(&self, input: &mut InputState) -> Vec<Command> {
+        self.bindings
+            .iter()
+            .filter(|(shortcut, _)| input.consume_shortcut(shortcut))
+            .map(|(_, command)| *command)
+            .collect()
+    }
+}
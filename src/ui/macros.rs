@@ -0,0 +1,74 @@
+use eframe::egui::{self, Ui};
+
+use super::{history, ActionId, MyApp};
+
+impl MyApp {
+    pub(super) fn macro_controls(&mut self, ui: &mut Ui) {
+        ui.label("MACROS");
+        ui.separator();
+        let labels = self
+            .graph
+            .history()
+            .undo_iter()
+            .enumerate()
+            .map(|(i, entry)| format!("{}. {:?}", i + 1, entry.id()))
+            .collect::<Vec<_>>();
+        let last = labels.len().saturating_sub(1);
+        let (newest, oldest) = &mut self.macro_entries;
+        *oldest = (*oldest).min(last);
+        *newest = (*newest).min(*oldest);
+        let entry_combo = |ui: &mut Ui, label: &str, index: &mut usize| {
+            egui::ComboBox::from_label(label)
+                .selected_text(labels.get(*index).map_or("", |l| l.as_str()))
+                .show_ui(ui, |ui| {
+                    for (i, l) in labels.iter().enumerate() {
+                        ui.selectable_value(index, i, l);
+                    }
+                });
+        };
+        entry_combo(ui, "From newest", newest);
+        entry_combo(ui, "To oldest", oldest);
+        if *newest > *oldest {
+            *oldest = *newest;
+        }
+        if ui
+            .add_enabled(!labels.is_empty(), egui::Button::new("Record"))
+            .clicked()
+        {
+            self.record_macro();
+        }
+        let mut remove = None;
+        for (i, graph_macro) in self.macros.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{} ({})",
+                    graph_macro.name,
+                    graph_macro.steps().len()
+                ));
+                if ui.button("Replay").clicked() {
+                    self.graph.replay_macro(ActionId::Macro, graph_macro);
+                }
+                if ui.button("Remove").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            self.macros.remove(i);
+        }
+        ui.separator();
+    }
+
+    /// Records chosen undo entries as new macro, nothing is recorded without entries.
+    pub(super) fn record_macro(&mut self) {
+        let (newest, oldest) = self.macro_entries;
+        let oldest = oldest.min(self.graph.history().undo_len().saturating_sub(1));
+        let name = format!("Macro {}", self.macros.len() + 1);
+        let graph_macro = self
+            .graph
+            .record_macro::<history::SharedHistoryOp>(name, newest.min(oldest)..oldest + 1);
+        if !graph_macro.is_empty() {
+            self.macros.push(graph_macro);
+        }
+    }
+}
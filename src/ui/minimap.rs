@@ -0,0 +1,77 @@
+use std::ops::RangeInclusive;
+
+use eframe::egui::{Color32, Pos2, Rect, Sense, Shape, Stroke, Ui, Vec2};
+use egui_plot::PlotBounds;
+
+use crate::{
+    functions::function::Func,
+    types::point::{X, Y},
+};
+
+/// Strip with whole x range of graph, current viewport and selection.
+/// Returns x, at which strip was clicked or dragged.
+pub fn minimap<'a>(
+    ui: &mut Ui,
+    height: f32,
+    x_range: &RangeInclusive<X>,
+    lines: impl Iterator<Item = (&'a Func, Color32)>,
+    viewport: &PlotBounds,
+    selection: &RangeInclusive<X>,
+) -> Option<X> {
+    let (response, painter) = ui.allocate_painter(
+        Vec2::new(ui.available_width(), height),
+        Sense::click_and_drag(),
+    );
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let start = *x_range.start();
+    let width = (x_range.end() - start).max(X::EPSILON);
+    let screen_x = |x: X| rect.left() + ((x - start) / width) as f32 * rect.width();
+    let lines = lines.collect::<Vec<_>>();
+    let (min, max) = lines
+        .iter()
+        .flat_map(|(f, _)| f.points().iter().map(|p| p.y))
+        .filter(|y| y.is_finite())
+        .fold((Y::INFINITY, Y::NEG_INFINITY), |(min, max), y| {
+            (min.min(y), max.max(y))
+        });
+    let height = (max - min).max(Y::EPSILON);
+    let screen_y = |y: Y| rect.bottom() - ((y - min) / height) as f32 * rect.height();
+    for (func, color) in lines {
+        // About one point for each pixel is enough for overview.
+        let step = (func.points().len() / rect.width().max(1.0) as usize).max(1);
+        let points = func
+            .points()
+            .iter()
+            .step_by(step)
+            .filter(|p| p.y.is_finite())
+            .map(|p| Pos2::new(screen_x(p.x), screen_y(p.y)))
+            .collect::<Vec<_>>();
+        painter.add(Shape::line(points, Stroke::new(1.0, color)));
+    }
+
+    let x_span = |range: &RangeInclusive<X>| {
+        Rect::from_x_y_ranges(
+            screen_x(*range.start()).max(rect.left())..=screen_x(*range.end()).min(rect.right()),
+            rect.y_range(),
+        )
+    };
+    painter.rect_filled(
+        x_span(selection),
+        0.0,
+        Color32::LIGHT_RED.gamma_multiply(0.25),
+    );
+    painter.rect_stroke(
+        x_span(&viewport.range_x()),
+        0.0,
+        Stroke::new(1.5, ui.visuals().strong_text_color()),
+    );
+
+    if response.clicked() || response.dragged() {
+        let pos = response.interact_pointer_pos()?;
+        Some(start + ((pos.x - rect.left()) / rect.width()) as X * width)
+    } else {
+        None
+    }
+}
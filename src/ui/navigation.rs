@@ -0,0 +1,63 @@
+use egui_plot::PlotBounds;
+
+/// Back and forward stack of viewed plot bounds, kept apart from history of edits.
+/// Continuous panning or zooming over consecutive frames is one step.
+#[derive(Default)]
+pub struct Navigation {
+    back: Vec<PlotBounds>,
+    forward: Vec<PlotBounds>,
+    /// Whether bounds changed in the previous frame
+    changing: bool,
+}
+
+impl Navigation {
+    const MAX_LEN: usize = 100;
+
+    /// Called each frame with bounds before the frame. The first change of continuous
+    /// navigation remembers `old` bounds.
+    pub fn view_changed(&mut self, old: PlotBounds, changed: bool) {
+        if changed && !self.changing {
+            self.push(old);
+        }
+        self.changing = changed;
+    }
+
+    /// Remembers `old` bounds before jump to other bounds.
+    pub fn jump(&mut self, old: PlotBounds) {
+        self.push(old);
+        self.changing = false;
+    }
+
+    fn push(&mut self, old: PlotBounds) {
+        if self.back.last() != Some(&old) {
+            self.back.push(old);
+            if self.back.len() > Self::MAX_LEN {
+                self.back.remove(0);
+            }
+        }
+        self.forward.clear();
+    }
+
+    /// Bounds to go back to, `current` can be returned to by `forward`.
+    pub fn back(&mut self, current: PlotBounds) -> Option<PlotBounds> {
+        let bounds = self.back.pop()?;
+        self.forward.push(current);
+        self.changing = false;
+        Some(bounds)
+    }
+
+    pub fn forward(&mut self, current: PlotBounds) -> Option<PlotBounds> {
+        let bounds = self.forward.pop()?;
+        self.back.push(current);
+        self.changing = false;
+        Some(bounds)
+    }
+
+    pub fn back_len(&self) -> usize {
+        self.back.len()
+    }
+
+    pub fn forward_len(&self) -> usize {
+        self.forward.len()
+    }
+}
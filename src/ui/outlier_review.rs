@@ -0,0 +1,303 @@
+use eframe::egui::{self, Color32, Ui};
+use egui_plot::{PlotPoints, PlotUi, Points};
+
+use crate::{
+    functions::function::outliers::{self, OutlierDetector},
+    graph::Graph,
+    types::point::{X, Y},
+    widgets::legend_widget::simple_legend_entries::SimpleLegendEntry,
+};
+
+use super::{ActionId, MyApp};
+
+/// Sample found by outlier detector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outlier {
+    pub func: usize,
+    /// Index of point in function
+    pub index: usize,
+    pub x: X,
+    pub y: Y,
+    /// Whether outlier is going to be replaced
+    pub accepted: bool,
+}
+
+/// Outliers found in selection of editable functions, reviewed one by one.
+/// Found outliers belong to revision of graph, in which they were found.
+#[derive(Default)]
+pub struct OutlierReview {
+    pub detector: OutlierDetector,
+    outliers: Vec<Outlier>,
+    current: usize,
+    revision: u64,
+}
+
+impl OutlierReview {
+    pub fn detect(&mut self, graph: &Graph) {
+        self.outliers.clear();
+        for fi in graph.active_func_indexes() {
+            if graph.is_locked(fi) != Some(false) {
+                continue;
+            }
+            let Some(func) = graph.get_func(fi) else {
+                continue;
+            };
+            let points = func.points();
+            self.outliers
+                .extend(
+                    func.selection_outliers(&self.detector)
+                        .into_iter()
+                        .map(|index| Outlier {
+                            func: fi,
+                            index,
+                            x: points[index].x,
+                            y: points[index].y,
+                            accepted: false,
+                        }),
+                );
+        }
+        self.outliers.sort_by(|a, b| a.x.total_cmp(&b.x));
+        self.current = 0;
+        self.revision = graph.revision();
+    }
+
+    /// Forgets outliers, if graph changed since they were found.
+    pub fn update(&mut self, graph: &Graph) {
+        if self.revision != graph.revision() {
+            self.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.outliers.clear();
+        self.current = 0;
+    }
+
+    pub fn outliers(&self) -> &[Outlier] {
+        &self.outliers
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    pub fn current(&self) -> Option<&Outlier> {
+        self.outliers.get(self.current)
+    }
+
+    /// Moves to the next outlier, from the last one to the first one.
+    pub fn go_next(&mut self) -> Option<&Outlier> {
+        if !self.outliers.is_empty() {
+            self.current = (self.current + 1) % self.outliers.len();
+        }
+        self.current()
+    }
+
+    pub fn go_previous(&mut self) -> Option<&Outlier> {
+        if !self.outliers.is_empty() {
+            self.current = self
+                .current
+                .checked_sub(1)
+                .unwrap_or(self.outliers.len() - 1);
+        }
+        self.current()
+    }
+
+    pub fn set_accepted(&mut self, accepted: bool) {
+        if let Some(outlier) = self.outliers.get_mut(self.current) {
+            outlier.accepted = accepted;
+        }
+    }
+
+    pub fn accept_all(&mut self) {
+        self.outliers.iter_mut().for_each(|o| o.accepted = true);
+    }
+
+    pub fn accepted_len(&self) -> usize {
+        self.outliers.iter().filter(|o| o.accepted).count()
+    }
+
+    /// Function, x of the first value and values interpolated over each run of consecutive
+    /// accepted outliers.
+    pub fn replacements(&self, graph: &Graph) -> Vec<(usize, X, Vec<Y>)> {
+        let mut funcs = self
+            .outliers
+            .iter()
+            .filter(|o| o.accepted)
+            .map(|o| o.func)
+            .collect::<Vec<_>>();
+        funcs.sort_unstable();
+        funcs.dedup();
+        let mut replacements = Vec::new();
+        for fi in funcs {
+            let Some(func) = graph.get_func(fi) else {
+                continue;
+            };
+            let points = func.points();
+            let mut indexes = self
+                .outliers
+                .iter()
+                .filter(|o| o.accepted && o.func == fi)
+                .map(|o| o.index)
+                .collect::<Vec<_>>();
+            indexes.sort_unstable();
+            let values = outliers::interpolated(points, &indexes);
+            let mut start = 0;
+            for end in 1..=indexes.len() {
+                if end == indexes.len() || indexes[end] != indexes[end - 1] + 1 {
+                    replacements.push((fi, points[indexes[start]].x, values[start..end].to_vec()));
+                    start = end;
+                }
+            }
+        }
+        replacements
+    }
+}
+
+impl MyApp {
+    pub(super) fn outlier_controls(&mut self, ui: &mut Ui) {
+        ui.label("OUTLIERS");
+        ui.separator();
+        self.outliers.update(self.graph.graph());
+        let detector = &mut self.outliers.detector;
+        egui::ComboBox::from_label("Detector")
+            .selected_text(detector.label())
+            .show_ui(ui, |ui| {
+                for d in OutlierDetector::ALL {
+                    if ui
+                        .selectable_label(d.label() == detector.label(), d.label())
+                        .clicked()
+                        && d.label() != detector.label()
+                    {
+                        *detector = d;
+                    }
+                }
+            });
+        ui.horizontal(|ui| match detector {
+            OutlierDetector::ZScore { threshold } => {
+                ui.label("Deviations");
+                ui.add(
+                    egui::DragValue::new(threshold)
+                        .speed(0.1)
+                        .range(0.1..=100.0),
+                );
+            }
+            OutlierDetector::Hampel { window, threshold } => {
+                ui.label("Window");
+                ui.add(egui::DragValue::new(window).range(1..=1000));
+                ui.label("Deviations");
+                ui.add(
+                    egui::DragValue::new(threshold)
+                        .speed(0.1)
+                        .range(0.1..=100.0),
+                );
+            }
+            OutlierDetector::RateOfChange { limit } => {
+                ui.label("Max change");
+                ui.add(egui::DragValue::new(limit).speed(0.1).range(0.0..=Y::MAX));
+            }
+        });
+        if ui.button("Detect in selection").clicked() {
+            self.detect_outliers();
+        }
+        let count = self.outliers.outliers().len();
+        if count == 0 {
+            ui.separator();
+            return;
+        }
+        let mut moved = None;
+        ui.horizontal(|ui| {
+            if ui.button("<").clicked() {
+                moved = Some(false);
+            }
+            ui.label(format!("{} / {}", self.outliers.current_index() + 1, count));
+            if ui.button(">").clicked() {
+                moved = Some(true);
+            }
+        });
+        if let Some(next) = moved {
+            self.review_outlier(next);
+        }
+        if let Some(outlier) = self.outliers.current() {
+            let mut accepted = outlier.accepted;
+            let x = self
+                .plot
+                .time_ctx
+                .as_ref()
+                .map_or(outlier.x.to_string(), |ctx| ctx.label(outlier.x));
+            ui.label(format!("{}: {}", x, outlier.y));
+            if ui.checkbox(&mut accepted, "Replace").changed() {
+                self.outliers.set_accepted(accepted);
+            }
+        }
+        let accepted = self.outliers.accepted_len();
+        ui.horizontal(|ui| {
+            if ui.button("Accept all").clicked() {
+                self.outliers.accept_all();
+            }
+            let replace = egui::Button::new(format!("Replace {} accepted", accepted));
+            if ui.add_enabled(accepted > 0, replace).clicked() {
+                self.replace_outliers();
+            }
+        });
+        ui.separator();
+    }
+
+    /// Detects outliers in selection and shows the first one.
+    pub(super) fn detect_outliers(&mut self) {
+        self.outliers.detect(self.graph.graph());
+        if let Some(x) = self.outliers.current().map(|o| o.x) {
+            self.zoom_to_outlier(x);
+        }
+    }
+
+    /// Shows the next or the previous detected outlier.
+    pub(super) fn review_outlier(&mut self, next: bool) {
+        let outlier = if next {
+            self.outliers.go_next()
+        } else {
+            self.outliers.go_previous()
+        };
+        if let Some(x) = outlier.map(|o| o.x) {
+            self.zoom_to_outlier(x);
+        }
+    }
+
+    pub(super) fn zoom_to_outlier(&mut self, x: X) {
+        let width = self.plot.bounds.width();
+        let half = (width / 2.0).min(50.0);
+        self.zoom_to(x - half..=x + half, None);
+    }
+
+    /// Replaces accepted outliers by interpolated values, as one history entry.
+    pub(super) fn replace_outliers(&mut self) {
+        let replacements = self.outliers.replacements(self.graph.graph());
+        {
+            let mut b = self.graph.open_action(ActionId::Outliers);
+            for (fi, x, values) in replacements {
+                b.replace_values(fi, x, values.len(), values);
+            }
+        }
+        self.graph.close_action(ActionId::Outliers);
+        self.outliers.clear();
+    }
+
+    /// Found outliers, the reviewed one is bigger and accepted ones are filled.
+    pub(super) fn outliers(
+        plot_ui: &mut PlotUi,
+        review: &OutlierReview,
+        entries: &[SimpleLegendEntry],
+    ) {
+        let current = review.current_index();
+        for (i, outlier) in review.outliers().iter().enumerate() {
+            let color = entries.get(outlier.func).map_or(Color32::RED, |e| e.color);
+            plot_ui.points(
+                Points::new(PlotPoints::new(vec![[outlier.x, outlier.y]]))
+                    .color(color)
+                    .radius(if i == current { 7.0 } else { 4.0 })
+                    .filled(outlier.accepted)
+                    .allow_hover(false),
+            );
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use eframe::egui::{Rangef, Ui};
 use egui_plot::{CoordinatesFormatter, Corner, Plot as EPlot, PlotBounds, PlotResponse, PlotUi};
 
-use crate::{date_time_ctx::DateTimeCtx, types::point::X};
+use crate::{date_time_ctx::DateTimeCtx, importer::DateTimeInfo, types::point::X};
+
+use super::history::{resample_change::ResampleChange, ApplyDataOp};
 
 pub struct Plotter {
     pub bounds: PlotBounds,
@@ -9,6 +11,8 @@ pub struct Plotter {
     x_base_size: f64,
     grid_spacing: Rangef,
     pub time_ctx: Option<DateTimeCtx>,
+    /// Whether plot can be panned by dragging.
+    pub allow_drag: bool,
 }
 
 impl Plotter {
@@ -19,6 +23,23 @@ impl Plotter {
             x_base_size: 1.0,
             grid_spacing: Rangef::new(8.0, 300.0),
             time_ctx,
+            allow_drag: true,
+        }
+    }
+
+    /// Moves bounds to x changed by resampling and replaces mapping between x and time.
+    pub fn resample(&mut self, change: &ApplyDataOp<&ResampleChange>) {
+        let (min, max) = (self.bounds.min(), self.bounds.max());
+        let (x_min, x_max) = (
+            ResampleChange::map_x(change, min[0]),
+            ResampleChange::map_x(change, max[0]),
+        );
+        self.bounds = PlotBounds::from_min_max([x_min, min[1]], [x_max, max[1]]);
+        if let (Some(ctx), Some(mapper)) = (self.time_ctx.take(), ResampleChange::mapper(change)) {
+            self.time_ctx = Some(DateTimeCtx::new(DateTimeInfo {
+                format: ctx.info.format,
+                mapper: mapper.clone(),
+            }));
         }
     }
 
@@ -59,6 +80,7 @@ impl Plotter {
             .height(height)
             .data_aspect(self.data_aspect)
             .grid_spacing(self.grid_spacing)
+            .allow_drag(self.allow_drag)
             .show(ui, |plot_ui| {
                 let r = build_fn(plot_ui);
                 if self.bounds.width() < X::EPSILON {
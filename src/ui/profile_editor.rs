@@ -0,0 +1,69 @@
+use eframe::egui::{self, Pos2, Sense, Shape, Slider, Stroke, Ui, Vec2};
+
+use crate::stretchers::stretch_profile::StretchProfile;
+
+/// Profiles offered in editor, with default parameters.
+fn profiles() -> [StretchProfile; 7] {
+    [
+        StretchProfile::Constant,
+        StretchProfile::Triangular { peak: 0.5 },
+        StretchProfile::Cosine,
+        StretchProfile::Bell { width: 0.2 },
+        StretchProfile::EaseIn,
+        StretchProfile::EaseOut,
+        StretchProfile::Envelope(vec![0.0, 0.25, 0.5, 0.75, 1.0, 0.75, 0.5, 0.25, 0.0].into()),
+    ]
+}
+
+/// Selection of profile, its parameters and preview of its weights.
+/// Weights of envelope are drawn by dragging in the preview.
+pub fn profile_editor(ui: &mut Ui, profile: &mut StretchProfile) {
+    egui::ComboBox::from_label("Profile")
+        .selected_text(profile.label())
+        .show_ui(ui, |ui| {
+            for choice in profiles() {
+                let selected = profile.label() == choice.label();
+                if ui.selectable_label(selected, choice.label()).clicked() && !selected {
+                    *profile = choice;
+                }
+            }
+        });
+    match profile {
+        StretchProfile::Triangular { peak } => {
+            ui.add(Slider::new(peak, 0.0..=1.0).text("Peak"));
+        }
+        StretchProfile::Bell { width } => {
+            ui.add(Slider::new(width, 0.05..=1.0).text("Width"));
+        }
+        _ => (),
+    }
+
+    let (response, painter) =
+        ui.allocate_painter(Vec2::new(ui.available_width(), 60.0), Sense::drag());
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    if let (StretchProfile::Envelope(weights), Some(pos)) =
+        (&mut *profile, response.interact_pointer_pos())
+    {
+        if weights.len() > 1 && rect.contains(pos) {
+            let t = (pos.x - rect.left()) / rect.width();
+            let i = (t * (weights.len() - 1) as f32).round() as usize;
+            weights[i] = ((rect.bottom() - pos.y) / rect.height()).clamp(0.0, 1.0) as f64;
+        }
+    }
+    let steps = rect.width().max(2.0) as usize;
+    let points = (0..=steps)
+        .map(|i| {
+            let t = i as f32 / steps as f32;
+            let weight = profile.weight(t as f64) as f32;
+            Pos2::new(
+                rect.left() + t * rect.width(),
+                rect.bottom() - weight * rect.height(),
+            )
+        })
+        .collect();
+    painter.add(Shape::line(
+        points,
+        Stroke::new(1.5, ui.visuals().strong_text_color()),
+    ));
+}
@@ -0,0 +1,89 @@
+use chrono::TimeDelta;
+use eframe::egui::{self, Ui};
+
+use crate::functions::function::resample::{Aggregation, Resample};
+
+use super::{
+    history::{
+        resample_change::{ResampleChange, ResampleChangeOp},
+        ApplyDataOp,
+    },
+    ActionId, MyApp,
+};
+
+impl MyApp {
+    pub(super) fn resample_controls(&mut self, ui: &mut Ui) {
+        ui.label("RESAMPLE");
+        ui.separator();
+        if self.plot.time_ctx.is_some() {
+            let steps = [
+                ("1 minute", TimeDelta::minutes(1)),
+                ("5 minutes", TimeDelta::minutes(5)),
+                ("15 minutes", TimeDelta::minutes(15)),
+                ("1 hour", TimeDelta::hours(1)),
+                ("1 day", TimeDelta::days(1)),
+                ("1 week", TimeDelta::weeks(1)),
+            ];
+            let selected = steps
+                .iter()
+                .find(|(_, step)| *step == self.resample_time_step)
+                .map_or("", |(label, _)| label);
+            egui::ComboBox::from_label("Interval")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for (label, step) in steps {
+                        ui.selectable_value(&mut self.resample_time_step, step, label);
+                    }
+                });
+        } else {
+            ui.horizontal(|ui| {
+                ui.label("Interval");
+                ui.add(
+                    egui::DragValue::new(&mut self.resample_step)
+                        .speed(0.1)
+                        .range(0.01..=1e6),
+                );
+            });
+        }
+        egui::ComboBox::from_label("Aggregation")
+            .selected_text(self.resample_aggregation.label())
+            .show_ui(ui, |ui| {
+                for aggregation in Aggregation::ALL {
+                    ui.selectable_value(
+                        &mut self.resample_aggregation,
+                        aggregation,
+                        aggregation.label(),
+                    );
+                }
+            });
+        if ui.button("Resample all functions").clicked() {
+            self.resample();
+        }
+        ui.separator();
+    }
+
+    /// Resamples all functions and changes mapping between x and time accordingly,
+    /// as one history entry.
+    pub(super) fn resample(&mut self) {
+        let aggregation = self.resample_aggregation;
+        let (resample, mappers) = match &self.plot.time_ctx {
+            Some(ctx) => {
+                let old = ctx.info.mapper.clone();
+                let new = old.aligned(self.resample_time_step);
+                (Resample::between(&old, &new, aggregation), Some((old, new)))
+            }
+            None => (Resample::new(self.resample_step, aggregation), None),
+        };
+        if !resample.is_valid() {
+            return;
+        }
+        let change = ResampleChange::new(&resample, mappers);
+        self.plot.resample(&ApplyDataOp::Redo(&change));
+        {
+            let mut b = self.graph.open_action(ActionId::Resample);
+            b.resample(&resample);
+            b.other(ResampleChangeOp(change));
+        }
+        self.graph.close_action(ActionId::Resample);
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::NaiveTime;
+use eframe::egui::{self, Color32, Ui};
+
+use crate::selection_rule::{SelectionRule, SelectionRuleError};
+
+use super::{ActionId, MyApp, SELECTION_RULES_PATH};
+
+/// Input of selection rule. Times of daily rule are edited as text.
+pub(super) struct RuleInput {
+    pub(super) rule: SelectionRule,
+    pub(super) start: String,
+    pub(super) end: String,
+    pub(super) error: Option<String>,
+}
+
+impl Default for RuleInput {
+    fn default() -> Self {
+        Self {
+            rule: SelectionRule::Above(0.0),
+            start: "22:00".to_string(),
+            end: "06:00".to_string(),
+            error: None,
+        }
+    }
+}
+
+impl RuleInput {
+    fn rule(&self) -> Result<SelectionRule, String> {
+        match self.rule {
+            SelectionRule::DailyTime(..) => {
+                let parse = |text: &str| {
+                    NaiveTime::parse_from_str(text.trim(), "%H:%M")
+                        .map_err(|_| format!("'{}' is not time like 22:00", text))
+                };
+                Ok(SelectionRule::DailyTime(
+                    parse(&self.start)?,
+                    parse(&self.end)?,
+                ))
+            }
+            ref rule => Ok(rule.clone()),
+        }
+    }
+}
+
+impl MyApp {
+    pub(super) fn selection_rule_controls(&mut self, ui: &mut Ui) {
+        ui.label("SELECTION RULES");
+        ui.separator();
+        let input = &mut self.rule;
+        let kinds = [
+            (SelectionRule::Above(0.0), "Value above"),
+            (SelectionRule::Below(0.0), "Value below"),
+            (SelectionRule::Weekends, "Weekends"),
+            (
+                SelectionRule::DailyTime(NaiveTime::MIN, NaiveTime::MIN),
+                "Time of day",
+            ),
+            (SelectionRule::AroundMaxima(3), "Around maxima"),
+        ];
+        let same_kind = |a: &SelectionRule, b: &SelectionRule| {
+            std::mem::discriminant(a) == std::mem::discriminant(b)
+        };
+        egui::ComboBox::from_label("Rule")
+            .selected_text(
+                kinds
+                    .iter()
+                    .find(|(rule, _)| same_kind(rule, &input.rule))
+                    .map_or("", |(_, text)| *text),
+            )
+            .show_ui(ui, |ui| {
+                for (rule, text) in kinds.iter() {
+                    if ui
+                        .selectable_label(same_kind(rule, &input.rule), *text)
+                        .clicked()
+                        && !same_kind(rule, &input.rule)
+                    {
+                        input.rule = rule.clone();
+                    }
+                }
+            });
+        match &mut input.rule {
+            SelectionRule::Above(threshold) | SelectionRule::Below(threshold) => {
+                ui.add(egui::DragValue::new(threshold).speed(0.1));
+            }
+            SelectionRule::Weekends => {}
+            SelectionRule::DailyTime(..) => {
+                ui.horizontal(|ui| {
+                    ui.label("From");
+                    ui.add(egui::TextEdit::singleline(&mut input.start).desired_width(50.0));
+                    ui.label("to");
+                    ui.add(egui::TextEdit::singleline(&mut input.end).desired_width(50.0));
+                });
+            }
+            SelectionRule::AroundMaxima(samples) => {
+                ui.horizontal(|ui| {
+                    ui.label("Samples");
+                    ui.add(egui::DragValue::new(samples).range(0..=10_000));
+                });
+            }
+        }
+        let mut apply = None;
+        ui.horizontal(|ui| {
+            if ui.button("Select").clicked() {
+                apply = Some(false);
+            }
+            if ui.button("Save").clicked() {
+                apply = Some(true);
+            }
+        });
+        if let Some(save) = apply {
+            let res = self.rule.rule().and_then(|rule| {
+                if save {
+                    self.selection_rules.push(rule.clone());
+                    self.save_selection_rules()?;
+                }
+                self.select_by_rule(&rule).map_err(|e| e.to_string())
+            });
+            self.rule.error = res.err();
+        }
+        if let Some(error) = &self.rule.error {
+            ui.colored_label(Color32::LIGHT_RED, error);
+        }
+        let mut select = None;
+        let mut remove = None;
+        for (i, rule) in self.selection_rules.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(rule.to_string());
+                if ui.button("Select").clicked() {
+                    select = Some(rule.clone());
+                }
+                if ui.button("Remove").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(rule) = select {
+            self.rule.error = self.select_by_rule(&rule).err().map(|e| e.to_string());
+        }
+        if let Some(i) = remove {
+            self.selection_rules.remove(i);
+            self.rule.error = self.save_selection_rules().err();
+        }
+        ui.separator();
+    }
+
+    pub(super) fn save_selection_rules(&self) -> Result<(), String> {
+        SelectionRule::save_all(SELECTION_RULES_PATH, &self.selection_rules)
+            .map_err(|e| format!("can not save selection rules: {}", e))
+    }
+
+    /// Selects ranges matching `rule`, first range becomes selection of graph.
+    pub(super) fn select_by_rule(
+        &mut self,
+        rule: &SelectionRule,
+    ) -> Result<(), SelectionRuleError> {
+        let mapper = self.plot.time_ctx.as_ref().map(|ctx| &ctx.info.mapper);
+        let ranges = rule.ranges(self.graph.graph(), mapper)?;
+        self.graph.select_ranges(ActionId::Conditions, &ranges);
+        Ok(())
+    }
+}
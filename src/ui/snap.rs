@@ -0,0 +1,117 @@
+use std::ops::RangeInclusive;
+
+use eframe::egui::{self, Ui};
+
+use crate::{
+    date_time_ctx::{CalendarUnit, DateTimeCtx},
+    types::point::{Point, X},
+};
+
+use super::MyApp;
+
+/// Boundaries, to which edges of selection snap.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Snap {
+    Off,
+    /// Whole samples
+    #[default]
+    Samples,
+    /// Starts of calendar unit, needs date and time x axis
+    Calendar(CalendarUnit),
+    /// Local minima and maxima of function
+    Extrema,
+}
+
+impl Snap {
+    pub const ALL: [Self; 7] = [
+        Self::Off,
+        Self::Samples,
+        Self::Calendar(CalendarUnit::Minute),
+        Self::Calendar(CalendarUnit::Hour),
+        Self::Calendar(CalendarUnit::Day),
+        Self::Calendar(CalendarUnit::Month),
+        Self::Extrema,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Samples => "Samples",
+            Self::Calendar(CalendarUnit::Minute) => "Minute",
+            Self::Calendar(CalendarUnit::Hour) => "Hour",
+            Self::Calendar(CalendarUnit::Day) => "Day",
+            Self::Calendar(CalendarUnit::Month) => "Month start",
+            Self::Extrema => "Extrema",
+        }
+    }
+
+    /// Snapped x. Calendar snapping is rounded to whole samples, x is kept, when there is
+    /// nothing to snap to.
+    pub fn apply(&self, x: X, time_ctx: Option<&DateTimeCtx>, points: &[Point]) -> X {
+        match self {
+            Self::Off => x,
+            Self::Samples => x.round(),
+            Self::Calendar(unit) => time_ctx.map_or(x, |ctx| ctx.snap(x, *unit).round()),
+            Self::Extrema => nearest_extremum(points, x).unwrap_or(x),
+        }
+    }
+}
+
+/// X of local minimum or maximum nearest to `x`.
+fn nearest_extremum(points: &[Point], x: X) -> Option<X> {
+    points
+        .windows(3)
+        .filter(|w| {
+            // Strict on the left side, so plateau is counted once.
+            (w[1].y > w[0].y && w[1].y >= w[2].y) || (w[1].y < w[0].y && w[1].y <= w[2].y)
+        })
+        .map(|w| w[1].x)
+        .min_by(|a, b| (a - x).abs().total_cmp(&(b - x).abs()))
+}
+
+impl MyApp {
+    pub(super) fn snap_controls(&mut self, ui: &mut Ui) {
+        ui.label("SNAPPING");
+        ui.separator();
+        egui::ComboBox::from_label("Snap to")
+            .selected_text(self.snap.label())
+            .show_ui(ui, |ui| {
+                for snap in Snap::ALL {
+                    if matches!(snap, Snap::Calendar(_)) && self.plot.time_ctx.is_none() {
+                        continue;
+                    }
+                    ui.selectable_value(&mut self.snap, snap, snap.label());
+                }
+            });
+        ui.label("Hold Alt to move selection without snapping.");
+        ui.separator();
+    }
+
+    /// Points of function, to whose extrema selection snaps.
+    pub(super) fn snap_points(&self) -> &[Point] {
+        self.focused_func()
+            .and_then(|fi| self.graph.graph().get_func(fi))
+            .map_or(&[], |f| f.points())
+    }
+
+    pub(super) fn snap_x(&self, x: X) -> X {
+        self.snap
+            .apply(x, self.plot.time_ctx.as_ref(), self.snap_points())
+    }
+
+    /// Snaps edges of `new`, which differ from `old`.
+    pub(super) fn snap_selection(
+        &self,
+        old: &RangeInclusive<X>,
+        new: RangeInclusive<X>,
+    ) -> RangeInclusive<X> {
+        let snap = |x: X, old: X| if x == old { x } else { self.snap_x(x) };
+        let start = snap(*new.start(), *old.start());
+        let end = snap(*new.end(), *old.end());
+        if start <= end {
+            start..=end
+        } else {
+            end..=start
+        }
+    }
+}
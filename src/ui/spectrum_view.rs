@@ -0,0 +1,189 @@
+use eframe::egui::{self, Color32, Ui};
+use egui_plot::{Line, LineStyle, Plot, PlotPoints, VLine};
+
+use crate::{
+    functions::function::{
+        spectrum::{Filter, Spectrum, Window},
+        transform::Transform,
+    },
+    graph::Graph,
+    types::point::X,
+};
+
+use super::{time_unit_label, ActionId, MyApp};
+
+/// Revision of graph, window and index, x of the first point and length of selection
+/// of each active function.
+type SpectraKey = (u64, Window, Vec<(usize, X, usize)>);
+
+/// Spectra of selected points of active functions. Frequencies of filter are in
+/// cycles per unit of the view.
+#[derive(Default)]
+pub struct SpectrumView {
+    pub enabled: bool,
+    pub window: Window,
+    pub filter: Filter,
+    spectra: Vec<(usize, Spectrum)>,
+    /// Key of graph state, for which spectra were computed
+    key: Option<SpectraKey>,
+}
+
+impl SpectrumView {
+    /// Recomputes spectra, if values, selection or window changed since last update.
+    pub fn update(&mut self, graph: &Graph) {
+        let selections = graph
+            .active_func_indexes()
+            .filter_map(|fi| {
+                let points = graph.get_func(fi)?.points_selection();
+                Some((fi, points.first()?.x, points.len()))
+            })
+            .collect::<Vec<_>>();
+        let key = (graph.revision(), self.window, selections);
+        if self.key.as_ref() == Some(&key) {
+            return;
+        }
+        self.spectra = key
+            .2
+            .iter()
+            .filter_map(|(fi, _, _)| {
+                let spectrum = graph.get_func(*fi)?.selection_spectrum(self.window)?;
+                Some((*fi, spectrum))
+            })
+            .collect();
+        self.key = Some(key);
+    }
+
+    pub fn spectra(&self) -> &[(usize, Spectrum)] {
+        &self.spectra
+    }
+
+    /// Plot of amplitudes over frequency in cycles per unit, in which sample is `x_unit` long.
+    pub fn show(&self, ui: &mut Ui, x_unit: X, unit: &str, colors: impl Fn(usize) -> Color32) {
+        Plot::new("spectrum")
+            .height(160.0)
+            .x_axis_label(format!("cycles per {unit}"))
+            .y_axis_label("amplitude")
+            .allow_scroll(false)
+            .show(ui, |plot_ui| {
+                for (fi, spectrum) in &self.spectra {
+                    let points = spectrum
+                        .frequencies
+                        .iter()
+                        .zip(&spectrum.amplitudes)
+                        .map(|(f, a)| [f / x_unit, *a])
+                        .collect::<Vec<_>>();
+                    plot_ui.line(Line::new(PlotPoints::new(points)).color(colors(*fi)));
+                }
+                let edges = match self.filter {
+                    Filter::LowPass { cutoff } => vec![cutoff],
+                    Filter::BandStop { low, high } => vec![low, high],
+                };
+                for edge in edges {
+                    plot_ui.vline(
+                        VLine::new(edge)
+                            .color(Color32::LIGHT_RED)
+                            .style(LineStyle::dashed_loose()),
+                    );
+                }
+            });
+    }
+}
+
+impl MyApp {
+    pub(super) fn spectrum_controls(&mut self, ui: &mut Ui) {
+        ui.label("SPECTRUM");
+        ui.separator();
+        ui.checkbox(&mut self.spectrum.enabled, "Show spectrum of selection");
+        let view = &mut self.spectrum;
+        egui::ComboBox::from_label("Window")
+            .selected_text(view.window.label())
+            .show_ui(ui, |ui| {
+                for window in Window::ALL {
+                    ui.selectable_value(&mut view.window, window, window.label());
+                }
+            });
+        let (x_unit, unit) = match &self.plot.time_ctx {
+            Some(ctx) => {
+                Self::time_unit_combo(ui, "Frequency per", &mut self.spectrum_unit);
+                let x_unit = Transform::x_unit(&ctx.info.mapper, self.spectrum_unit);
+                (x_unit, time_unit_label(self.spectrum_unit))
+            }
+            None => (1.0, "x"),
+        };
+        let view = &mut self.spectrum;
+        let (cutoff, low, high) = match view.filter {
+            Filter::LowPass { cutoff } => (cutoff, cutoff / 2.0, cutoff),
+            Filter::BandStop { low, high } => (low, low, high),
+        };
+        egui::ComboBox::from_label("Filter")
+            .selected_text(view.filter.label())
+            .show_ui(ui, |ui| {
+                for filter in [Filter::LowPass { cutoff }, Filter::BandStop { low, high }] {
+                    if ui
+                        .selectable_label(view.filter.label() == filter.label(), filter.label())
+                        .clicked()
+                        && view.filter.label() != filter.label()
+                    {
+                        view.filter = filter;
+                    }
+                }
+            });
+        let speed = 0.01 / x_unit;
+        match &mut view.filter {
+            Filter::LowPass { cutoff } => {
+                ui.horizontal(|ui| {
+                    ui.label("Cutoff");
+                    ui.add(
+                        egui::DragValue::new(cutoff)
+                            .speed(speed)
+                            .range(0.0..=X::MAX),
+                    );
+                });
+            }
+            Filter::BandStop { low, high } => {
+                ui.horizontal(|ui| {
+                    ui.label("From");
+                    ui.add(egui::DragValue::new(low).speed(speed).range(0.0..=*high));
+                    ui.label("to");
+                    ui.add(egui::DragValue::new(high).speed(speed).range(*low..=X::MAX));
+                });
+            }
+        }
+        if ui.button("Apply filter to selection").clicked() {
+            self.apply_filter();
+        }
+        if self.spectrum.enabled {
+            self.spectrum.update(self.graph.graph());
+            for (fi, spectrum) in self.spectrum.spectra() {
+                let (Some(entry), Some((f, a))) = (self.legend_entries.get(*fi), spectrum.peak())
+                else {
+                    continue;
+                };
+                ui.label(format!(
+                    "{}: peak {:.3} at {:.4} per {}",
+                    entry.name,
+                    a,
+                    f / x_unit,
+                    unit
+                ));
+            }
+            let entries = &self.legend_entries;
+            let colors = |fi: usize| entries.get(fi).map_or(Color32::GRAY, |e| e.color);
+            self.spectrum.show(ui, x_unit, unit, colors);
+        }
+        ui.separator();
+    }
+
+    /// Filters selected values of editable functions by filter of the view.
+    pub(super) fn apply_filter(&mut self) {
+        let x_unit = self.plot.time_ctx.as_ref().map_or(1.0, |ctx| {
+            Transform::x_unit(&ctx.info.mapper, self.spectrum_unit)
+        });
+        let filter = self.spectrum.filter.scaled(x_unit);
+        {
+            let mut b = self.graph.open_action(ActionId::Filter);
+            b.filter(&filter);
+        }
+        self.graph.close_action(ActionId::Filter);
+    }
+}
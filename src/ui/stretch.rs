@@ -0,0 +1,328 @@
+use core::f64;
+use std::ops::RangeInclusive;
+
+use eframe::egui::{self, Color32, Id, Response, Ui};
+use enumflags2::BitFlags;
+
+use crate::{
+    functions::function::{StretchY, StretchYBounds, StretchYBoundsError},
+    reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    stretchers::{
+        stretch_profile::StretchProfile,
+        y_stretcher::{StretchBaseline, YStretcherFlags},
+    },
+    types::point::Y,
+    widgets::slider_widget::{SliderOptionalData, SliderWidget},
+};
+
+use super::{
+    history::OwnedHistoryOp, profile_editor, tinput, ActionId, MyApp, NonAlteringGraphOpHelper,
+};
+
+impl MyApp {
+    pub(super) fn stretch_profile_controls(&mut self, ui: &mut Ui) {
+        ui.label("STRETCH PROFILE");
+        ui.separator();
+        profile_editor::profile_editor(ui, &mut self.stretch_profile);
+        ui.label("Applies to stretching by factor.");
+        ui.separator();
+    }
+
+    /// Text fields for exact target of stretch. Target, which can not be reached, is
+    /// reported below the fields.
+    pub(super) fn stretch_y_target_controls(
+        ui: &mut Ui,
+        value_range: &RangeInclusive<Y>,
+        stretch_flags: BitFlags<YStretcherFlags>,
+        profile: &StretchProfile,
+        baseline: &StretchBaseline,
+        graph: &mut BasicReversibleGraph<ActionId, OwnedHistoryOp, NonAlteringGraphOpHelper>,
+    ) {
+        let buffers_id = Id::new("stretch_y_targets");
+        let error_id = buffers_id.with("error");
+        let mut buffers: [String; 4] = ui.data(|r| r.get_temp(buffers_id).unwrap_or_default());
+        let (min, max) = (*value_range.start(), *value_range.end());
+        let amplitude = max - min;
+        let bounds = || {
+            if stretch_flags.is_all() {
+                StretchYBounds::new(Y::NEG_INFINITY, Y::INFINITY)
+            } else {
+                StretchYBounds::new(Y::NAN, Y::NAN)
+            }
+            .with_baseline(baseline.clone())
+        };
+        let mut res = None;
+        if let Some(new_min) = tinput(ui, &mut buffers[0], "Min", &min) {
+            let mut bounds = bounds();
+            bounds.set_min(new_min);
+            res = Some(Self::stretch_y_to_bounds(&bounds, graph));
+        }
+        if let Some(new_max) = tinput(ui, &mut buffers[1], "Max", &max) {
+            let mut bounds = bounds();
+            bounds.set_max(new_max);
+            res = Some(Self::stretch_y_to_bounds(&bounds, graph));
+        }
+        if let Some(new_amplitude) = tinput(ui, &mut buffers[2], "Peak to peak", &amplitude) {
+            // Around horizontal baseline distance of extremes scales with the factor.
+            let factor = new_amplitude / amplitude;
+            res = Some(if factor.is_finite() && factor > 0.0 {
+                let stretch = StretchY::new(factor, BitFlags::all())
+                    .map(|s| s.with_baseline(baseline.clone()));
+                Self::stretch_y_by(stretch.as_ref(), graph);
+                Ok(())
+            } else if amplitude < Y::EPSILON {
+                Err(StretchYBoundsError::Unstretchable.to_string())
+            } else {
+                Err(StretchYBoundsError::BoundsOutOfRange.to_string())
+            });
+        }
+        if let Some(factor) = tinput(ui, &mut buffers[3], "Factor", &1.0) {
+            let stretch = StretchY::new(factor, stretch_flags)
+                .and_then(|s| s.with_profile(profile.clone()))
+                .map(|s| s.with_baseline(baseline.clone()));
+            res = Some(if stretch.is_some() || (factor - 1.0).abs() < Y::EPSILON {
+                Self::stretch_y_by(stretch.as_ref(), graph);
+                Ok(())
+            } else {
+                Err(format!("factor {} does not stretch", factor))
+            });
+        }
+        match res {
+            Some(Ok(())) => ui.data_mut(|w| w.remove::<String>(error_id)),
+            Some(Err(error)) => ui.data_mut(|w| w.insert_temp(error_id, error)),
+            None => {}
+        }
+        if let Some(error) = ui.data(|r| r.get_temp::<String>(error_id)) {
+            ui.colored_label(Color32::LIGHT_RED, error);
+        }
+        ui.data_mut(|w| w.insert_temp(buffers_id, buffers));
+    }
+
+    pub(super) fn stretch_y_to_bounds(
+        bounds: &StretchYBounds,
+        graph: &mut BasicReversibleGraph<ActionId, OwnedHistoryOp, NonAlteringGraphOpHelper>,
+    ) -> Result<(), String> {
+        let res = graph
+            .open_action(ActionId::StretchY)
+            .stretch_y_bounds_in_ranges(bounds)
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+        graph.close_action(ActionId::StretchY);
+        res
+    }
+
+    pub(super) fn stretch_y_by(
+        stretch: Option<&StretchY>,
+        graph: &mut BasicReversibleGraph<ActionId, OwnedHistoryOp, NonAlteringGraphOpHelper>,
+    ) {
+        if let Some(stretch) = stretch {
+            graph
+                .open_action(ActionId::StretchY)
+                .for_each_selection(|b, excluded| {
+                    b.stretch_y_with_factor_excluding(stretch, excluded)
+                });
+            graph.close_action(ActionId::StretchY);
+        }
+    }
+
+    /// Stretches selected values of editable functions by `factor` with chosen profile
+    /// and baseline.
+    pub(super) fn stretch_selection(&mut self, factor: Y) {
+        let stretch = StretchY::new(factor, BitFlags::all())
+            .and_then(|s| s.with_profile(self.stretch_profile.clone()))
+            .map(|s| s.with_baseline(self.stretch_baseline.clone()));
+        Self::stretch_y_by(stretch.as_ref(), &mut self.graph);
+    }
+
+    pub(super) fn stretch_y_controls(
+        ui: &mut Ui,
+        bounds: &RangeInclusive<f64>,
+        profile: &StretchProfile,
+        baseline: &mut StretchBaseline,
+        graph: &mut BasicReversibleGraph<ActionId, OwnedHistoryOp, NonAlteringGraphOpHelper>,
+    ) {
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                ui.label("Around");
+                egui::ComboBox::from_id_source("stretch_y_baseline")
+                    .selected_text(baseline.label())
+                    .show_ui(ui, |ui| {
+                        for mode in StretchBaseline::MODES {
+                            let label = mode.label();
+                            let selected = baseline.label() == label;
+                            if ui.selectable_label(selected, label).clicked() && !selected {
+                                *baseline = mode;
+                            }
+                        }
+                    });
+                if let StretchBaseline::Value(value) = baseline {
+                    ui.add(egui::DragValue::new(value).speed(0.1));
+                }
+            });
+            let mut stretch_flags = BitFlags::empty();
+            ui.horizontal(|ui| {
+                let stretch_y_mode_id = Id::new("stretch_y_mode");
+                let old_both = ui.data(|r| r.get_temp(stretch_y_mode_id).unwrap_or(false));
+                let mut both = old_both;
+
+                ui.checkbox(&mut both, "Stretch both").rect.height();
+                if old_both != both {
+                    ui.data_mut(|w| w.insert_temp(stretch_y_mode_id, both));
+                }
+                if both {
+                    stretch_flags = BitFlags::all();
+                }
+                ui.group(|ui| {
+                    if stretch_flags.is_all() {
+                        ui.disable();
+                    }
+                    let stretch_factor_mode_id = Id::new("stretch_factor_mode");
+                    let is_top = ui.data(|r| r.get_temp(stretch_factor_mode_id).unwrap_or(true));
+                    let mut new_is_top = is_top;
+                    ui.toggle_value(&mut new_is_top, if is_top { "Top" } else { "Bottom" });
+
+                    stretch_flags.insert(if new_is_top {
+                        YStretcherFlags::Top
+                    } else {
+                        YStretcherFlags::Bottom
+                    });
+                    if new_is_top != is_top {
+                        ui.data_mut(|w| w.insert_temp(stretch_factor_mode_id, new_is_top));
+                    }
+                });
+            });
+            let mut stretch_bounds = if stretch_flags.is_all() {
+                StretchYBounds::new(Y::NEG_INFINITY, Y::INFINITY)
+            } else {
+                StretchYBounds::new(Y::NAN, Y::NAN)
+            }
+            .with_baseline(baseline.clone());
+
+            let value_range = match graph.graph().value_range() {
+                Some(r) => r,
+                None => return,
+            };
+            Self::stretch_y_target_controls(
+                ui,
+                &value_range,
+                stretch_flags,
+                profile,
+                baseline,
+                graph,
+            );
+            let overflow_height_id = Id::new("overwlow/underflow_of_height_of_stretch_y_controls");
+            let height =
+                ui.available_height() - ui.data(|r| r.get_temp(overflow_height_id).unwrap_or(0.0));
+            ui.spacing_mut().slider_width = height;
+            let mut min_height = 0.0_f32;
+            let range =
+                value_range.start().min(*bounds.start())..=value_range.end().max(*bounds.end());
+
+            ui.horizontal_top(|ui| {
+                let max = *value_range.end();
+                let mut new_max = max;
+
+                let resp = ui.add(SliderWidget {
+                    num: &mut new_max,
+                    range: range.clone(),
+                    data: SliderOptionalData {
+                        orientation: egui::SliderOrientation::Vertical,
+                        ..Default::default()
+                    },
+                });
+                min_height = min_height.max(resp.rect.height());
+
+                fn is_action_end(response: &Response) -> bool {
+                    response.drag_stopped() || response.lost_focus()
+                }
+
+                let mut action_end = false;
+                if (new_max - max).abs() >= Y::EPSILON {
+                    action_end = is_action_end(&resp);
+                    stretch_bounds.set_max(new_max);
+                }
+
+                let min = *value_range.start();
+                let mut new_min = min;
+                let resp = ui.add(SliderWidget {
+                    num: &mut new_min,
+                    range: range.clone(),
+                    data: SliderOptionalData {
+                        orientation: egui::SliderOrientation::Vertical,
+                        ..Default::default()
+                    },
+                });
+                min_height = min_height.max(resp.rect.height());
+                if (new_min - min).abs() >= Y::EPSILON {
+                    if action_end {
+                        action_end = is_action_end(&resp);
+                    }
+                    stretch_bounds.set_min(new_min);
+                }
+                let stretch_factor_zp_id = Id::new("stretch_factor_zp");
+
+                let mut factor: f64 = ui.data(|r| r.get_temp(stretch_factor_zp_id).unwrap_or(1.0));
+                let mut new_factor = factor;
+                let resp = ui.add(SliderWidget {
+                    num: &mut new_factor,
+                    range: -1.5..=1.5,
+                    data: SliderOptionalData {
+                        step: 0.01,
+                        clamp_to_range: false,
+                        orientation: egui::SliderOrientation::Vertical,
+                        ..Default::default()
+                    },
+                });
+                min_height = min_height.max(resp.rect.height());
+
+                let mut is_factor_action_end = false;
+                if (new_factor - factor).abs() >= Y::EPSILON {
+                    let factor_action_end = is_action_end(&resp);
+                    if action_end {
+                        action_end = factor_action_end;
+                    }
+                    is_factor_action_end = factor_action_end;
+                }
+                if !is_factor_action_end && (resp.dragged() || resp.drag_started()) {
+                    if new_factor.abs() < f64::EPSILON {
+                        new_factor = new_factor.signum() * 0.01;
+                    }
+                    if (new_factor - factor).abs() >= f64::EPSILON {
+                        ui.data_mut(|w| w.insert_temp(stretch_factor_zp_id, new_factor));
+                    }
+                } else {
+                    ui.data_mut(|w| w.remove::<f64>(stretch_factor_zp_id));
+                }
+                factor = new_factor / factor;
+                if factor.abs() > 10.0 {
+                    println!("factor {} is too large!", factor);
+                }
+                if (factor - 1.0).abs() < 0.0001 {
+                    factor = 1.0;
+                }
+                {
+                    let mut b = graph.open_action(ActionId::StretchY);
+                    let _ = b.stretch_y_bounds_in_ranges(&stretch_bounds);
+                    let stretch = StretchY::new(factor, stretch_flags)
+                        .and_then(|s| s.with_profile(profile.clone()))
+                        .map(|s| s.with_baseline(baseline.clone()));
+                    if let Some(stretch) = stretch {
+                        b.for_each_selection(|b, excluded| {
+                            b.stretch_y_with_factor_excluding(&stretch, excluded)
+                        });
+                    }
+                }
+
+                if action_end {
+                    graph.close_action(ActionId::StretchY);
+                }
+            });
+            ui.data_mut(|w| {
+                w.insert_temp(
+                    overflow_height_id,
+                    min_height.max(ui.min_rect().height()) - height,
+                )
+            });
+        });
+    }
+}
@@ -0,0 +1,119 @@
+use eframe::egui::{self, Ui};
+
+use crate::{
+    functions::function::transform::Transform,
+    types::point::X,
+    widgets::legend_widget::simple_legend_entries::{SimpleLegendEntries, SimpleLegendEntry},
+};
+
+use super::{utils, ActionId, MyApp};
+
+/// Transforms, which turn functions transformed in place back.
+#[derive(Default)]
+pub(super) struct InverseTransform {
+    /// Id of function and transform turning it back
+    transforms: Vec<(usize, Transform)>,
+    /// Length of sample in unit of rate, with which functions were transformed
+    x_unit: X,
+}
+
+impl MyApp {
+    pub(super) fn transform_controls(&mut self, ui: &mut Ui) {
+        ui.label("DERIVATIVE AND INTEGRAL");
+        ui.separator();
+        let initial = match self.transform {
+            Transform::Integral { initial } => initial,
+            Transform::Derivative => 0.0,
+        };
+        egui::ComboBox::from_label("Transform")
+            .selected_text(self.transform.label())
+            .show_ui(ui, |ui| {
+                for transform in [Transform::Derivative, Transform::Integral { initial }] {
+                    ui.selectable_value(&mut self.transform, transform, transform.label());
+                }
+            });
+        if let Transform::Integral { initial } = &mut self.transform {
+            ui.horizontal(|ui| {
+                ui.label("Start at");
+                ui.add(egui::DragValue::new(initial).speed(0.1));
+            });
+        }
+        if self.plot.time_ctx.is_some() {
+            Self::time_unit_combo(ui, "Rate per", &mut self.transform_unit);
+        }
+        let focused = self.focused_func();
+        ui.horizontal(|ui| {
+            let add = ui.add_enabled(focused.is_some(), egui::Button::new("Add as function"));
+            if let (true, Some(fi)) = (add.clicked(), focused) {
+                self.add_transformed(fi);
+            }
+            if ui.button("Apply in place").clicked() {
+                self.transform_in_place();
+            }
+            let inverse = &self.transform_inverse.transforms;
+            if ui
+                .add_enabled(!inverse.is_empty(), egui::Button::new("Inverse"))
+                .on_hover_text("Turns functions transformed in place back")
+                .clicked()
+            {
+                self.inverse_transform();
+            }
+        });
+        ui.separator();
+    }
+
+    /// Transforms editable functions in place and remembers, how to turn them back.
+    pub(super) fn transform_in_place(&mut self) {
+        let x_unit = self.transform_x_unit();
+        let transforms = {
+            let mut b = self.graph.open_action(ActionId::Transform);
+            b.transform(&self.transform, x_unit)
+        };
+        self.graph.close_action(ActionId::Transform);
+        self.transform_inverse = InverseTransform { transforms, x_unit };
+    }
+
+    /// Turns functions transformed in place back by inverse of the last transform, e.g.
+    /// integral of derivative starts at the first value, which derivative lost.
+    pub(super) fn inverse_transform(&mut self) {
+        let x_unit = self.transform_inverse.x_unit;
+        let transforms = {
+            let mut b = self.graph.open_action(ActionId::Transform);
+            b.transform_funcs(&self.transform_inverse.transforms, x_unit)
+        };
+        self.graph.close_action(ActionId::Transform);
+        self.transform_inverse = InverseTransform { transforms, x_unit };
+    }
+
+    /// Length of sample in unit of rate, x is the unit without dates.
+    pub(super) fn transform_x_unit(&self) -> X {
+        self.plot.time_ctx.as_ref().map_or(1.0, |ctx| {
+            Transform::x_unit(&ctx.info.mapper, self.transform_unit)
+        })
+    }
+
+    /// Adds function transformed from function at `index` at the end of legend.
+    pub(super) fn add_transformed(&mut self, index: usize) {
+        let graph = self.graph.graph();
+        let (Some(func), Some(entry)) = (graph.get_func(index), self.legend_entries.get(index))
+        else {
+            return;
+        };
+        let Some(x) = func.points().first().map(|p| p.x) else {
+            return;
+        };
+        let values = func.transformed(&self.transform, self.transform_x_unit());
+        let name = match self.transform {
+            Transform::Derivative => format!("d({})", entry.name),
+            Transform::Integral { .. } => format!("∫({})", entry.name),
+        };
+        let id = graph.next_func_id();
+        let style = SimpleLegendEntry::new(name, utils::auto_color(id), false).style();
+        SimpleLegendEntries::new(
+            &mut self.graph,
+            &mut self.legend_entries,
+            &mut self.legend_order,
+        )
+        .add_func(style, x, values);
+    }
+}
@@ -0,0 +1,50 @@
+use eframe::egui::{self, Ui};
+
+use super::MyApp;
+
+/// How view changes of one kind are recorded in history of edits.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ViewRecording {
+    /// Each change is undo step, which does not forget redo steps
+    #[default]
+    Undoable,
+    /// Changes are undone together with the next edit
+    Coalesced,
+    /// Undo and redo keep the current view. Viewport is then not recorded at all,
+    /// selection and visible functions are still recorded with the next edit, because
+    /// undo of edits depends on them, and are restored after undo and redo.
+    KeptOnUndo,
+}
+
+impl ViewRecording {
+    pub const ALL: [Self; 3] = [Self::Undoable, Self::Coalesced, Self::KeptOnUndo];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Undoable => "Undoable",
+            Self::Coalesced => "With next edit",
+            Self::KeptOnUndo => "Kept on undo",
+        }
+    }
+}
+
+impl MyApp {
+    pub(super) fn view_recording_controls(&mut self, ui: &mut Ui) {
+        ui.label("VIEW CHANGES IN HISTORY");
+        ui.separator();
+        let combo = |ui: &mut Ui, label: &str, recording: &mut ViewRecording| {
+            egui::ComboBox::from_label(label)
+                .selected_text(recording.label())
+                .show_ui(ui, |ui| {
+                    for value in ViewRecording::ALL {
+                        ui.selectable_value(recording, value, value.label());
+                    }
+                });
+        };
+        let helper = self.graph.history_mut().helper_mut();
+        combo(ui, "Selection", &mut helper.selection);
+        combo(ui, "Visible functions", &mut helper.visibility);
+        combo(ui, "Viewport", &mut self.viewport_recording);
+        ui.separator();
+    }
+}
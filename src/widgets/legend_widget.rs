@@ -1,9 +1,11 @@
 pub mod simple_legend_entries;
 
+use std::ops::RangeInclusive;
+
 use eframe::{
     egui::{
-        pos2, vec2, Align, Color32, Direction, Frame, Layout, PointerButton, Rect, Response, Sense,
-        TextStyle, Ui, Widget, WidgetInfo, WidgetType,
+        pos2, vec2, Align, Color32, Direction, DragValue, Frame, Layout, PointerButton, Rect,
+        Response, Sense, Slider, Stroke, TextStyle, Ui, Widget, WidgetInfo, WidgetType,
     },
     epaint,
 };
@@ -21,6 +23,10 @@ pub trait LegendEntries {
     fn get_hovered(&self, index: usize) -> Option<bool>;
     fn get_checked(&self, index: usize) -> Option<bool>;
     fn get_entry(&self, index: usize) -> Option<LegendEntry>;
+    fn get_style(&self, index: usize) -> Option<LegendEntryStyle>;
+
+    /// Indexes of entries in order, in which they are shown.
+    fn order(&self) -> Vec<usize>;
 
     fn iter_checked(&self) -> impl Iterator<Item = usize>;
     fn iter_unchecked(&self) -> impl Iterator<Item = usize>;
@@ -31,6 +37,20 @@ pub trait LegendEntries {
 
     fn set_hovered(&mut self, index: usize, hovered: bool);
     fn set_checked(&mut self, index: usize, checked: bool);
+    fn set_style(&mut self, index: usize, style: LegendEntryStyle);
+    /// Moves entry at position `from` to position `to` in order of entries.
+    fn move_entry(&mut self, from: usize, to: usize);
+    fn get_locked(&self, index: usize) -> Option<bool>;
+    fn set_locked(&mut self, index: usize, locked: bool);
+    /// Selection of entry and whether it is its own selection.
+    fn get_selection(&self, index: usize) -> Option<(RangeInclusive<f64>, bool)>;
+    /// Sets own selection of entry, `None` makes it use shared selection.
+    fn set_selection(&mut self, index: usize, selection: Option<RangeInclusive<f64>>);
+    /// Adds copy of entry right after it.
+    fn duplicate(&mut self, index: usize);
+    fn remove(&mut self, index: usize);
+    /// Called when editing of style of entry ends.
+    fn finish_edit(&mut self);
     fn toggle_checked(&mut self, index: usize) {
         if let Some(checked) = self.get_checked(index) {
             self.set_checked(index, !checked);
@@ -43,6 +63,15 @@ pub trait LegendEntries {
     }
 }
 
+/// Presentation of entry, which can be changed from its context menu.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendEntryStyle {
+    pub name: String,
+    pub color: Color32,
+    pub width: f32,
+    pub dashed: bool,
+}
+
 #[derive(Clone)]
 pub struct LegendEntry {
     pub name: String,
@@ -79,7 +108,7 @@ impl LegendEntry {
         let total_extra = vec2(icon_size + icon_spacing, 0.0);
 
         let desired_size = total_extra + galley.size();
-        let (rect, response) = ui.allocate_exact_size(desired_size, Sense::click());
+        let (rect, response) = ui.allocate_exact_size(desired_size, Sense::click_and_drag());
 
         response.widget_info(|| {
             WidgetInfo::selected(
@@ -188,21 +217,38 @@ impl<'a, Entries: LegendEntries> Widget for &mut LegendWidget<'a, Entries> {
                 background_frame
                     .show(ui, |ui| {
                         let mut focus_on_item = None;
+                        let mut rects = Vec::with_capacity(entries.len());
+                        // Position of dragged entry and whether it was released
+                        let mut dragged = None;
 
-                        let response_union = (0..entries.len())
+                        let response_union = entries
+                            .order()
                             .into_iter()
-                            .map(|(entry_index)| {
-                                let entry = entries.get_entry(entry_index).unwrap();
+                            .enumerate()
+                            .filter_map(|(position, entry_index)| {
+                                // Entry can be removed from context menu of previous entry.
+                                let entry = entries.get_entry(entry_index)?;
                                 let response = entry.ui(ui, &config.text_style);
 
                                 // Handle interactions. Alt-clicking must be deferred to end of loop
                                 // since it may affect all entries.
                                 handle_interaction_on_legend_item(&response, entry_index, *entries);
+                                handle_context_menu_on_legend_item(
+                                    &response,
+                                    entry_index,
+                                    *entries,
+                                );
                                 if response.clicked() && ui.input(|r| r.modifiers.alt) {
                                     focus_on_item = Some(entry_index);
                                 }
+                                if response.drag_stopped() {
+                                    dragged = Some((position, true));
+                                } else if response.dragged() {
+                                    dragged = Some((position, false));
+                                }
+                                rects.push(response.rect);
 
-                                response
+                                Some(response)
                             })
                             .reduce(|r1, r2| r1.union(r2))
                             .unwrap();
@@ -210,6 +256,22 @@ impl<'a, Entries: LegendEntries> Widget for &mut LegendWidget<'a, Entries> {
                         if let Some(focus_on_item) = focus_on_item {
                             handle_focus_on_legend_item(focus_on_item, *entries);
                         }
+                        if let (Some((from, released)), Some(pointer)) =
+                            (dragged, ui.ctx().pointer_interact_pos())
+                        {
+                            let to = drop_position(&rects, pointer.y);
+                            if released {
+                                if from != to {
+                                    entries.move_entry(from, to);
+                                }
+                            } else {
+                                ui.painter().rect_stroke(
+                                    rects[to],
+                                    0.0,
+                                    Stroke::new(1.0, ui.visuals().selection.stroke.color),
+                                );
+                            }
+                        }
 
                         response_union
                     })
@@ -231,6 +293,96 @@ fn handle_interaction_on_legend_item(
     entries.set_hovered(index, response.hovered());
 }
 
+/// Shows context menu, which edits style, lock and selection of entry, duplicates or removes it.
+fn handle_context_menu_on_legend_item(
+    response: &Response,
+    index: usize,
+    entries: &mut impl LegendEntries,
+) {
+    let was_open_id = response.id.with("context_menu_was_open");
+    let was_open = response
+        .ctx
+        .data(|r| r.get_temp(was_open_id).unwrap_or(false));
+    let is_open = response
+        .context_menu(|ui| {
+            let mut style = match entries.get_style(index) {
+                Some(style) => style,
+                None => return,
+            };
+            let old = style.clone();
+            ui.horizontal(|ui| {
+                ui.label("Name");
+                ui.text_edit_singleline(&mut style.name);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Color");
+                ui.color_edit_button_srgba(&mut style.color);
+            });
+            ui.add(Slider::new(&mut style.width, 0.5..=10.0).text("Width"));
+            ui.checkbox(&mut style.dashed, "Dashed");
+            if style != old {
+                entries.set_style(index, style);
+            }
+            ui.separator();
+            if let Some(mut locked) = entries.get_locked(index) {
+                if ui.checkbox(&mut locked, "Locked").changed() {
+                    entries.set_locked(index, locked);
+                }
+            }
+            if let Some((selection, own)) = entries.get_selection(index) {
+                let (mut start, mut end, mut own_selection) =
+                    (*selection.start(), *selection.end(), own);
+                ui.checkbox(&mut own_selection, "Own selection");
+                if own_selection {
+                    ui.horizontal(|ui| {
+                        ui.add(DragValue::new(&mut start).speed(1.0));
+                        ui.add(DragValue::new(&mut end).speed(1.0));
+                    });
+                }
+                if own_selection != own || start != *selection.start() || end != *selection.end() {
+                    entries.set_selection(
+                        index,
+                        own_selection.then(|| start.min(end)..=start.max(end)),
+                    );
+                }
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Duplicate").clicked() {
+                    entries.duplicate(index);
+                    ui.close_menu();
+                }
+                if ui.button("Delete").clicked() {
+                    entries.remove(index);
+                    ui.close_menu();
+                }
+            });
+        })
+        .is_some();
+    if was_open && !is_open {
+        entries.finish_edit();
+    }
+    if was_open != is_open {
+        response
+            .ctx
+            .data_mut(|w| w.insert_temp(was_open_id, is_open));
+    }
+}
+
+/// Position of entry, whose rect is nearest to `y`.
+fn drop_position(rects: &[Rect], y: f32) -> usize {
+    rects
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            (a.center().y - y)
+                .abs()
+                .total_cmp(&(b.center().y - y).abs())
+        })
+        .map(|(position, _)| position)
+        .unwrap_or(0)
+}
+
 /// Handle alt-click interaction (which may affect all entries).
 fn handle_focus_on_legend_item(clicked_entry_index: usize, entries: &mut impl LegendEntries) {
     // if all other items are already hidden, we show everything
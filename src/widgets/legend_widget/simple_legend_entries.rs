@@ -1,21 +1,31 @@
+use std::ops::RangeInclusive;
+
 use eframe::egui::Color32;
 
 use crate::{
     graph::GraphFuncState,
-    history::history_stack::{shared_entry::OtherOp, IsGraphOpNonAltering},
+    history::history_stack::{
+        shared_entry::{InOp, OtherOp},
+        IsGraphOpNonAltering,
+    },
     reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    types::point::{X, Y},
 };
 
-use super::{LegendEntries, LegendEntry};
+use super::{LegendEntries, LegendEntry, LegendEntryStyle};
 
 pub trait LegendActionId: Clone + PartialEq + Default {
     fn change_active_funcs() -> Self;
+    fn change_legend() -> Self;
+    fn change_funcs() -> Self;
 }
 
 pub struct SimpleLegendEntry {
     pub name: String,
     pub color: Color32,
     pub hovered: bool,
+    pub width: f32,
+    pub dashed: bool,
 }
 
 impl SimpleLegendEntry {
@@ -24,10 +34,116 @@ impl SimpleLegendEntry {
             name,
             color,
             hovered,
+            width: 1.5,
+            dashed: false,
+        }
+    }
+
+    pub fn from_style(style: &LegendEntryStyle) -> Self {
+        let mut entry = Self::new(style.name.clone(), style.color, false);
+        entry.set_style(style);
+        entry
+    }
+
+    pub fn style(&self) -> LegendEntryStyle {
+        LegendEntryStyle {
+            name: self.name.clone(),
+            color: self.color,
+            width: self.width,
+            dashed: self.dashed,
+        }
+    }
+
+    pub fn set_style(&mut self, style: &LegendEntryStyle) {
+        self.name = style.name.clone();
+        self.color = style.color;
+        self.width = style.width;
+        self.dashed = style.dashed;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LegendChange {
+    /// Index of entry, old style and new style
+    Style(usize, LegendEntryStyle, LegendEntryStyle),
+    /// Entry was moved from position to position in order of entries
+    Move(usize, usize),
+    /// Index of added entry, its style and its position in order of entries
+    Add(usize, LegendEntryStyle, usize),
+    /// Index of removed entry, its style and its position in order of entries
+    Remove(usize, LegendEntryStyle, usize),
+}
+
+impl LegendChange {
+    pub fn undo(&self, entries: &mut Vec<SimpleLegendEntry>, order: &mut Vec<usize>) {
+        match self {
+            Self::Style(index, old, _) => Self::set_style(entries, *index, old),
+            Self::Move(from, to) => Self::move_entry(order, *to, *from),
+            Self::Add(index, _, _) => Self::remove_entry(entries, order, *index),
+            Self::Remove(index, style, position) => {
+                Self::insert_entry(entries, order, *index, style, *position)
+            }
+        }
+    }
+
+    pub fn redo(&self, entries: &mut Vec<SimpleLegendEntry>, order: &mut Vec<usize>) {
+        match self {
+            Self::Style(index, _, new) => Self::set_style(entries, *index, new),
+            Self::Move(from, to) => Self::move_entry(order, *from, *to),
+            Self::Add(index, style, position) => {
+                Self::insert_entry(entries, order, *index, style, *position)
+            }
+            Self::Remove(index, _, _) => Self::remove_entry(entries, order, *index),
+        }
+    }
+
+    fn insert_entry(
+        entries: &mut Vec<SimpleLegendEntry>,
+        order: &mut Vec<usize>,
+        index: usize,
+        style: &LegendEntryStyle,
+        position: usize,
+    ) {
+        if index > entries.len() {
+            return;
+        }
+        entries.insert(index, SimpleLegendEntry::from_style(style));
+        order
+            .iter_mut()
+            .filter(|i| **i >= index)
+            .for_each(|i| *i += 1);
+        order.insert(position.min(order.len()), index);
+    }
+
+    fn remove_entry(entries: &mut Vec<SimpleLegendEntry>, order: &mut Vec<usize>, index: usize) {
+        if index >= entries.len() {
+            return;
+        }
+        entries.remove(index);
+        order.retain(|i| *i != index);
+        order
+            .iter_mut()
+            .filter(|i| **i > index)
+            .for_each(|i| *i -= 1);
+    }
+
+    fn set_style(entries: &mut [SimpleLegendEntry], index: usize, style: &LegendEntryStyle) {
+        if let Some(entry) = entries.get_mut(index) {
+            entry.set_style(style);
+        }
+    }
+
+    fn move_entry(order: &mut Vec<usize>, from: usize, to: usize) {
+        if from < order.len() && to < order.len() {
+            let index = order.remove(from);
+            order.insert(to, index);
         }
     }
 }
 
+/// Change of legend entries, which is recorded into history.
+pub struct LegendChangeOp(pub LegendChange);
+
 pub struct SimpleLegendEntries<
     'a,
     ActionId: LegendActionId,
@@ -35,7 +151,8 @@ pub struct SimpleLegendEntries<
     NonAlteringGraphOpHelper: IsGraphOpNonAltering<ActionId>,
 > {
     graph: &'a mut BasicReversibleGraph<ActionId, OpOwned, NonAlteringGraphOpHelper>,
-    entries: &'a mut [SimpleLegendEntry],
+    entries: &'a mut Vec<SimpleLegendEntry>,
+    order: &'a mut Vec<usize>,
 }
 
 impl<
@@ -45,11 +162,36 @@ impl<
         NonAlteringGraphOpHelper: IsGraphOpNonAltering<ActionId>,
     > SimpleLegendEntries<'a, ActionId, OpOwned, NonAlteringGraphOpHelper>
 {
+    /// `order` - indexes of entries in order, in which they are shown.
     pub fn new(
         graph: &'a mut BasicReversibleGraph<ActionId, OpOwned, NonAlteringGraphOpHelper>,
-        entries: &'a mut [SimpleLegendEntry],
+        entries: &'a mut Vec<SimpleLegendEntry>,
+        order: &'a mut Vec<usize>,
     ) -> Self {
-        Self { graph, entries }
+        Self {
+            graph,
+            entries,
+            order,
+        }
+    }
+}
+
+impl<
+        'a,
+        ActionId: LegendActionId,
+        OpOwned: OtherOp,
+        NonAlteringGraphOpHelper: IsGraphOpNonAltering<ActionId>,
+    > SimpleLegendEntries<'a, ActionId, OpOwned, NonAlteringGraphOpHelper>
+where
+    LegendChangeOp: InOp<OpOwned, ActionId>,
+{
+    /// Adds new active function with first point at `x` and entry for it at the end of legend.
+    pub fn add_func(&mut self, style: LegendEntryStyle, x: X, values: impl IntoIterator<Item = Y>) {
+        let mut action = self.graph.action(ActionId::change_funcs());
+        let index = action.add_func(x, values, GraphFuncState::Active);
+        let change = LegendChange::Add(index, style, self.order.len());
+        change.redo(self.entries, self.order);
+        action.other(LegendChangeOp(change));
     }
 }
 
@@ -59,6 +201,8 @@ impl<
         OpOwned: OtherOp,
         NonAlteringGraphOpHelper: IsGraphOpNonAltering<ActionId>,
     > LegendEntries for SimpleLegendEntries<'a, ActionId, OpOwned, NonAlteringGraphOpHelper>
+where
+    LegendChangeOp: InOp<OpOwned, ActionId>,
 {
     fn len(&self) -> usize {
         self.graph.graph().len()
@@ -88,8 +232,13 @@ impl<
             self.entries.get(index),
             self.graph.graph().get_func_state(index),
         ) {
+            let locked = self.graph.graph().is_locked(index) == Some(true);
             Some(LegendEntry {
-                name: entry.name.clone(),
+                name: if locked {
+                    format!("{} (locked)", entry.name)
+                } else {
+                    entry.name.clone()
+                },
                 color: entry.color,
                 checked: state == GraphFuncState::Active,
                 hovered: entry.hovered,
@@ -99,6 +248,14 @@ impl<
         }
     }
 
+    fn get_style(&self, index: usize) -> Option<LegendEntryStyle> {
+        self.entries.get(index).map(|e| e.style())
+    }
+
+    fn order(&self) -> Vec<usize> {
+        self.order.clone()
+    }
+
     fn iter_checked(&self) -> impl Iterator<Item = usize> {
         self.graph.graph().active_functions_index().map(|(_, i)| i)
     }
@@ -131,6 +288,99 @@ impl<
         }
     }
 
+    fn set_style(&mut self, index: usize, style: LegendEntryStyle) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            let old = entry.style();
+            if old != style {
+                entry.set_style(&style);
+                self.graph
+                    .open_action(ActionId::change_legend())
+                    .other(LegendChangeOp(LegendChange::Style(index, old, style)));
+            }
+        }
+    }
+
+    fn move_entry(&mut self, from: usize, to: usize) {
+        let len = self.order.len();
+        if from != to && from < len && to < len {
+            let change = LegendChange::Move(from, to);
+            change.redo(self.entries, self.order);
+            self.graph
+                .action(ActionId::change_legend())
+                .other(LegendChangeOp(change));
+        }
+    }
+
+    fn get_locked(&self, index: usize) -> Option<bool> {
+        self.graph.graph().is_locked(index)
+    }
+
+    fn set_locked(&mut self, index: usize, locked: bool) {
+        self.graph
+            .action(ActionId::change_funcs())
+            .change_func_lock(index, locked);
+    }
+
+    fn get_selection(&self, index: usize) -> Option<(RangeInclusive<X>, bool)> {
+        let graph = self.graph.graph();
+        if index >= graph.len() {
+            return None;
+        }
+        Some(match graph.func_selection(index) {
+            Some(selection) => (selection.clone(), true),
+            None => (graph.selection().clone(), false),
+        })
+    }
+
+    fn set_selection(&mut self, index: usize, selection: Option<RangeInclusive<X>>) {
+        self.graph
+            .open_action(ActionId::change_funcs())
+            .change_func_selection(index, selection);
+    }
+
+    fn duplicate(&mut self, index: usize) {
+        let (style, position) = match (
+            self.entries.get(index),
+            self.order.iter().position(|i| *i == index),
+        ) {
+            (Some(entry), Some(position)) => (entry.style(), position),
+            _ => return,
+        };
+        let mut action = self.graph.action(ActionId::change_funcs());
+        if let Some(new_index) = action.duplicate_func(index) {
+            let change = LegendChange::Add(
+                new_index,
+                LegendEntryStyle {
+                    name: format!("{} copy", style.name),
+                    ..style
+                },
+                position + 1,
+            );
+            change.redo(self.entries, self.order);
+            action.other(LegendChangeOp(change));
+        }
+    }
+
+    fn remove(&mut self, index: usize) {
+        let (style, position) = match (
+            self.entries.get(index),
+            self.order.iter().position(|i| *i == index),
+        ) {
+            (Some(entry), Some(position)) => (entry.style(), position),
+            _ => return,
+        };
+        let mut action = self.graph.action(ActionId::change_funcs());
+        action.remove_func(index);
+        let change = LegendChange::Remove(index, style, position);
+        change.redo(self.entries, self.order);
+        action.other(LegendChangeOp(change));
+    }
+
+    fn finish_edit(&mut self) {
+        self.graph.close_action(ActionId::change_legend());
+        self.graph.close_action(ActionId::change_funcs());
+    }
+
     fn check_all(&mut self) {
         {
             let mut binding = self.graph.open_action(ActionId::change_active_funcs());
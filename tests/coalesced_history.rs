@@ -0,0 +1,119 @@
+mod common;
+
+use common::NoOp;
+use plots::{
+    functions::function::Func,
+    graph::Graph,
+    history::{
+        history_stack::{shared_entry::ApplyOtherOp, IsGraphOpNonAltering},
+        History,
+    },
+    reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    shared_op::SharedOp,
+    types::point::{Point, X, Y},
+};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+enum Id {
+    #[default]
+    Edit,
+    /// Selection moves recorded with this id are coalesced into the next edit.
+    View,
+}
+
+#[derive(Default)]
+struct CoalescingHelper;
+
+impl IsGraphOpNonAltering<Id> for CoalescingHelper {
+    fn graph_op_alters_history<IterChangeActiveFuncs, FuncIter, YExactIter>(
+        g_op: &SharedOp<IterChangeActiveFuncs, FuncIter, YExactIter>,
+        group_id: &Id,
+    ) -> bool
+    where
+        IterChangeActiveFuncs: Iterator<Item = usize> + Clone,
+        FuncIter: Iterator<Item = YExactIter>,
+        YExactIter: ExactSizeIterator<Item = Y> + Clone,
+    {
+        *group_id != Id::View || !matches!(g_op, SharedOp::MoveSelectBy(_))
+    }
+
+    fn coalesces_graph_op<IterChangeActiveFuncs, FuncIter, YExactIter>(
+        &self,
+        g_op: &SharedOp<IterChangeActiveFuncs, FuncIter, YExactIter>,
+        _group_id: &Id,
+    ) -> bool
+    where
+        IterChangeActiveFuncs: Iterator<Item = usize> + Clone,
+        FuncIter: Iterator<Item = YExactIter>,
+        YExactIter: ExactSizeIterator<Item = Y> + Clone,
+    {
+        matches!(g_op, SharedOp::MoveSelectBy(_))
+    }
+}
+
+type TestGraph = BasicReversibleGraph<Id, NoOp, CoalescingHelper>;
+
+fn graph() -> TestGraph {
+    let mut graph = Graph::new(vec![Func::new_from(
+        (0..8).map(|i| Point::new(i as X, i as Y)).collect(),
+    )]);
+    graph.change_selection(1.0..=2.0);
+    BasicReversibleGraph::new(graph, History::new())
+}
+
+fn values(graph: &TestGraph) -> Vec<Y> {
+    let points = graph.graph().get_func(0).unwrap().points();
+    points.iter().map(|p| p.y).collect()
+}
+
+fn undo(graph: &mut TestGraph) {
+    graph.undo(|_: ApplyOtherOp<()>| {});
+}
+
+fn redo(graph: &mut TestGraph) {
+    graph.redo(|_: ApplyOtherOp<()>| {});
+}
+
+#[test]
+fn coalesced_ops_are_undone_with_next_edit() {
+    let mut graph = graph();
+    graph.action(Id::View).change_selection(4.0..=5.0);
+    assert_eq!(graph.history().undo_len(), 0);
+    graph.action(Id::Edit).delete();
+    assert_eq!(values(&graph), vec![0.0, 1.0, 2.0, 3.0, 6.0, 7.0]);
+    assert_eq!(graph.history().undo_len(), 1);
+    assert_eq!(graph.history().undo_iter().count(), 1);
+
+    undo(&mut graph);
+    assert_eq!(values(&graph), (0..8).map(Y::from).collect::<Vec<_>>());
+    assert_eq!(graph.graph().selection(), &(1.0..=2.0));
+    redo(&mut graph);
+    assert_eq!(values(&graph), vec![0.0, 1.0, 2.0, 3.0, 6.0, 7.0]);
+    assert_eq!(graph.graph().selection(), &(4.0..=5.0));
+}
+
+#[test]
+fn pending_coalesced_ops_are_undone_with_last_edit() {
+    let mut graph = graph();
+    graph.action(Id::Edit).delete();
+    graph.action(Id::View).change_selection(3.0..=4.0);
+    assert_eq!(graph.history().undo_len(), 1);
+
+    undo(&mut graph);
+    assert_eq!(values(&graph), (0..8).map(Y::from).collect::<Vec<_>>());
+    assert_eq!(graph.graph().selection(), &(1.0..=2.0));
+    assert_eq!(graph.history().redo_len(), 1);
+
+    // Coalesced move keeps redo entry, new edit replaces it.
+    graph.action(Id::View).change_selection(5.0..=6.0);
+    assert_eq!(graph.history().redo_len(), 1);
+    graph.action(Id::Edit).delete();
+    assert_eq!(graph.history().redo_len(), 0);
+    assert_eq!(graph.history().undo_len(), 1);
+    assert_eq!(values(&graph), vec![0.0, 1.0, 2.0, 3.0, 4.0, 7.0]);
+
+    undo(&mut graph);
+    assert_eq!(values(&graph), (0..8).map(Y::from).collect::<Vec<_>>());
+    assert_eq!(graph.graph().selection(), &(1.0..=2.0));
+    assert_eq!(graph.history().undo_len(), 0);
+}
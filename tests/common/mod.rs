@@ -0,0 +1,107 @@
+//! Fixture shared by integration tests, which record graph edits into `History`.
+
+// Each test crate compiles this module, but not every test uses every item.
+#![allow(dead_code)]
+
+use plots::{
+    history::history_stack::{
+        shared_entry::{ApplyOtherOp, InOp, OpCombineErr, OpCreateErr, OtherOp, OwnedOp},
+        IsGraphOpNonAltering,
+    },
+    shared_op::SharedOp,
+    types::point::Y,
+    widgets::legend_widget::simple_legend_entries::{
+        LegendActionId, LegendChange, LegendChangeOp, SimpleLegendEntry,
+    },
+};
+
+/// Other op of tests, which record only graph ops.
+pub struct NoOp;
+
+impl OtherOp for NoOp {}
+
+impl OwnedOp<()> for NoOp {
+    fn get_shared(&self) {}
+}
+
+/// Every graph op alters history, whatever its id is.
+#[derive(Default)]
+pub struct AllAlter;
+
+impl<Id> IsGraphOpNonAltering<Id> for AllAlter {
+    fn graph_op_alters_history<IterChangeActiveFuncs, FuncIter, YExactIter>(
+        _g_op: &SharedOp<IterChangeActiveFuncs, FuncIter, YExactIter>,
+        _group_id: &Id,
+    ) -> bool
+    where
+        IterChangeActiveFuncs: Iterator<Item = usize> + Clone,
+        FuncIter: Iterator<Item = YExactIter>,
+        YExactIter: ExactSizeIterator<Item = Y> + Clone,
+    {
+        true
+    }
+}
+
+/// Action ids of tests, which record legend changes.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum TestId {
+    #[default]
+    Conditions,
+    Legend,
+    Functions,
+}
+
+impl LegendActionId for TestId {
+    fn change_active_funcs() -> Self {
+        TestId::Conditions
+    }
+
+    fn change_legend() -> Self {
+        TestId::Legend
+    }
+
+    fn change_funcs() -> Self {
+        TestId::Functions
+    }
+}
+
+/// Other op of tests, which record legend changes.
+pub struct LegendOp(LegendChange);
+
+impl OtherOp for LegendOp {}
+
+impl OwnedOp<LegendChange> for LegendOp {
+    fn get_shared(&self) -> LegendChange {
+        self.0.clone()
+    }
+}
+
+impl TryFrom<LegendChangeOp> for LegendOp {
+    type Error = OpCreateErr;
+
+    fn try_from(op: LegendChangeOp) -> Result<Self, Self::Error> {
+        Ok(Self(op.0))
+    }
+}
+
+impl InOp<LegendOp, TestId> for LegendChangeOp {
+    fn try_combine(self, _owned: &mut LegendOp) -> Result<(), OpCombineErr<Self>> {
+        Err(OpCombineErr::CannotCombine(self))
+    }
+
+    fn alters_history(&self, _id: &TestId) -> bool {
+        true
+    }
+}
+
+/// Applies undone or redone legend change to `entries` and `order`.
+pub fn apply_legend(
+    op: ApplyOtherOp<LegendChange>,
+    entries: &mut Vec<SimpleLegendEntry>,
+    order: &mut Vec<usize>,
+) {
+    match op {
+        ApplyOtherOp::Undo(change) => change.undo(entries, order),
+        ApplyOtherOp::Redo(change) => change.redo(entries, order),
+    }
+}
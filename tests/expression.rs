@@ -0,0 +1,150 @@
+mod common;
+
+use common::{AllAlter, NoOp};
+use plots::{
+    functions::{
+        expression::{Expression, ExpressionError},
+        function::Func,
+    },
+    graph::{Graph, GraphFuncState},
+    history::{history_stack::shared_entry::ApplyOtherOp, History},
+    reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    types::point::{Point, X, Y},
+};
+
+type TestGraph = BasicReversibleGraph<(), NoOp, AllAlter>;
+
+fn func(start: X, values: &[Y]) -> Func {
+    Func::new_from(
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, y)| Point::new(start + i as X, *y))
+            .collect(),
+    )
+}
+
+fn resolve(name: &str) -> Option<usize> {
+    ["A", "B", "long name"].iter().position(|n| *n == name)
+}
+
+fn graph() -> Graph {
+    Graph::new(vec![
+        func(0.0, &[1.0, 2.0, 3.0, 4.0]),
+        func(1.0, &[10.0, 20.0, 30.0, 40.0]),
+        func(0.0, &[5.0, 5.0]),
+    ])
+}
+
+#[test]
+fn derives_on_shared_x() {
+    let graph = graph();
+    let expression = Expression::parse("A - B * 0.5 + \"long name\"", resolve).unwrap();
+    assert_eq!(expression.sources(), &[0, 1, 2]);
+    assert_eq!(graph.derive(&expression), Ok((1.0, vec![2.0])));
+
+    let expression = Expression::parse("-A^2 + max(A, 2) / 2", resolve).unwrap();
+    assert_eq!(
+        graph.derive(&expression),
+        Ok((0.0, vec![0.0, -3.0, -7.5, -14.0]))
+    );
+}
+
+#[test]
+fn constant_spans_graph() {
+    let graph = graph();
+    let expression = Expression::parse("2 * pi + x", resolve).unwrap();
+    let (x, values) = graph.derive(&expression).unwrap();
+    assert_eq!(x, 0.0);
+    assert_eq!(values.len(), 5);
+    assert_eq!(values[4], 2.0 * std::f64::consts::PI + 4.0);
+}
+
+#[test]
+fn reports_errors() {
+    let graph = graph();
+    assert_eq!(
+        Expression::parse("A +", resolve),
+        Err(ExpressionError::UnexpectedEnd)
+    );
+    assert_eq!(
+        Expression::parse("A + C", resolve),
+        Err(ExpressionError::UnknownName("C".to_string()))
+    );
+    assert_eq!(
+        Expression::parse("max(A)", resolve),
+        Err(ExpressionError::WrongArgCount("max".to_string(), 1))
+    );
+    let expression = Expression::parse("1 / (A - 1)", resolve).unwrap();
+    assert_eq!(
+        graph.derive(&expression),
+        Err(ExpressionError::NotFinite(0.0))
+    );
+}
+
+#[test]
+fn live_function_follows_source() {
+    let mut graph = graph();
+    let expression = Expression::parse("A * 2", resolve).unwrap();
+    let (x, values) = graph.derive(&expression).unwrap();
+    let id = graph.next_func_id();
+    let index = graph.len();
+    graph.insert_func(index, id, func(x, &values), GraphFuncState::Inactive);
+    graph.change_selection(0.0..=1.0);
+    let mut graph = BasicReversibleGraph::new(graph, History::<(), NoOp, AllAlter>::new());
+    graph.set_live_expression(id, Some(expression));
+    let values = |graph: &TestGraph, index| {
+        graph
+            .graph()
+            .get_func(index)
+            .unwrap()
+            .points()
+            .iter()
+            .map(|p| p.y)
+            .collect::<Vec<_>>()
+    };
+    let original = (values(&graph, 0), values(&graph, index));
+
+    graph.action(()).delete();
+    assert_eq!(values(&graph, 0), vec![3.0, 4.0]);
+    assert_eq!(values(&graph, index), vec![6.0, 8.0]);
+
+    // Recomputation is part of the undone action, derived values are not recomputed.
+    graph.undo(|_: ApplyOtherOp<()>| {});
+    assert_eq!((values(&graph, 0), values(&graph, index)), original);
+    graph.redo(|_: ApplyOtherOp<()>| {});
+    assert_eq!(values(&graph, index), vec![6.0, 8.0]);
+    graph.undo(|_: ApplyOtherOp<()>| {});
+
+    graph.set_live_expression(id, None);
+    graph.action(()).delete();
+    assert_eq!(values(&graph, index), original.1);
+}
+
+#[test]
+fn live_function_with_missing_value_is_not_recomputed_without_edit() {
+    let mut graph = Graph::new(vec![func(0.0, &[1.0, Y::NAN, 3.0]), func(0.0, &[5.0])]);
+    let expression = Expression::parse("A * 2", resolve).unwrap();
+    let (x, values) = graph.derive(&expression).unwrap();
+    assert!(values[1].is_nan());
+    let id = graph.next_func_id();
+    let index = graph.len();
+    graph.insert_func(index, id, func(x, &values), GraphFuncState::Inactive);
+    graph.set_live_expression(id, Some(expression));
+    assert!(graph.live_updates().is_empty());
+    let mut graph: TestGraph = BasicReversibleGraph::new(graph, History::new());
+
+    // Edit of function, which is not a source, keeps the live function.
+    graph.action(()).replace_values(1, 0.0, 1, [6.0]);
+    assert_eq!(graph.history().undo_len(), 1);
+    graph.undo(|_: ApplyOtherOp<()>| {});
+    assert_eq!(graph.history().redo_len(), 1);
+
+    // Actions, which change nothing, do not add entries or forget redo.
+    for _ in 0..5 {
+        let selection = graph.graph().selection().clone();
+        graph.open_action(()).change_selection(selection);
+    }
+    assert_eq!(graph.history().undo_len(), 0);
+    assert_eq!(graph.history().redo_len(), 1);
+}
@@ -0,0 +1,65 @@
+use plots::{
+    functions::function::{
+        fit::{FitError, FitModel},
+        Func,
+    },
+    types::point::{Point, X, Y},
+};
+
+fn points(len: usize, f: impl Fn(X) -> Y) -> Vec<Point> {
+    (0..len).map(|i| Point::new(i as X, f(i as X))).collect()
+}
+
+#[test]
+fn polynomial_recovers_values_and_skips_missing() {
+    let mut values = points(20, |x| 3.0 - 2.0 * x + 0.5 * x * x);
+    values[7].y = Y::NAN;
+    let fit = FitModel::Polynomial(2).fit(&values).unwrap();
+    assert!(fit.stats.rmse < 1e-9);
+    assert!((fit.stats.r_squared - 1.0).abs() < 1e-9);
+    assert!((fit.curve.at(7.0) - 0.5 * 49.0 + 11.0).abs() < 1e-9);
+    assert!((fit.curve.at(30.0) - (3.0 - 60.0 + 450.0)).abs() < 1e-6);
+    assert!(fit.curve.line().is_none());
+
+    let line = FitModel::Polynomial(1).fit(&points(5, |x| 2.0 * x + 1.0));
+    assert!(line.unwrap().curve.line().is_some());
+    assert_eq!(
+        FitModel::Polynomial(3).fit(&values[..3]),
+        Err(FitError::NotEnoughPoints)
+    );
+}
+
+#[test]
+fn nonlinear_models_fit_their_curves() {
+    let fits = [
+        (FitModel::Exponential, points(30, |x| 2.0 * (0.1 * x).exp())),
+        (
+            FitModel::Logarithmic,
+            points(30, |x| 4.0 + 3.0 * (x + 1.0).ln()),
+        ),
+        (
+            FitModel::Sinusoid,
+            points(200, |x| 1.0 + 2.0 * (0.3 * x + 0.5).sin()),
+        ),
+        (
+            FitModel::PiecewiseLinear(2),
+            points(21, |x| if x < 10.0 { x } else { 20.0 - x }),
+        ),
+    ];
+    for (model, values) in fits {
+        let fit = model.fit(&values).unwrap();
+        assert!(fit.stats.r_squared > 0.999, "{}", model.label());
+        assert!(fit.stats.max_residual < 0.05, "{}", model.label());
+    }
+    let mixed = points(5, |x| x - 2.0);
+    assert_eq!(FitModel::Exponential.fit(&mixed), Err(FitError::MixedSigns));
+}
+
+#[test]
+fn fit_of_selection() {
+    let mut func = Func::new_from(points(10, |x| if x < 5.0 { 0.0 } else { x }));
+    func.change_selection(&(5.0..=9.0));
+    let fit = func.fit_selection(&FitModel::Polynomial(1)).unwrap();
+    assert!(fit.stats.rmse < 1e-9);
+    assert!((fit.curve.at(0.0)).abs() < 1e-9);
+}
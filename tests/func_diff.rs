@@ -0,0 +1,101 @@
+mod common;
+
+use common::{AllAlter, NoOp};
+use plots::{
+    functions::function::{
+        func_diff::{DiffKind, DiffRegion, FuncDiff},
+        Func,
+    },
+    graph::Graph,
+    history::{history_stack::shared_entry::ApplyOtherOp, History},
+    reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    types::point::{Point, X, Y},
+    ui::diff_view::DiffView,
+};
+
+fn region(original: std::ops::Range<usize>, current: std::ops::Range<usize>) -> DiffRegion {
+    DiffRegion { original, current }
+}
+
+#[test]
+fn changed_inserted_and_deleted_regions() {
+    let diff = FuncDiff::from_values(&[1.0, 2.0, 3.0, 4.0, 5.0], &[1.0, 2.0, 9.0, 4.0, 5.0]);
+    assert_eq!(diff.regions(), [region(2..3, 2..3)]);
+    assert_eq!(diff.regions()[0].kind(), DiffKind::Changed);
+    assert_eq!(
+        (diff.summary().changed, diff.summary().max_abs_deviation),
+        (1, 6.0)
+    );
+
+    let diff = FuncDiff::from_values(&[1.0, 2.0, 3.0], &[1.0, 2.0, 7.0, 8.0, 3.0]);
+    assert_eq!(diff.regions(), [region(2..2, 2..4)]);
+    assert_eq!(diff.regions()[0].kind(), DiffKind::Inserted);
+    assert_eq!(diff.summary().inserted, 2);
+
+    let diff = FuncDiff::from_values(&[1.0, 2.0, 3.0, 4.0], &[1.0, 4.0]);
+    assert_eq!(diff.regions(), [region(1..3, 1..1)]);
+    assert_eq!(diff.regions()[0].kind(), DiffKind::Deleted);
+    assert_eq!(diff.summary().deleted, 2);
+
+    let diff = FuncDiff::from_values(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[1.0, 9.0, 3.0, 4.0, 6.0]);
+    assert_eq!(diff.regions(), [region(1..2, 1..2), region(4..5, 4..4)]);
+    assert!(FuncDiff::from_values(&[1.0, Y::NAN], &[1.0, Y::NAN]).is_empty());
+}
+
+#[test]
+fn many_edits_fall_back_to_one_region() {
+    let original = (0..1000).map(|i| i as Y).collect::<Vec<_>>();
+    let changed = |count: usize| {
+        let mut current = original.clone();
+        for i in 0..count {
+            current[100 + 2 * i] = -1.0;
+        }
+        FuncDiff::from_values(&original, &current)
+    };
+    // Each changed value is one deletion and one insertion.
+    let exact = changed(200);
+    assert_eq!(exact.regions().len(), 200);
+    assert_eq!(exact.summary().changed, 200);
+
+    let fallback = changed(300);
+    assert_eq!(fallback.regions(), [region(100..699, 100..699)]);
+    assert_eq!(fallback.summary().changed, 300);
+}
+
+#[test]
+fn reverted_region_is_undone() {
+    let points = |values: &[Y]| {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, y)| Point::new(i as X, *y))
+            .collect::<Vec<_>>()
+    };
+    let values_of = |graph: &BasicReversibleGraph<(), NoOp, AllAlter>| {
+        let points = graph.graph().get_func(0).unwrap().points();
+        points.iter().map(|p| p.y).collect::<Vec<_>>()
+    };
+    let original = [0.0, 1.0, 2.0, 3.0, 4.0];
+    let graph = Graph::new(vec![Func::new_from(points(&original))]);
+    let mut view = DiffView::new(&graph);
+    let mut graph = BasicReversibleGraph::new(graph, History::new());
+
+    graph.action(()).replace_values(0, 1.0, 2, [9.0]);
+    let edited = values_of(&graph);
+    assert_eq!(edited, [0.0, 9.0, 3.0, 4.0]);
+
+    view.update(graph.graph());
+    let diff = view.diff(0).unwrap();
+    assert_eq!(diff.regions(), [region(1..3, 1..2)]);
+    let current = graph.graph().get_func(0).unwrap().points();
+    let (x, len, values) = view.revert_args(0, &diff.regions()[0], current).unwrap();
+    graph.action(()).replace_values(0, x, len, values);
+    assert_eq!(values_of(&graph), original);
+    view.update(graph.graph());
+    assert!(view.diff(0).unwrap().is_empty());
+
+    graph.undo(|_: ApplyOtherOp<()>| {});
+    assert_eq!(values_of(&graph), edited);
+    graph.undo(|_: ApplyOtherOp<()>| {});
+    assert_eq!(values_of(&graph), original);
+}
@@ -0,0 +1,442 @@
+use enumflags2::BitFlags;
+use plots::{
+    functions::function::{func_values_check_iter::FuncValuesCheckIter, Func, StretchY},
+    graph::Graph,
+    shared_op::{
+        Delete, FuncChange, FuncSelection, InsertPattern, InsertPolicy, InsertValues, SharedOp,
+    },
+    stretchers::y_stretcher::YStretcherFlags,
+    types::point::{Point, X, Y},
+};
+use proptest::prelude::*;
+use std::ops::RangeInclusive;
+
+const TOLERANCE: Y = 1e-6;
+
+type TestOp = SharedOp<
+    std::vec::IntoIter<usize>,
+    std::vec::IntoIter<std::vec::IntoIter<Y>>,
+    std::vec::IntoIter<Y>,
+>;
+
+fn points(start: i32, values: &[Y]) -> Vec<Point> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, y)| Point::new(start as X + i as X, *y))
+        .collect()
+}
+
+fn values(max_len: usize) -> impl Strategy<Value = Vec<Y>> {
+    prop::collection::vec(-1000.0..1000.0_f64, 0..max_len)
+}
+
+fn flags() -> impl Strategy<Value = BitFlags<YStretcherFlags>> {
+    prop_oneof![
+        Just(BitFlags::from(YStretcherFlags::Top)),
+        Just(BitFlags::from(YStretcherFlags::Bottom)),
+        Just(BitFlags::all()),
+    ]
+}
+
+/// Maps fraction from 0..=1 onto x range of function.
+fn x_at(points: &[Point], fraction: f64) -> X {
+    match (points.first(), points.last()) {
+        (Some(first), Some(last)) => (first.x + (last.x - first.x + 1.0) * fraction).floor(),
+        _ => 0.0,
+    }
+}
+
+fn check_func(f: &Func) -> Result<(), TestCaseError> {
+    let points = f.points();
+    for p in points {
+        prop_assert!(
+            p.x.is_finite() && p.y.is_finite(),
+            "point {:?} is not finite",
+            p
+        );
+    }
+    for w in points.windows(2) {
+        prop_assert!(w[0].x < w[1].x, "x is not increasing {:?}", w);
+    }
+    check_stats(points, f.min(), f.max(), "global")?;
+    check_stats(
+        f.points_selection(),
+        f.selection_min(),
+        f.selection_max(),
+        "selection",
+    )
+}
+
+fn check_stats(
+    points: &[Point],
+    min: Option<Y>,
+    max: Option<Y>,
+    label: &str,
+) -> Result<(), TestCaseError> {
+    if points.is_empty() {
+        return Ok(());
+    }
+    let real_min = points.iter().map(|p| p.y).fold(Y::INFINITY, Y::min);
+    let real_max = points.iter().map(|p| p.y).fold(Y::NEG_INFINITY, Y::max);
+    prop_assert_eq!(min, Some(real_min), "{} min", label);
+    prop_assert_eq!(max, Some(real_max), "{} max", label);
+    Ok(())
+}
+
+fn assert_points_eq(a: &[Point], b: &[Point]) -> Result<(), TestCaseError> {
+    prop_assert_eq!(a.len(), b.len(), "{:?} != {:?}", a, b);
+    for (a, b) in a.iter().zip(b) {
+        let tolerance = TOLERANCE * a.y.abs().max(1.0);
+        prop_assert!(
+            (a.x - b.x).abs() < TOLERANCE && (a.y - b.y).abs() < tolerance,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+enum FuncOp {
+    ChangeSelection(f64, f64),
+    InsertValues(f64, Vec<Y>),
+    InsertPattern(f64, Vec<Y>),
+    Delete,
+    StretchY(Y, BitFlags<YStretcherFlags>),
+    ReplaceValues(f64, usize, Vec<Y>),
+}
+
+fn func_op() -> impl Strategy<Value = FuncOp> {
+    prop_oneof![
+        (0.0..=1.0, 0.0..=1.0).prop_map(|(a, b)| FuncOp::ChangeSelection(a, b)),
+        (0.0..=1.0, values(20)).prop_map(|(x, v)| FuncOp::InsertValues(x, v)),
+        (0.0..=1.0, values(20)).prop_map(|(x, v)| FuncOp::InsertPattern(x, v)),
+        Just(FuncOp::Delete),
+        (-3.0..3.0, flags()).prop_map(|(f, flags)| FuncOp::StretchY(f, flags)),
+        (0.0..=1.0, 0..10_usize, values(10)).prop_map(|(x, l, v)| FuncOp::ReplaceValues(x, l, v)),
+    ]
+}
+
+fn apply_func_op(f: &mut Func, op: &FuncOp) {
+    let at = |f: &Func, fraction| x_at(f.points(), fraction);
+    match op {
+        FuncOp::ChangeSelection(a, b) => {
+            let (a, b) = (at(f, *a), at(f, *b));
+            f.change_selection(&(a.min(b)..=a.max(b)));
+        }
+        FuncOp::InsertValues(x, values) => {
+            let x = at(f, *x);
+            f.insert_values(FuncValuesCheckIter::new(values.iter().copied(), x).into());
+        }
+        FuncOp::InsertPattern(x, values) => {
+            let x = at(f, *x);
+            f.insert_pattern(FuncValuesCheckIter::new(values.iter().copied(), x).into());
+        }
+        FuncOp::Delete => {
+            f.delete();
+        }
+        FuncOp::StretchY(factor, flags) => {
+            if let Some(stretch) = StretchY::new(*factor, *flags) {
+                f.stretch_y_with_factor(&stretch);
+            }
+        }
+        FuncOp::ReplaceValues(x, len, values) => {
+            let x = at(f, *x);
+            f.replace_values(x, *len, values.iter().copied());
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum GraphOp {
+    Delete,
+    StretchY(Y, BitFlags<YStretcherFlags>),
+    InsertValues(f64, Vec<Vec<Y>>, InsertPolicy),
+    InsertPattern(f64, Vec<Y>, InsertPolicy),
+    DuplicateFunc(usize),
+    RemoveFunc(usize),
+    ToggleLock(usize),
+    /// Function and own selection as fractions of its x range
+    FuncSelection(usize, Option<(f64, f64)>),
+}
+
+fn policy() -> impl Strategy<Value = InsertPolicy> {
+    prop_oneof![
+        Just(InsertPolicy::Shift),
+        Just(InsertPolicy::Overwrite),
+        Just(InsertPolicy::Stretch),
+    ]
+}
+
+fn graph_op() -> impl Strategy<Value = GraphOp> {
+    prop_oneof![
+        Just(GraphOp::Delete),
+        // Only positive factors are invertible for one sided stretch, because negative factor
+        // moves points to the other side of stretch line.
+        (0.1..3.0, flags()).prop_map(|(f, flags)| GraphOp::StretchY(f, flags)),
+        (-3.0..-0.1).prop_map(|f| GraphOp::StretchY(f, BitFlags::all())),
+        (
+            0.0..=1.0,
+            prop::collection::vec(prop::collection::vec(-1000.0..1000.0_f64, 1..10), 1..4),
+            policy(),
+        )
+            .prop_map(|(x, v, policy)| GraphOp::InsertValues(x, v, policy)),
+        (
+            0.0..=1.0,
+            prop::collection::vec(-1000.0..1000.0_f64, 1..10),
+            policy()
+        )
+            .prop_map(|(x, v, policy)| GraphOp::InsertPattern(x, v, policy)),
+        (0..4_usize).prop_map(GraphOp::DuplicateFunc),
+        (0..4_usize).prop_map(GraphOp::RemoveFunc),
+        (0..4_usize).prop_map(GraphOp::ToggleLock),
+        (0..4_usize, prop::option::of((0.0..=1.0, 0.0..=1.0)))
+            .prop_map(|(index, s)| GraphOp::FuncSelection(index, s)),
+    ]
+}
+
+fn func_values(values: Vec<Box<[Y]>>) -> std::vec::IntoIter<std::vec::IntoIter<Y>> {
+    values
+        .into_iter()
+        .map(|v| v.into_vec().into_iter())
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+/// Applies operation on current state of graph and returns shared op, which describes it.
+fn apply_graph_op(graph: &mut Graph, op: &GraphOp) -> Option<TestOp> {
+    let at = x_at(
+        graph
+            .active_functions()
+            .next()
+            .map(|f| f.points())
+            .unwrap_or(&[]),
+        match op {
+            GraphOp::InsertValues(x, ..) | GraphOp::InsertPattern(x, ..) => *x,
+            _ => 0.0,
+        },
+    );
+    let op = match op {
+        GraphOp::Delete => {
+            let deleted = graph
+                .selection_points()
+                .map(|f| f.collect::<Vec<_>>().into_iter())
+                .collect::<Vec<_>>();
+            let op = SharedOp::Delete(Delete(deleted.into_iter()));
+            graph.redo_op(&op);
+            op
+        }
+        GraphOp::StretchY(factor, flags) => {
+            let op = SharedOp::StretchY(StretchY::new(*factor, *flags)?);
+            graph.redo_op(&op);
+            op
+        }
+        GraphOp::InsertValues(_, values, policy) => {
+            let values = values
+                .iter()
+                .take(graph.active_funcs_len())
+                .cloned()
+                .collect::<Vec<_>>();
+            let replaced =
+                graph.insert_values(at, values.iter().map(|v| v.iter().copied()), *policy);
+            SharedOp::InsertValues(InsertValues {
+                x: at,
+                values: func_values(values.into_iter().map(Into::into).collect()),
+                policy: *policy,
+                replaced: func_values(replaced),
+            })
+        }
+        GraphOp::InsertPattern(_, values, policy) => {
+            let replaced = graph.insert_pattern(at, values.iter().copied(), *policy);
+            SharedOp::InsertPattern(InsertPattern {
+                x: at,
+                values: values.clone().into_iter(),
+                policy: *policy,
+                replaced: func_values(replaced),
+            })
+        }
+        GraphOp::DuplicateFunc(index) => {
+            let index = index % graph.len().max(1);
+            let f = graph.get_func(index)?;
+            let change = FuncChange {
+                index: graph.len(),
+                id: graph.next_func_id(),
+                x: f.points().first().map(|p| p.x).unwrap_or_default(),
+                values: f
+                    .points()
+                    .iter()
+                    .map(|p| p.y)
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+                state: graph.get_func_state(index)?,
+                gaps: f.gaps().to_vec(),
+            };
+            let op = SharedOp::AddFunc(change);
+            graph.redo_op(&op);
+            op
+        }
+        GraphOp::RemoveFunc(index) => {
+            let index = index % graph.len().max(1);
+            let (id, f, state) = graph.remove_func(index)?;
+            SharedOp::RemoveFunc(FuncChange {
+                index,
+                id,
+                x: f.points().first().map(|p| p.x).unwrap_or_default(),
+                values: f
+                    .points()
+                    .iter()
+                    .map(|p| p.y)
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+                state,
+                gaps: f.gaps().to_vec(),
+            })
+        }
+        GraphOp::ToggleLock(index) => {
+            let index = index % graph.len().max(1);
+            let op = SharedOp::ChangeLockedFuncs(vec![index].into_iter());
+            graph.redo_op(&op);
+            op
+        }
+        GraphOp::FuncSelection(index, selection) => {
+            let index = index % graph.len().max(1);
+            let points = graph.get_func(index)?.points();
+            let new = selection.map(|(a, b)| {
+                let (a, b) = (x_at(points, a), x_at(points, b));
+                a.min(b)..=a.max(b)
+            });
+            let old = graph.set_func_selection(index, new.clone())?;
+            SharedOp::ChangeFuncSelection(FuncSelection {
+                func: index,
+                old,
+                new,
+            })
+        }
+    };
+    Some(op)
+}
+
+fn graph_values() -> impl Strategy<Value = (i32, Vec<Vec<Y>>, f64, f64)> {
+    (-50..50_i32, 1..4_usize).prop_flat_map(|(start, count)| {
+        (
+            Just(start),
+            prop::collection::vec(prop::collection::vec(-1000.0..1000.0_f64, 3..40), count),
+            0.0..=1.0,
+            0.0..=1.0,
+        )
+    })
+}
+
+fn new_graph((start, values, a, b): &(i32, Vec<Vec<Y>>, f64, f64)) -> Graph {
+    let mut graph = Graph::new(
+        values
+            .iter()
+            .map(|v| Func::new_from(points(*start, v)))
+            .collect(),
+    );
+    let first = graph.functions().next().unwrap().points();
+    let (a, b) = (x_at(first, *a), x_at(first, *b));
+    graph.change_selection(a.min(b)..=a.max(b));
+    graph
+}
+
+fn graph_points(graph: &Graph) -> Vec<Vec<Point>> {
+    graph.functions().map(|f| f.points().to_vec()).collect()
+}
+
+/// Id, state, lock and own selection of each function.
+fn graph_funcs(graph: &Graph) -> Vec<(Option<usize>, bool, bool, Option<RangeInclusive<X>>)> {
+    (0..graph.len())
+        .map(|i| {
+            (
+                graph.func_id(i),
+                graph.active_func_indexes().any(|a| a == i),
+                graph.is_locked(i) == Some(true),
+                graph.func_selection(i).cloned(),
+            )
+        })
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn new_func_keeps_invariants(start in -50..50_i32, values in values(50)) {
+        let mut f = Func::new_from(points(start, &values));
+        let all = x_at(f.points(), 0.0)..=x_at(f.points(), 1.0);
+        f.change_selection(&all);
+        check_func(&f)?;
+    }
+
+    #[test]
+    fn func_ops_keep_invariants(
+        start in -50..50_i32,
+        values in values(50),
+        ops in prop::collection::vec(func_op(), 1..20),
+    ) {
+        let mut f = Func::new_from(points(start, &values));
+        let all = x_at(f.points(), 0.0)..=x_at(f.points(), 1.0);
+        f.change_selection(&all);
+        for op in ops.iter() {
+            apply_func_op(&mut f, op);
+            check_func(&f)?;
+        }
+    }
+
+    #[test]
+    fn graph_undo_after_redo_restores_points(
+        values in graph_values(),
+        ops in prop::collection::vec(graph_op(), 1..10),
+    ) {
+        let mut graph = new_graph(&values);
+        for op in ops.iter() {
+            let before = graph_points(&graph);
+            let funcs_before = graph_funcs(&graph);
+            let op = match apply_graph_op(&mut graph, op) {
+                Some(op) => op,
+                None => continue,
+            };
+            for f in graph.functions() {
+                check_func(f)?;
+            }
+            let applied = graph_points(&graph);
+            graph.undo_op(&op);
+            for f in graph.functions() {
+                check_func(f)?;
+            }
+            prop_assert_eq!(&funcs_before, &graph_funcs(&graph));
+            for (before, after) in before.iter().zip(graph_points(&graph)) {
+                assert_points_eq(before, &after)?;
+            }
+            graph.redo_op(&op);
+            prop_assert_eq!(applied.len(), graph.len());
+            for (applied, redone) in applied.iter().zip(graph_points(&graph)) {
+                assert_points_eq(applied, &redone)?;
+            }
+            graph.undo_op(&op);
+        }
+    }
+
+    #[test]
+    fn locked_func_is_not_modified(
+        values in graph_values(),
+        locked in 0..4_usize,
+        ops in prop::collection::vec(graph_op(), 1..10),
+    ) {
+        let mut graph = new_graph(&values);
+        let locked = locked % graph.len();
+        graph.set_locked(locked, true);
+        let id = graph.func_id(locked);
+        let points = graph.get_func(locked).unwrap().points().to_vec();
+        for op in ops.iter() {
+            if matches!(op, GraphOp::ToggleLock(_)) {
+                continue;
+            }
+            apply_graph_op(&mut graph, op);
+            let index = (0..graph.len()).find(|i| graph.func_id(*i) == id);
+            prop_assert!(index.is_some(), "locked function was removed");
+            assert_points_eq(&points, graph.get_func(index.unwrap()).unwrap().points())?;
+        }
+    }
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use common::{apply_legend, AllAlter, LegendOp, TestId};
+use eframe::egui::Color32;
+use plots::{
+    functions::function::Func,
+    graph::Graph,
+    history::History,
+    reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    types::point::{Point, X, Y},
+    widgets::legend_widget::{
+        simple_legend_entries::{SimpleLegendEntries, SimpleLegendEntry},
+        LegendEntries,
+    },
+};
+
+type TestGraph = BasicReversibleGraph<TestId, LegendOp, AllAlter>;
+
+/// Id, legend name and points of each function and legend order.
+type Snapshot = (Vec<(usize, String, Vec<(X, Y)>)>, Vec<usize>);
+
+struct App {
+    graph: TestGraph,
+    entries: Vec<SimpleLegendEntry>,
+    order: Vec<usize>,
+}
+
+impl App {
+    fn new() -> Self {
+        let func = |values: &[Y]| {
+            Func::new_from(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, y)| Point::new(i as X, *y))
+                    .collect(),
+            )
+        };
+        let graph = Graph::new(vec![func(&[1.0, 2.0, 3.0]), func(&[4.0, 5.0])]);
+        Self {
+            graph: BasicReversibleGraph::new(graph, History::new()),
+            entries: ["a", "b"]
+                .map(|name| SimpleLegendEntry::new(name.to_string(), Color32::RED, false))
+                .into_iter()
+                .collect(),
+            order: vec![1, 0],
+        }
+    }
+
+    fn legend(&mut self) -> SimpleLegendEntries<'_, TestId, LegendOp, AllAlter> {
+        SimpleLegendEntries::new(&mut self.graph, &mut self.entries, &mut self.order)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let graph = self.graph.graph();
+        let funcs = (0..graph.len())
+            .map(|fi| {
+                let points = graph.get_func(fi).unwrap().points();
+                (
+                    graph.func_id(fi).unwrap(),
+                    self.entries[fi].name.clone(),
+                    points.iter().map(|p| (p.x, p.y)).collect(),
+                )
+            })
+            .collect();
+        (funcs, self.order.clone())
+    }
+
+    fn undo(&mut self) {
+        let (entries, order) = (&mut self.entries, &mut self.order);
+        self.graph.undo(|op| apply_legend(op, entries, order));
+    }
+
+    fn redo(&mut self) {
+        let (entries, order) = (&mut self.entries, &mut self.order);
+        self.graph.redo(|op| apply_legend(op, entries, order));
+    }
+}
+
+fn func(id: usize, name: &str, values: &[Y]) -> (usize, String, Vec<(X, Y)>) {
+    let points = values.iter().enumerate().map(|(i, y)| (i as X, *y));
+    (id, name.to_string(), points.collect())
+}
+
+#[test]
+fn new_duplicated_and_deleted_functions_are_undone_and_redone() {
+    let mut app = App::new();
+    let mut snapshots = vec![app.snapshot()];
+
+    let style = SimpleLegendEntry::new("c".to_string(), Color32::BLUE, false).style();
+    app.legend().add_func(style, 0.0, [7.0, 8.0]);
+    snapshots.push(app.snapshot());
+    assert_eq!(
+        snapshots[1],
+        (
+            vec![
+                func(0, "a", &[1.0, 2.0, 3.0]),
+                func(1, "b", &[4.0, 5.0]),
+                func(2, "c", &[7.0, 8.0]),
+            ],
+            vec![1, 0, 2]
+        )
+    );
+
+    // Copy gets new id and is shown right after the original.
+    app.legend().duplicate(0);
+    snapshots.push(app.snapshot());
+    assert_eq!(
+        snapshots[2],
+        (
+            vec![
+                func(0, "a", &[1.0, 2.0, 3.0]),
+                func(1, "b", &[4.0, 5.0]),
+                func(2, "c", &[7.0, 8.0]),
+                func(3, "a copy", &[1.0, 2.0, 3.0]),
+            ],
+            vec![1, 0, 3, 2]
+        )
+    );
+
+    // Functions after the removed one move down, ids stay.
+    app.legend().remove(1);
+    snapshots.push(app.snapshot());
+    assert_eq!(
+        snapshots[3],
+        (
+            vec![
+                func(0, "a", &[1.0, 2.0, 3.0]),
+                func(2, "c", &[7.0, 8.0]),
+                func(3, "a copy", &[1.0, 2.0, 3.0]),
+            ],
+            vec![0, 2, 1]
+        )
+    );
+
+    for expected in snapshots.iter().rev().skip(1) {
+        app.undo();
+        assert_eq!(&app.snapshot(), expected);
+    }
+    for expected in snapshots.iter().skip(1) {
+        app.redo();
+        assert_eq!(&app.snapshot(), expected);
+    }
+    // Id of the next function is not reused after undo.
+    assert_eq!(app.graph.graph().next_func_id(), 4);
+}
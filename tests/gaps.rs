@@ -0,0 +1,113 @@
+mod common;
+
+use common::{AllAlter, NoOp};
+use plots::{
+    functions::function::{func_builder::FuncBuilder, gap_fill::GapFill, Func},
+    graph::Graph,
+    history::{history_stack::shared_entry::ApplyOtherOp, History},
+    reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    shared_op::InsertPolicy,
+    types::point::{Point, X, Y},
+};
+
+fn func(values: &[Y], gaps: Vec<std::ops::Range<X>>) -> Func {
+    let mut func = Func::new_from(
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, y)| Point::new(i as X, *y))
+            .collect(),
+    );
+    func.set_gaps(gaps);
+    func
+}
+
+#[test]
+fn builder_records_gaps_over_threshold() {
+    let mut builder = FuncBuilder::new().with_gap_threshold(2.0);
+    for (x, y) in [(0.0, 1.0), (1.0, 2.0), (5.0, 6.0), (7.0, 4.0), (8.0, 5.0)] {
+        builder.add_point(&Point::new(x, y)).unwrap();
+    }
+    let func: Func = builder.into();
+    assert_eq!(func.gaps(), &[1.0..5.0]);
+    assert_eq!(func.gap_index_range(&func.gaps()[0]), 2..5);
+    assert!(func.is_in_gap(3.0));
+    assert!(!func.is_in_gap(5.0));
+    assert!(!func.is_in_gap(6.0));
+}
+
+#[test]
+fn fill_strategies() {
+    // Period of 4 samples, the second period is missing.
+    let values = [1.0, 2.0, 3.0, 4.0, 4.0, 0.0, 0.0, 0.0, 3.0, 4.0, 5.0, 6.0];
+    let f = func(&values, vec![4.0..8.0]);
+    let gap = 4.0..8.0;
+    let fill = |fill: GapFill| fill.values(&f, &gap);
+    let (x, empty) = fill(GapFill::Empty).unwrap();
+    assert!(x == 5.0 && empty.len() == 3 && empty.iter().all(|y| y.is_nan()));
+    assert_eq!(fill(GapFill::Linear), Some((5.0, vec![3.75, 3.5, 3.25])));
+    assert_eq!(fill(GapFill::Previous), Some((5.0, vec![4.0, 4.0, 4.0])));
+    assert_eq!(
+        fill(GapFill::PreviousPeriod(4.0)),
+        Some((5.0, vec![2.0, 3.0, 4.0]))
+    );
+    assert_eq!(
+        fill(GapFill::Seasonal(4.0)),
+        Some((5.0, vec![3.0, 4.0, 5.0]))
+    );
+    assert_eq!(GapFill::PreviousPeriod(8.0).values(&f, &gap), None);
+}
+
+#[test]
+fn gaps_move_with_deleted_points() {
+    let mut f = func(&[0.0; 10], vec![2.0..5.0, 7.0..9.0]);
+    let delete = |f: &mut Func, selection: std::ops::RangeInclusive<X>| {
+        f.change_selection(&selection);
+        f.delete();
+    };
+    delete(&mut f, 0.0..=0.0);
+    assert_eq!(f.gaps(), &[1.0..4.0, 6.0..8.0]);
+    // Interpolated point inside gap.
+    delete(&mut f, 2.0..=2.0);
+    assert_eq!(f.gaps(), &[1.0..3.0, 5.0..7.0]);
+    // Gap with both of its samples.
+    delete(&mut f, 1.0..=3.0);
+    assert_eq!(f.gaps(), &[2.0..4.0]);
+    // Points to the end.
+    delete(&mut f, 3.0..=4.0);
+    assert_eq!(f.gaps(), &[2.0..3.0]);
+}
+
+#[test]
+fn gaps_follow_edits_and_undo() {
+    let mut graph = Graph::new(vec![func(&[0.0; 10], vec![4.0..7.0])]);
+    graph.change_selection(1.0..=2.0);
+    let mut graph = BasicReversibleGraph::new(graph, History::<(), NoOp, AllAlter>::new());
+    let gaps = |graph: &BasicReversibleGraph<(), NoOp, AllAlter>, index| {
+        graph.graph().get_func(index).unwrap().gaps().to_vec()
+    };
+
+    graph.action(()).delete();
+    assert_eq!(gaps(&graph, 0), [2.0..5.0]);
+    graph.undo(|_: ApplyOtherOp<()>| {});
+    assert_eq!(gaps(&graph, 0), [4.0..7.0]);
+
+    graph
+        .action(())
+        .insert_values(5.0, [[1.0, 1.0].into_iter()], InsertPolicy::Shift);
+    assert_eq!(gaps(&graph, 0), [4.0..9.0]);
+    graph.undo(|_: ApplyOtherOp<()>| {});
+    assert_eq!(gaps(&graph, 0), [4.0..7.0]);
+
+    // Overwritten values keep x, so do gaps.
+    graph
+        .action(())
+        .insert_values(5.0, [[1.0].into_iter()], InsertPolicy::Overwrite);
+    assert_eq!(gaps(&graph, 0), [4.0..7.0]);
+
+    assert_eq!(graph.action(()).duplicate_func(0), Some(1));
+    assert_eq!(gaps(&graph, 1), [4.0..7.0]);
+    graph.action(()).remove_func(0);
+    graph.undo(|_: ApplyOtherOp<()>| {});
+    assert_eq!(gaps(&graph, 0), [4.0..7.0]);
+}
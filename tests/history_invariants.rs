@@ -0,0 +1,79 @@
+mod history_model;
+
+use enumflags2::BitFlags;
+use history_model::{Action, Harness, Id, Options, Step, TestOp};
+use plots::stretchers::y_stretcher::YStretcherFlags;
+use proptest::prelude::*;
+
+fn id() -> impl Strategy<Value = Id> {
+    prop_oneof![Just(Id::A), Just(Id::B), Just(Id::View)]
+}
+
+fn action() -> impl Strategy<Value = Action> {
+    let factor = prop_oneof![Just(0.25), Just(0.5), Just(2.0), Just(4.0)];
+    prop_oneof![
+        (factor.clone(), 0..3_u8).prop_map(|(f, flags)| match flags {
+            0 => Action::StretchY(f, YStretcherFlags::Top.into()),
+            1 => Action::StretchY(f, YStretcherFlags::Bottom.into()),
+            _ => Action::StretchY(f, BitFlags::all()),
+        }),
+        factor.prop_map(|f| Action::StretchY(-f, BitFlags::all())),
+        (-3..=3_i8).prop_map(Action::MoveSelectBy),
+        (0..3_usize).prop_map(Action::ToggleFunc),
+        Just(Action::Delete),
+        (-1..=3_i8, prop::collection::vec(-10.0..10.0_f64, 0..4))
+            .prop_map(|(offset, values)| Action::InsertPattern(offset, values)),
+        (-2..=2_i64).prop_map(|add| Action::Other(TestOp::Add(add))),
+        any::<u8>().prop_map(|value| Action::Other(TestOp::Push(value))),
+    ]
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    let actions = || prop::collection::vec(action(), 0..4);
+    prop_oneof![
+        3 => (id(), actions()).prop_map(|(id, a)| Step::AddEntry(id, a)),
+        3 => (id(), actions()).prop_map(|(id, a)| Step::OpenEntry(id, a)),
+        1 => id().prop_map(Step::CloseEntry),
+        2 => Just(Step::Undo),
+        2 => Just(Step::Redo),
+    ]
+}
+
+fn options() -> impl Strategy<Value = Options> {
+    (any::<bool>(), prop::option::of(1..6_usize)).prop_map(|(regular, max_size)| Options {
+        treat_non_altering_as_regular: regular,
+        max_size,
+    })
+}
+
+proptest! {
+    #[test]
+    fn history_matches_model(
+        options in options(),
+        steps in prop::collection::vec(step(), 1..40),
+    ) {
+        if let Err(e) = Harness::run(options, &steps) {
+            prop_assert!(false, "{}", e);
+        }
+    }
+
+    #[test]
+    fn history_matches_model_with_clear(
+        steps in prop::collection::vec(
+            prop_oneof![10 => step(), 1 => Just(Step::Clear)],
+            1..40,
+        ),
+    ) {
+        if let Err(e) = Harness::run(Options::default(), &steps) {
+            prop_assert!(false, "{}", e);
+        }
+    }
+
+    #[test]
+    fn decoded_fuzz_input_matches_model(data in prop::collection::vec(any::<u8>(), 0..200)) {
+        let (options, steps) = history_model::decode(&data);
+        if let Err(e) = Harness::run(options, &steps) {
+            prop_assert!(false, "{}", e);
+        }
+    }
+}
@@ -0,0 +1,708 @@
+//! Model based harness for `History`, shared by integration tests and the fuzz target.
+//!
+//! Steps are applied to real `Graph` and state of other ops and recorded into `History`.
+//! Reference model keeps snapshots of state before and after each entry, so undo and redo
+//! can be checked without knowing, how `History` combines ops.
+
+use std::ops::RangeInclusive;
+
+use enumflags2::BitFlags;
+use plots::{
+    functions::function::{Func, StretchY},
+    graph::{Graph, GraphFuncState},
+    history::{
+        history_stack::{
+            shared_entry::{
+                ApplyGraphOp, ApplyOp, ApplyOtherOp, InOp, OpCombineErr, OpCreateErr,
+                OrderMattersOp, OtherOp, OwnedOp,
+            },
+            HistoryOption, IsGraphOpNonAltering,
+        },
+        History,
+    },
+    shared_op::{Delete, InsertPattern, InsertPolicy, MoveSelectBy, SharedOp},
+    stretchers::y_stretcher::YStretcherFlags,
+    types::point::{Point, X, Y},
+};
+
+const TOLERANCE: Y = 1e-6;
+const FUNCS: usize = 3;
+
+type TestSharedOp = SharedOp<
+    std::vec::IntoIter<usize>,
+    std::vec::IntoIter<std::vec::IntoIter<Y>>,
+    std::vec::IntoIter<Y>,
+>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Id {
+    #[default]
+    A,
+    B,
+    /// Changes of active functions and selection recorded with this id do not alter history.
+    View,
+}
+
+#[derive(Default)]
+pub struct NonAlteringHelper;
+
+impl IsGraphOpNonAltering<Id> for NonAlteringHelper {
+    fn graph_op_alters_history<IterChangeActiveFuncs, FuncIter, YExactIter>(
+        g_op: &SharedOp<IterChangeActiveFuncs, FuncIter, YExactIter>,
+        group_id: &Id,
+    ) -> bool
+    where
+        IterChangeActiveFuncs: Iterator<Item = usize> + Clone,
+        FuncIter: Iterator<Item = YExactIter>,
+        YExactIter: ExactSizeIterator<Item = Y> + Clone,
+    {
+        *group_id != Id::View
+            || !matches!(
+                g_op,
+                SharedOp::ChangeActiveFuncs(_) | SharedOp::MoveSelectBy(_)
+            )
+    }
+}
+
+/// `Add` changes counter and is combined with other `Add` ops,
+/// `Push` pushes value to a stack, so the order of `Push` ops matters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TestOp {
+    Add(i64),
+    Push(u8),
+}
+
+impl OtherOp for TestOp {
+    fn order_to_graph_matters<IterChangeActiveFuncs, FuncIter, YExactIter>(
+        &self,
+        _other: &SharedOp<IterChangeActiveFuncs, FuncIter, YExactIter>,
+    ) -> bool
+    where
+        IterChangeActiveFuncs: Iterator<Item = usize> + Clone,
+        FuncIter: Iterator<Item = YExactIter>,
+        YExactIter: ExactSizeIterator<Item = Y> + Clone,
+    {
+        matches!(self, Self::Push(_))
+    }
+}
+
+impl OwnedOp<TestOp> for TestOp {
+    fn get_shared(&self) -> TestOp {
+        *self
+    }
+}
+
+pub struct InTestOp(TestOp);
+
+impl TryFrom<InTestOp> for TestOp {
+    type Error = OpCreateErr;
+
+    fn try_from(op: InTestOp) -> Result<Self, Self::Error> {
+        match op.0 {
+            TestOp::Add(0) => Err(OpCreateErr::OpDoesNotHaveEffect),
+            op => Ok(op),
+        }
+    }
+}
+
+impl InOp<TestOp, Id> for InTestOp {
+    fn try_combine(self, owned: &mut TestOp) -> Result<(), OpCombineErr<Self>> {
+        match (self.0, owned) {
+            (TestOp::Add(0), _) => Err(OpCombineErr::OpDoesNotHaveEffect),
+            (TestOp::Add(add), TestOp::Add(owned)) => {
+                *owned += add;
+                Ok(())
+            }
+            _ => Err(OpCombineErr::CannotCombine(self)),
+        }
+    }
+
+    fn order_matters<'a>(&self, other: OrderMattersOp<'a, TestOp>) -> bool {
+        match other {
+            OrderMattersOp::Graph(_) => matches!(self.0, TestOp::Push(_)),
+            OrderMattersOp::Other(other) => {
+                matches!((self.0, other), (TestOp::Push(_), TestOp::Push(_)))
+            }
+        }
+    }
+
+    fn alters_history(&self, id: &Id) -> bool {
+        *id != Id::View
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Factor and flags, one sided stretch only with positive factor,
+    /// because stretch with negative factor moves points to the other side.
+    StretchY(Y, BitFlags<YStretcherFlags>),
+    MoveSelectBy(i8),
+    ToggleFunc(usize),
+    Delete,
+    /// Offset from the start of the selection and values
+    InsertPattern(i8, Vec<Y>),
+    Other(TestOp),
+}
+
+#[derive(Debug, Clone)]
+pub enum Step {
+    AddEntry(Id, Vec<Action>),
+    OpenEntry(Id, Vec<Action>),
+    CloseEntry(Id),
+    Undo,
+    Redo,
+    Clear,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    pub treat_non_altering_as_regular: bool,
+    pub max_size: Option<usize>,
+}
+
+/// Observable state of graph and other ops.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    funcs: Vec<Vec<Point>>,
+    selection: RangeInclusive<X>,
+    active: Vec<usize>,
+    counter: i64,
+    pushed: Vec<u8>,
+}
+
+impl Snapshot {
+    fn check_eq(&self, other: &Self, context: &str) -> Result<(), String> {
+        let err = || Err(format!("{context}:\n{self:?}\n!=\n{other:?}"));
+        if self.active != other.active
+            || self.counter != other.counter
+            || self.pushed != other.pushed
+            || self.funcs.len() != other.funcs.len()
+            || (self.selection.start() - other.selection.start()).abs() > TOLERANCE
+            || (self.selection.end() - other.selection.end()).abs() > TOLERANCE
+        {
+            return err();
+        }
+        for (a, b) in self.funcs.iter().zip(other.funcs.iter()) {
+            if a.len() != b.len() {
+                return err();
+            }
+            let scale = a
+                .iter()
+                .chain(b.iter())
+                .map(|p| p.y.abs())
+                .fold(1.0, Y::max);
+            let differs = a.iter().zip(b.iter()).any(|(a, b)| {
+                (a.x - b.x).abs() > TOLERANCE || (a.y - b.y).abs() > TOLERANCE * scale
+            });
+            if differs {
+                return err();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// State, onto which ops from history are applied.
+struct State {
+    graph: Graph,
+    counter: i64,
+    pushed: Vec<u8>,
+}
+
+impl State {
+    fn new() -> Self {
+        let funcs = (0..FUNCS)
+            .map(|fi| {
+                let points = (0..10)
+                    .map(|i| Point::new(i as X, ((i * 7 + fi * 3) % 11) as Y - 5.0))
+                    .collect::<Vec<_>>();
+                Func::new_from(points)
+            })
+            .collect();
+        let mut graph = Graph::new(funcs);
+        graph.change_selection(2.0..=6.0);
+        Self {
+            graph,
+            counter: 0,
+            pushed: Vec::new(),
+        }
+    }
+
+    fn from_snapshot(snapshot: &Snapshot) -> Self {
+        let mut graph = Graph::new(
+            snapshot
+                .funcs
+                .iter()
+                .map(|points| Func::new_from(points.clone()))
+                .collect(),
+        );
+        graph.change_selection(snapshot.selection.clone());
+        for fi in 0..snapshot.funcs.len() {
+            if !snapshot.active.contains(&fi) {
+                graph.change_func_state(fi, GraphFuncState::Inactive);
+            }
+        }
+        Self {
+            graph,
+            counter: snapshot.counter,
+            pushed: snapshot.pushed.clone(),
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            funcs: self
+                .graph
+                .functions()
+                .map(|f| f.points().to_vec())
+                .collect(),
+            selection: self.graph.selection().clone(),
+            active: (0..self.graph.len())
+                .filter(|fi| self.graph.get_func_state(*fi) == Some(GraphFuncState::Active))
+                .collect(),
+            counter: self.counter,
+            pushed: self.pushed.clone(),
+        }
+    }
+
+    /// Creates op, which describes given action on current state.
+    fn graph_op(&self, action: &Action) -> Option<TestSharedOp> {
+        let op = match action {
+            Action::StretchY(factor, flags) => SharedOp::StretchY(StretchY::new(*factor, *flags)?),
+            Action::MoveSelectBy(by) => SharedOp::MoveSelectBy(MoveSelectBy {
+                start_by: *by as X,
+                end_by: *by as X,
+            }),
+            Action::ToggleFunc(fi) => SharedOp::ChangeActiveFuncs(vec![*fi % FUNCS].into_iter()),
+            Action::Delete => {
+                let deleted = self
+                    .graph
+                    .selection_points()
+                    .map(|f| f.collect::<Vec<_>>())
+                    .collect::<Vec<_>>();
+                // Delete op does not record x of deleted points, so deleting all points
+                // of a function cannot be undone at the same x.
+                let deletes_all = self
+                    .graph
+                    .active_functions()
+                    .zip(deleted.iter())
+                    .any(|(f, deleted)| !deleted.is_empty() && deleted.len() == f.points().len());
+                if deletes_all {
+                    return None;
+                }
+                SharedOp::Delete(Delete(
+                    deleted
+                        .into_iter()
+                        .map(|d| d.into_iter())
+                        .collect::<Vec<_>>()
+                        .into_iter(),
+                ))
+            }
+            Action::InsertPattern(offset, values) => SharedOp::InsertPattern(InsertPattern {
+                x: self.graph.selection().start() + *offset as X,
+                values: values.clone().into_iter(),
+                policy: InsertPolicy::Shift,
+                replaced: Vec::new().into_iter(),
+            }),
+            Action::Other(_) => return None,
+        };
+        Some(op)
+    }
+
+    fn apply_other(&mut self, op: ApplyOtherOp<TestOp>) {
+        match op {
+            ApplyOtherOp::Redo(TestOp::Add(add)) => self.counter += add,
+            ApplyOtherOp::Undo(TestOp::Add(add)) => self.counter -= add,
+            ApplyOtherOp::Redo(TestOp::Push(value)) => self.pushed.push(value),
+            ApplyOtherOp::Undo(TestOp::Push(_)) => {
+                self.pushed.pop();
+            }
+        }
+    }
+
+    fn apply<'a>(&mut self, ops: impl Iterator<Item = ApplyOp<'a, TestOp>>) {
+        for op in ops {
+            match op {
+                ApplyOp::Graph(ApplyGraphOp::Undo(op)) => self.graph.undo_op(&op),
+                ApplyOp::Graph(ApplyGraphOp::Redo(op)) => self.graph.redo_op(&op),
+                ApplyOp::Other(op) => self.apply_other(op),
+            }
+        }
+    }
+}
+
+/// Whether op would be recorded into history, if it could not be combined.
+fn has_effect(op: &TestSharedOp) -> bool {
+    match op {
+        SharedOp::Delete(delete) => delete.0.len() > 0,
+        SharedOp::InsertPattern(insert) => insert.values.len() > 0,
+        SharedOp::MoveSelectBy(move_by) => move_by.is_move(),
+        SharedOp::StretchY(stretch) => stretch.stretches(),
+        SharedOp::ChangeActiveFuncs(change) | SharedOp::ChangeLockedFuncs(change) => {
+            change.len() > 0
+        }
+        SharedOp::ChangeFuncSelection(change) => change.old != change.new,
+        SharedOp::InsertValues(_)
+        | SharedOp::ReplaceValues(_)
+        | SharedOp::AddFunc(_)
+        | SharedOp::RemoveFunc(_) => true,
+    }
+}
+
+/// Reference model of history.
+#[derive(Default)]
+struct Model {
+    /// Entries, which can be undone, with state before them
+    undo: Vec<(Id, Snapshot)>,
+    /// Entries, which can be redone, with state after them, the last one is redone first
+    redo: Vec<(Id, Snapshot)>,
+    /// Entries recorded while there were redo entries, with state before them
+    non_altering: Vec<(Id, Snapshot)>,
+    /// Id of the last entry, if it was opened and not closed yet
+    open: Option<Id>,
+}
+
+impl Model {
+    fn len(&self) -> usize {
+        self.undo.len() + self.redo.len() + self.non_altering.len()
+    }
+
+    fn no_redo(&mut self) {
+        if !self.redo.is_empty() {
+            self.redo.clear();
+            self.undo.append(&mut self.non_altering);
+        }
+    }
+
+    /// State of regular entries, before non altering entries were recorded.
+    fn regular_state<'a>(&'a self, current: &'a Snapshot) -> &'a Snapshot {
+        self.non_altering.first().map_or(current, |(_, s)| s)
+    }
+}
+
+pub struct Harness {
+    history: History<Id, TestOp, NonAlteringHelper>,
+    state: State,
+    model: Model,
+    options: Options,
+}
+
+impl Harness {
+    pub fn new(options: Options) -> Self {
+        let mut history = History::new();
+        if options.treat_non_altering_as_regular {
+            history.with_options(HistoryOption::TreatNonAlteringEntriesAsRegular);
+        }
+        if let Some(max_size) = options.max_size {
+            history.with_max_size(max_size);
+        }
+        Self {
+            history,
+            state: State::new(),
+            model: Model::default(),
+            options,
+        }
+    }
+
+    pub fn run(options: Options, steps: &[Step]) -> Result<(), String> {
+        let mut harness = Self::new(options);
+        for (i, step) in steps.iter().enumerate() {
+            harness
+                .step(step)
+                .and_then(|_| harness.check())
+                .map_err(|e| format!("step {i} {step:?}: {e}"))?;
+        }
+        Ok(())
+    }
+
+    pub fn step(&mut self, step: &Step) -> Result<(), String> {
+        match step {
+            Step::AddEntry(id, actions) => self.entry(*id, actions, false),
+            Step::OpenEntry(id, actions) => self.entry(*id, actions, true),
+            Step::CloseEntry(id) => {
+                self.history.close_entry(*id);
+                if self.model.open == Some(*id) {
+                    self.model.open = None;
+                }
+            }
+            Step::Undo => return self.undo(),
+            Step::Redo => return self.redo(),
+            Step::Clear => {
+                self.history.clear();
+                self.model = Model::default();
+            }
+        }
+        Ok(())
+    }
+
+    fn entry(&mut self, id: Id, actions: &[Action], open: bool) {
+        let continues = open && self.model.open == Some(id);
+        if !continues {
+            self.model.open = None;
+            let full = self
+                .options
+                .max_size
+                .is_some_and(|max| self.model.len() >= max);
+            if full && !self.model.undo.is_empty() {
+                self.model.undo.remove(0);
+            }
+        }
+        let before = self.state.snapshot();
+        let mut has_ops = false;
+        let mut entry = if open {
+            self.history.open_entry(id)
+        } else {
+            self.history.add_entry(id)
+        };
+        for action in actions {
+            let alters = if let Action::Other(op) = action {
+                has_ops |= *op != TestOp::Add(0);
+                self.state.apply_other(ApplyOtherOp::Redo(*op));
+                entry.add_other_op(InTestOp(*op));
+                id != Id::View
+            } else {
+                let Some(op) = self.state.graph_op(action) else {
+                    continue;
+                };
+                has_ops |= has_effect(&op);
+                let alters = NonAlteringHelper::graph_op_alters_history(&op, &id);
+                self.state.graph.redo_op(&op);
+                entry.add_graph_op(op);
+                alters
+            };
+            if alters {
+                self.model.no_redo();
+            }
+        }
+        drop(entry);
+        if has_ops && !continues {
+            if self.model.redo.is_empty() {
+                self.model.undo.push((id, before));
+            } else {
+                self.model.non_altering.push((id, before));
+            }
+        }
+        if open && !continues && has_ops {
+            self.model.open = Some(id);
+        }
+    }
+
+    fn undo(&mut self) -> Result<(), String> {
+        self.model.open = None;
+        let current = self.state.snapshot();
+        let applied = match self.history.undo_entry() {
+            Some(entry) => {
+                self.state.apply(entry.iter());
+                Some(*entry.id())
+            }
+            None => None,
+        };
+        let Some((id, before)) = self.model.undo.pop() else {
+            return match applied {
+                Some(id) => Err(format!("undo of {id:?}, which should not exist")),
+                None => Ok(()),
+            };
+        };
+        if applied != Some(id) {
+            return Err(format!("undo of {applied:?}, expected {id:?}"));
+        }
+        let after = self.model.regular_state(&current).clone();
+        self.model.non_altering.clear();
+        self.model.redo.push((id, after));
+        self.state.snapshot().check_eq(&before, "state after undo")
+    }
+
+    fn redo(&mut self) -> Result<(), String> {
+        self.model.open = None;
+        let current = self.state.snapshot();
+        let applied = match self.history.redo_entry() {
+            Some(entry) => {
+                self.state.apply(entry.iter());
+                Some(*entry.id())
+            }
+            None => None,
+        };
+        let Some((id, after)) = self.model.redo.pop() else {
+            return match applied {
+                Some(id) => Err(format!("redo of {id:?}, which should not exist")),
+                None => Ok(()),
+            };
+        };
+        if applied != Some(id) {
+            return Err(format!("redo of {applied:?}, expected {id:?}"));
+        }
+        let before = self.model.regular_state(&current).clone();
+        self.model.non_altering.clear();
+        self.model.undo.push((id, before));
+        self.state.snapshot().check_eq(&after, "state after redo")
+    }
+
+    /// Compares entries listed by `History` with the model and checks, that applying their ops
+    /// one after another leads to states recorded in the model.
+    pub fn check(&self) -> Result<(), String> {
+        let model = &self.model;
+        if self.history.undo_len() != model.undo.len() {
+            return Err(format!(
+                "undo len {} != {}",
+                self.history.undo_len(),
+                model.undo.len()
+            ));
+        }
+        if self.history.redo_len() != model.redo.len() {
+            return Err(format!(
+                "redo len {} != {}",
+                self.history.redo_len(),
+                model.redo.len()
+            ));
+        }
+        let listed_non_altering = if self.options.treat_non_altering_as_regular {
+            &model.non_altering[..]
+        } else {
+            &[]
+        };
+        let current = self.state.snapshot();
+
+        let undo_ids = self
+            .history
+            .undo_iter()
+            .map(|e| *e.id())
+            .collect::<Vec<_>>();
+        let expected = listed_non_altering
+            .iter()
+            .rev()
+            .chain(model.undo.iter().rev())
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        if undo_ids != expected {
+            return Err(format!("undo entries {undo_ids:?} != {expected:?}"));
+        }
+        let redo_ids = self
+            .history
+            .redo_iter()
+            .map(|e| *e.id())
+            .collect::<Vec<_>>();
+        let expected = listed_non_altering
+            .iter()
+            .rev()
+            .chain(model.redo.iter().rev())
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        if redo_ids != expected {
+            return Err(format!("redo entries {redo_ids:?} != {expected:?}"));
+        }
+
+        let skip = listed_non_altering.len();
+        if let Some((_, before)) = listed_non_altering.first() {
+            let mut state = State::from_snapshot(&current);
+            for entry in self.history.undo_iter().take(skip) {
+                Self::check_entry_len(entry.len(), entry.iter().count())?;
+                state.apply(entry.iter());
+            }
+            state
+                .snapshot()
+                .check_eq(before, "state before non altering entries")?;
+        }
+        let mut state = State::from_snapshot(&current);
+        for (i, (entry, (_, before))) in self
+            .history
+            .undo_iter()
+            .skip(skip)
+            .zip(model.undo.iter().rev())
+            .enumerate()
+        {
+            Self::check_entry_len(entry.len(), entry.iter().count())?;
+            state.apply(entry.iter());
+            state
+                .snapshot()
+                .check_eq(before, &format!("state after {i}. listed undo entry"))?;
+        }
+        let mut state = State::from_snapshot(&current);
+        for (i, (entry, (_, after))) in self
+            .history
+            .redo_iter()
+            .skip(skip)
+            .zip(model.redo.iter().rev())
+            .enumerate()
+        {
+            Self::check_entry_len(entry.len(), entry.iter().count())?;
+            state.apply(entry.iter());
+            state
+                .snapshot()
+                .check_eq(after, &format!("state after {i}. listed redo entry"))?;
+        }
+        Ok(())
+    }
+
+    fn check_entry_len(len: usize, count: usize) -> Result<(), String> {
+        if len != count {
+            return Err(format!("entry len {len} != number of its ops {count}"));
+        }
+        Ok(())
+    }
+}
+
+/// Decodes options and steps from arbitrary bytes, used by the fuzz target.
+pub fn decode(data: &[u8]) -> (Options, Vec<Step>) {
+    let mut bytes = data.iter().copied();
+    let options = match bytes.next() {
+        Some(b) => Options {
+            treat_non_altering_as_regular: b & 1 == 1,
+            max_size: match (b >> 1) % 8 {
+                0 => None,
+                max => Some(max as usize),
+            },
+        },
+        None => Options::default(),
+    };
+    let mut steps = Vec::new();
+    while let Some(b) = bytes.next() {
+        let id = match (b >> 3) % 3 {
+            0 => Id::A,
+            1 => Id::B,
+            _ => Id::View,
+        };
+        let step = match b % 8 {
+            0 | 1 => Step::AddEntry(id, decode_actions(&mut bytes)),
+            2 | 3 => Step::OpenEntry(id, decode_actions(&mut bytes)),
+            4 => Step::CloseEntry(id),
+            5 => Step::Undo,
+            6 => Step::Redo,
+            _ if b >> 5 == 0 => Step::Clear,
+            _ => Step::Undo,
+        };
+        steps.push(step);
+    }
+    (options, steps)
+}
+
+fn decode_actions(bytes: &mut impl Iterator<Item = u8>) -> Vec<Action> {
+    let count = bytes.next().unwrap_or(0) % 4;
+    (0..count)
+        .filter_map(|_| {
+            let b = bytes.next()?;
+            let arg = bytes.next()?;
+            let action = match b % 7 {
+                0 => {
+                    let factors = [0.5, 2.0, 4.0, 0.25];
+                    let factor = factors[(arg % 4) as usize];
+                    match (arg >> 2) % 4 {
+                        0 => Action::StretchY(factor, YStretcherFlags::Top.into()),
+                        1 => Action::StretchY(factor, YStretcherFlags::Bottom.into()),
+                        2 => Action::StretchY(factor, BitFlags::all()),
+                        _ => Action::StretchY(-factor, BitFlags::all()),
+                    }
+                }
+                1 => Action::MoveSelectBy((arg % 7) as i8 - 3),
+                2 => Action::ToggleFunc(arg as usize),
+                3 => Action::Delete,
+                4 => Action::InsertPattern(
+                    (arg % 5) as i8 - 1,
+                    (0..arg % 4).map(|i| i as Y * 1.5 - 1.0).collect(),
+                ),
+                5 => Action::Other(TestOp::Add((arg % 5) as i64 - 2)),
+                _ => Action::Other(TestOp::Push(arg)),
+            };
+            Some(action)
+        })
+        .collect()
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::{AllAlter, NoOp};
+use plots::{
+    functions::function::{Func, FuncInsertMode},
+    graph::Graph,
+    history::{history_stack::shared_entry::ApplyOtherOp, History},
+    reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    shared_op::InsertPolicy,
+    types::point::{Point, X, Y},
+};
+
+fn func() -> Func {
+    let mut func = Func::new_from((0..6).map(|i| Point::new(i as X, i as Y)).collect());
+    func.change_selection(&(1.0..=4.0));
+    func
+}
+
+fn points_of(func: &Func) -> Vec<(X, Y)> {
+    func.points().iter().map(|p| (p.x, p.y)).collect()
+}
+
+#[test]
+fn overwritten_and_stretched_values_are_restored() {
+    let original = points_of(&func());
+
+    let mut f = func();
+    let old = f.overwrite(2.0, &[9.0, 8.0], FuncInsertMode::Values);
+    assert_eq!(&*old, [2.0, 3.0]);
+    let ys = f.points().iter().map(|p| p.y).collect::<Vec<_>>();
+    assert_eq!(ys, [0.0, 1.0, 9.0, 8.0, 4.0, 5.0]);
+    f.restore_overwritten(2.0, 2, old.iter().copied());
+    assert_eq!(points_of(&f), original);
+
+    // Past end of function, values are appended.
+    let mut f = func();
+    let old = f.overwrite(5.0, &[7.0, 7.0], FuncInsertMode::Values);
+    assert_eq!(&*old, [5.0]);
+    assert_eq!(f.points().len(), 7);
+    f.restore_overwritten(5.0, 2, old.iter().copied());
+    assert_eq!(points_of(&f), original);
+
+    // Points from 2 to end of selection make room for the inserted one.
+    let mut f = func();
+    let old = f.stretch_into_selection(2.0, &[9.0], FuncInsertMode::Values);
+    assert_eq!(&*old, [2.0, 3.0, 4.0]);
+    let ys = f.points().iter().map(|p| p.y).collect::<Vec<_>>();
+    assert_eq!(ys, [0.0, 1.0, 9.0, 2.5, 4.0, 5.0]);
+    f.restore_overwritten(2.0, old.len(), old.iter().copied());
+    assert_eq!(points_of(&f), original);
+    assert!(f
+        .stretch_into_selection(5.0, &[9.0], FuncInsertMode::Values)
+        .is_empty());
+}
+
+#[test]
+fn undo_restores_values_of_each_policy() {
+    for policy in [InsertPolicy::Overwrite, InsertPolicy::Stretch] {
+        let mut graph = Graph::new(vec![func(), func()]);
+        graph.change_selection(1.0..=4.0);
+        graph.set_locked(1, true);
+        let original = (0..2)
+            .map(|fi| points_of(graph.get_func(fi).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(graph.selection_len(), 4);
+        let mut graph = BasicReversibleGraph::new(graph, History::<(), NoOp, AllAlter>::new());
+
+        graph
+            .action(())
+            .insert_values(2.0, [[9.0, 8.0].into_iter()], policy);
+        let edited = points_of(graph.graph().get_func(0).unwrap());
+        assert_ne!(edited, original[0], "{policy:?}");
+        // x stays on the timeline.
+        let xs = edited.iter().map(|(x, _)| *x).collect::<Vec<_>>();
+        assert_eq!(xs, [0.0, 1.0, 2.0, 3.0, 4.0, 5.0], "{policy:?}");
+        assert_eq!(points_of(graph.graph().get_func(1).unwrap()), original[1]);
+
+        graph.undo(|_: ApplyOtherOp<()>| {});
+        let undone = (0..2)
+            .map(|fi| points_of(graph.graph().get_func(fi).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(undone, original, "{policy:?}");
+
+        graph.redo(|_: ApplyOtherOp<()>| {});
+        assert_eq!(points_of(graph.graph().get_func(0).unwrap()), edited);
+    }
+}
@@ -0,0 +1,85 @@
+use eframe::egui::{Key, KeyboardShortcut, Modifiers};
+use plots::ui::keymap::{Command, Keymap, KeymapError};
+
+#[test]
+fn parses_shortcuts() {
+    let shortcut = |modifiers, key| Some(KeyboardShortcut::new(modifiers, key));
+    assert_eq!(
+        Keymap::parse_shortcut("Ctrl+Shift+Z"),
+        shortcut(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z)
+    );
+    assert_eq!(
+        Keymap::parse_shortcut(" alt + left "),
+        shortcut(Modifiers::ALT, Key::ArrowLeft)
+    );
+    assert_eq!(
+        Keymap::parse_shortcut("Ctrl++"),
+        shortcut(Modifiers::COMMAND, Key::Plus)
+    );
+    assert_eq!(
+        Keymap::parse_shortcut("+"),
+        shortcut(Modifiers::NONE, Key::Plus)
+    );
+    assert_eq!(
+        Keymap::parse_shortcut("Delete"),
+        shortcut(Modifiers::NONE, Key::Delete)
+    );
+    assert_eq!(Keymap::parse_shortcut("Hyper+Z"), None);
+    assert_eq!(Keymap::parse_shortcut("Ctrl+Nothing"), None);
+}
+
+#[test]
+fn config_replaces_default_bindings() {
+    let keymap = Keymap::parse(
+        "# comment\n\
+         undo = Alt+Backspace, Ctrl+U\n\
+         \n\
+         zoom_in =\n",
+    )
+    .unwrap();
+    assert_eq!(
+        keymap.shortcuts(Command::Undo).collect::<Vec<_>>(),
+        vec![
+            &KeyboardShortcut::new(Modifiers::ALT, Key::Backspace),
+            &KeyboardShortcut::new(Modifiers::COMMAND, Key::U)
+        ]
+    );
+    assert_eq!(keymap.shortcuts(Command::ZoomIn).count(), 0);
+    assert_eq!(
+        keymap.shortcuts(Command::Redo).count(),
+        Keymap::default().shortcuts(Command::Redo).count()
+    );
+    for command in Command::ALL {
+        assert_eq!(Command::from_name(command.name()), Some(command));
+    }
+}
+
+#[test]
+fn every_command_has_its_own_default_shortcut() {
+    let keymap = Keymap::default();
+    let mut shortcuts: Vec<&KeyboardShortcut> = Vec::new();
+    for command in Command::ALL {
+        let bound = keymap.shortcuts(command).collect::<Vec<_>>();
+        assert!(!bound.is_empty(), "{} has no shortcut", command.name());
+        shortcuts.extend(bound);
+    }
+    for (i, shortcut) in shortcuts.iter().enumerate() {
+        assert!(!shortcuts[i + 1..].contains(shortcut), "{:?}", shortcut);
+    }
+}
+
+#[test]
+fn reports_config_errors() {
+    assert!(matches!(
+        Keymap::parse("undo Ctrl+Z"),
+        Err(KeymapError::InvalidLine(1, _))
+    ));
+    assert!(matches!(
+        Keymap::parse("\nfly = F"),
+        Err(KeymapError::UnknownCommand(2, _))
+    ));
+    assert!(matches!(
+        Keymap::parse("undo = Ctrl+"),
+        Err(KeymapError::InvalidShortcut(1, _))
+    ));
+}
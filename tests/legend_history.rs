@@ -0,0 +1,53 @@
+mod common;
+
+use common::{apply_legend, AllAlter, LegendOp, TestId};
+use eframe::egui::Color32;
+use plots::{
+    functions::function::Func,
+    graph::Graph,
+    history::History,
+    reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    types::point::Point,
+    widgets::legend_widget::{
+        simple_legend_entries::{SimpleLegendEntries, SimpleLegendEntry},
+        LegendEntries,
+    },
+};
+
+#[test]
+fn style_and_move_are_undone_and_redone() {
+    let funcs = (0..3)
+        .map(|_| Func::new_from(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)]))
+        .collect();
+    let mut graph = BasicReversibleGraph::new(
+        Graph::new(funcs),
+        History::<TestId, LegendOp, AllAlter>::new(),
+    );
+    let mut entries = ["a", "b", "c"]
+        .map(|name| SimpleLegendEntry::new(name.to_string(), Color32::RED, false))
+        .into_iter()
+        .collect::<Vec<_>>();
+    let mut order = vec![0, 1, 2];
+    {
+        let mut legend = SimpleLegendEntries::new(&mut graph, &mut entries, &mut order);
+        let mut style = legend.get_style(1).unwrap();
+        style.name = "B".to_string();
+        style.dashed = true;
+        legend.set_style(1, style);
+        legend.finish_edit();
+        legend.move_entry(0, 2);
+    }
+    assert_eq!((entries[1].name.as_str(), entries[1].dashed), ("B", true));
+    assert_eq!(order, [1, 2, 0]);
+
+    graph.undo(|op| apply_legend(op, &mut entries, &mut order));
+    assert_eq!(order, [0, 1, 2]);
+    assert_eq!(entries[1].name, "B");
+    graph.undo(|op| apply_legend(op, &mut entries, &mut order));
+    assert_eq!((entries[1].name.as_str(), entries[1].dashed), ("b", false));
+
+    graph.redo(|op| apply_legend(op, &mut entries, &mut order));
+    assert_eq!((entries[1].name.as_str(), entries[1].dashed), ("B", true));
+    graph.redo(|op| apply_legend(op, &mut entries, &mut order));
+    assert_eq!(order, [1, 2, 0]);
+}
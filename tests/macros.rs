@@ -0,0 +1,86 @@
+mod common;
+
+use common::{AllAlter, NoOp};
+use plots::{
+    functions::function::Func,
+    graph::Graph,
+    history::{history_stack::shared_entry::ApplyOtherOp, History},
+    reversible_graph::{basic_reversible_graph::BasicReversibleGraph, graph_macro::MacroStep},
+    shared_op::InsertPolicy,
+    types::point::{Point, X, Y},
+};
+
+type TestGraph = BasicReversibleGraph<(), NoOp, AllAlter>;
+
+fn zeros(selection: std::ops::RangeInclusive<X>) -> TestGraph {
+    let points = (0..10).map(|i| Point::new(i as X, 0.0)).collect();
+    let mut graph = Graph::new(vec![Func::new_from(points)]);
+    graph.change_selection(selection);
+    BasicReversibleGraph::new(graph, History::new())
+}
+
+fn values(graph: &TestGraph) -> Vec<Y> {
+    let points = graph.graph().get_func(0).unwrap().points();
+    points.iter().map(|p| p.y).collect()
+}
+
+/// Overwrites one value after start of selection, moves selection by two
+/// and overwrites value at its new start.
+fn record_edits(graph: &mut TestGraph) {
+    graph
+        .action(())
+        .insert_values(3.0, [[1.0].into_iter()], InsertPolicy::Overwrite);
+    graph.action(()).change_selection(4.0..=7.0);
+    graph
+        .action(())
+        .insert_values(4.0, [[2.0].into_iter()], InsertPolicy::Overwrite);
+}
+
+#[test]
+fn replayed_inserts_follow_moved_selection() {
+    let mut graph = zeros(2.0..=5.0);
+    record_edits(&mut graph);
+    let graph_macro = graph.record_macro::<()>("edits".to_string(), 0..3);
+    let offsets = graph_macro
+        .steps()
+        .iter()
+        .map(|step| match step {
+            MacroStep::InsertValues { offset, .. } => Some(*offset),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(offsets, vec![Some(1.0), None, Some(0.0)]);
+
+    let mut other = zeros(0.0..=3.0);
+    other.replay_macro((), &graph_macro);
+    assert_eq!(
+        values(&other),
+        vec![0.0, 1.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]
+    );
+    assert_eq!(other.graph().selection(), &(2.0..=5.0));
+
+    // Replay is one entry.
+    other.undo(|_: ApplyOtherOp<()>| {});
+    assert_eq!(values(&other), vec![0.0; 10]);
+    assert_eq!(other.graph().selection(), &(0.0..=3.0));
+}
+
+#[test]
+fn run_of_older_entries_is_recorded() {
+    let mut graph = zeros(2.0..=5.0);
+    record_edits(&mut graph);
+    // Skips the newest insert, offset of the older one is still relative to its selection.
+    let graph_macro = graph.record_macro::<()>("older".to_string(), 1..3);
+    assert_eq!(graph_macro.steps().len(), 2);
+    assert!(matches!(
+        graph_macro.steps()[0],
+        MacroStep::InsertValues { offset, .. } if offset == 1.0
+    ));
+    assert!(matches!(
+        graph_macro.steps()[1],
+        MacroStep::MoveSelectBy { start_by, end_by } if start_by == 2.0 && end_by == 2.0
+    ));
+
+    let only_move = graph.record_macro::<()>("move".to_string(), 1..2);
+    assert_eq!(only_move.steps().len(), 1);
+}
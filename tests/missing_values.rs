@@ -0,0 +1,79 @@
+use enumflags2::BitFlags;
+use plots::{
+    exporter::Exporter,
+    functions::function::{func_builder::FuncBuilder, Func, StretchY},
+    graph::Graph,
+    types::point::{Point, Y},
+};
+
+fn built(points: &[(f64, Y)]) -> Func {
+    let mut builder = FuncBuilder::new();
+    for (x, y) in points {
+        builder.add_point(&Point::new(*x, *y)).unwrap();
+    }
+    builder.into()
+}
+
+fn values(func: &Func) -> Vec<Y> {
+    func.points().iter().map(|p| p.y).collect()
+}
+
+fn same(a: &[Y], b: &[Y]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a, b)| a == b || a.is_nan() && b.is_nan())
+}
+
+#[test]
+fn missing_values_are_kept_and_skipped() {
+    let func = built(&[
+        (0.0, 1.0),
+        (1.0, Y::NAN),
+        (3.0, 3.0),
+        (6.0, 0.0),
+        (7.0, 9.0),
+    ]);
+    assert!(same(
+        &values(&func),
+        &[1.0, Y::NAN, Y::NAN, 3.0, 2.0, 1.0, 0.0]
+    ));
+    assert_eq!(func.value_range(), Some(0.0..=3.0));
+    assert_eq!(func.lines().len(), 2);
+    assert!(FuncBuilder::new()
+        .add_point(&Point::new(0.0, Y::INFINITY))
+        .is_err());
+
+    let mut graph = Graph::new(vec![func]);
+    graph.change_selection(0.0..=6.0);
+    let stretch = StretchY::new(2.0, BitFlags::all()).unwrap();
+    assert!(graph.stretch_y_with_factor(&stretch));
+    // Chord goes through the first and the last present values, 1 and 0.
+    let stretched = values(graph.get_func(0).unwrap());
+    let expected = [1.0, Y::NAN, Y::NAN, 5.5, 11.0 / 3.0, 11.0 / 6.0, 0.0];
+    assert!(stretched[1].is_nan() && stretched[2].is_nan());
+    for (y, expected) in stretched.iter().zip(expected).filter(|(y, _)| !y.is_nan()) {
+        assert!((y - expected).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn export_writes_missing_values_as_empty_cells() {
+    let func = |x: f64, values: &[Y]| {
+        Func::new_from(
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, y)| Point::new(x + i as f64, *y))
+                .collect(),
+        )
+    };
+    let a = func(0.0, &[1.0, Y::NAN, 3.0]);
+    let b = func(1.0, &[5.0, 6.0, 7.0]);
+    let mut out = Vec::new();
+    Exporter::write(&mut out, [("a", &a), ("b", &b)], None).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "x,a,b\n0,1,\n1,,5\n2,3,6\n3,,7\n"
+    );
+}
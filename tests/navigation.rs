@@ -0,0 +1,35 @@
+use egui_plot::PlotBounds;
+use plots::ui::navigation::Navigation;
+
+fn bounds(x: f64) -> PlotBounds {
+    PlotBounds::from_min_max([x, 0.0], [x + 10.0, 1.0])
+}
+
+#[test]
+fn continuous_navigation_is_one_step() {
+    let mut navigation = Navigation::default();
+    navigation.view_changed(bounds(0.0), true);
+    navigation.view_changed(bounds(1.0), true);
+    navigation.view_changed(bounds(2.0), false);
+    navigation.view_changed(bounds(2.0), true);
+    assert_eq!(navigation.back_len(), 2);
+
+    assert_eq!(navigation.back(bounds(3.0)), Some(bounds(2.0)));
+    assert_eq!(navigation.back(bounds(2.0)), Some(bounds(0.0)));
+    assert_eq!(navigation.back(bounds(0.0)), None);
+    assert_eq!(navigation.forward_len(), 2);
+    assert_eq!(navigation.forward(bounds(0.0)), Some(bounds(2.0)));
+    assert_eq!(navigation.forward(bounds(2.0)), Some(bounds(3.0)));
+    assert_eq!(navigation.forward(bounds(3.0)), None);
+}
+
+#[test]
+fn jump_clears_forward_views() {
+    let mut navigation = Navigation::default();
+    navigation.jump(bounds(0.0));
+    navigation.jump(bounds(1.0));
+    assert_eq!(navigation.back(bounds(2.0)), Some(bounds(1.0)));
+    navigation.jump(bounds(1.0));
+    assert_eq!(navigation.forward_len(), 0);
+    assert_eq!(navigation.back_len(), 2);
+}
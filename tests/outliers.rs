@@ -0,0 +1,62 @@
+use plots::{
+    functions::function::{
+        outliers::{self, OutlierDetector},
+        Func,
+    },
+    graph::Graph,
+    types::point::{Point, X, Y},
+    ui::outlier_review::OutlierReview,
+};
+
+fn points(values: &[Y]) -> Vec<Point> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, y)| Point::new(i as X, *y))
+        .collect()
+}
+
+#[test]
+fn detectors_find_spikes() {
+    let mut values = vec![1.0, 2.0, 1.0, 2.0, 1.0, 2.0, 1.0, 2.0, 1.0, 2.0];
+    values[4] = 30.0;
+    values[7] = Y::NAN;
+    let points = points(&values);
+    assert_eq!(
+        OutlierDetector::ZScore { threshold: 2.5 }.detect(&points),
+        vec![4]
+    );
+    assert_eq!(OutlierDetector::default().detect(&points), vec![4]);
+    assert_eq!(
+        OutlierDetector::RateOfChange { limit: 5.0 }.detect(&points),
+        vec![4]
+    );
+    assert!(OutlierDetector::default()
+        .detect(&self::points(&[3.0; 8]))
+        .is_empty());
+}
+
+#[test]
+fn accepted_outliers_are_interpolated() {
+    let spikes = [0.0, 1.0, 50.0, 60.0, 4.0];
+    assert_eq!(
+        outliers::interpolated(&points(&spikes), &[2, 3]),
+        vec![2.0, 3.0]
+    );
+
+    let values = [0.0, 1.0, 50.0, 3.0, 4.0, 5.0, 6.0, -40.0, 8.0, 9.0, 10.0];
+
+    let mut graph = Graph::new(vec![Func::new_from(points(&values))]);
+    graph.change_selection(0.0..=10.0);
+    let mut review = OutlierReview::default();
+    review.detector = OutlierDetector::RateOfChange { limit: 20.0 };
+    review.detect(&graph);
+    let xs = review.outliers().iter().map(|o| o.x).collect::<Vec<_>>();
+    assert_eq!(xs, vec![2.0, 7.0]);
+    assert_eq!(review.go_previous().map(|o| o.x), Some(7.0));
+    review.set_accepted(true);
+    assert_eq!(review.go_next().map(|o| o.x), Some(2.0));
+    assert_eq!(review.replacements(&graph), vec![(0, 7.0, vec![7.0])]);
+    review.accept_all();
+    assert_eq!(review.replacements(&graph).len(), 2);
+}
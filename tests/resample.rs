@@ -0,0 +1,111 @@
+mod common;
+
+use chrono::{NaiveDate, TimeDelta};
+use common::{AllAlter, NoOp};
+use plots::{
+    functions::function::{
+        resample::{Aggregation, Resample},
+        Func,
+    },
+    graph::Graph,
+    history::{history_stack::shared_entry::ApplyOtherOp, History},
+    reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    types::{
+        datetime_f64_mapper::{DateTimeF64Mapper, DateTimePrecision},
+        point::{Point, X, Y},
+    },
+};
+
+fn points(x: X, values: &[Y]) -> Vec<Point> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, y)| Point::new(x + i as X, *y))
+        .collect()
+}
+
+fn points_of(graph: &Graph, index: usize) -> Vec<(X, Y)> {
+    let points = graph.get_func(index).unwrap().points();
+    points.iter().map(|p| (p.x, p.y)).collect()
+}
+
+#[test]
+fn aggregates_intervals() {
+    let points = points(1.0, &[1.0, 2.0, 3.0, Y::NAN, 5.0, 6.0, Y::NAN, Y::NAN, 9.0]);
+    let values = |aggregation| {
+        let (x, values) = Resample::new(3.0, aggregation).values(&points).unwrap();
+        let values = values
+            .iter()
+            .map(|y| if y.is_nan() { None } else { Some(*y) });
+        (x, values.collect::<Vec<_>>())
+    };
+    // Intervals start at 0, 3, 6 and 9.
+    assert_eq!(
+        values(Aggregation::Mean),
+        (0.0, vec![Some(1.5), Some(4.0), Some(6.0), Some(9.0)])
+    );
+    assert_eq!(values(Aggregation::Min).1[1], Some(3.0));
+    assert_eq!(values(Aggregation::Max).1[1], Some(5.0));
+    assert_eq!(values(Aggregation::Sum).1[0], Some(3.0));
+    assert_eq!(values(Aggregation::Last).1[1], Some(5.0));
+
+    let shifted = Resample::new(3.0, Aggregation::Sum).with_offset(1.0);
+    assert_eq!(shifted.values(&points), Some((0.0, vec![6.0, 11.0, 9.0])));
+    let missing = Resample::new(2.0, Aggregation::Max).with_offset(1.0);
+    let missing = missing.values(&points).unwrap();
+    assert!(missing.1[3].is_nan());
+
+    let upsampled = Resample::new(0.5, Aggregation::Interpolate).values(&points[..3]);
+    assert_eq!(upsampled, Some((2.0, vec![1.0, 1.5, 2.0, 2.5, 3.0])));
+}
+
+#[test]
+fn resampling_is_undone_with_ids_and_selection() {
+    let mut graph = Graph::new(vec![
+        Func::new_from(points(0.0, &[0.0, 2.0, 4.0, 6.0, 8.0, 10.0])),
+        Func::new_from(points(2.0, &[1.0, 1.0, 3.0, 3.0])),
+    ]);
+    graph.set_locked(1, true);
+    graph.change_selection(2.0..=4.0);
+    let original = (0..2).map(|fi| points_of(&graph, fi)).collect::<Vec<_>>();
+    let mut graph = BasicReversibleGraph::new(graph, History::<(), NoOp, AllAlter>::new());
+
+    graph
+        .action(())
+        .resample(&Resample::new(2.0, Aggregation::Mean));
+    assert_eq!(
+        points_of(graph.graph(), 0),
+        vec![(0.0, 1.0), (1.0, 5.0), (2.0, 9.0)]
+    );
+    assert_eq!(points_of(graph.graph(), 1), vec![(1.0, 1.0), (2.0, 3.0)]);
+    assert_eq!(graph.graph().selection(), &(1.0..=2.0));
+    assert_eq!(graph.graph().func_id(1), Some(1));
+    assert_eq!(graph.graph().is_locked(1), Some(true));
+
+    graph.undo(|_: ApplyOtherOp<()>| {});
+    let undone = (0..2)
+        .map(|fi| points_of(graph.graph(), fi))
+        .collect::<Vec<_>>();
+    assert_eq!(undone, original);
+    assert_eq!(graph.graph().selection(), &(2.0..=4.0));
+    assert_eq!(graph.graph().is_locked(1), Some(true));
+}
+
+#[test]
+fn minutes_are_resampled_into_hours() {
+    let start = NaiveDate::from_ymd_opt(2024, 3, 1)
+        .unwrap()
+        .and_hms_opt(9, 30, 0)
+        .unwrap();
+    let minutes = DateTimeF64Mapper::new(start, DateTimePrecision::Minutes);
+    let hours = minutes.aligned(TimeDelta::hours(1));
+    assert_eq!(hours.step(), TimeDelta::hours(1));
+    assert_eq!(hours.f64_to_time(0.0), start - TimeDelta::minutes(30));
+
+    // 9:30 to 11:29, mean of each hour
+    let values = (0..120).map(|i| i as Y).collect::<Vec<_>>();
+    let resample = Resample::between(&minutes, &hours, Aggregation::Mean);
+    let (x, values) = resample.values(&points(0.0, &values)).unwrap();
+    assert_eq!(x, 0.0);
+    assert_eq!(values, vec![14.5, 59.5, 104.5]);
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use common::{AllAlter, NoOp};
+use plots::{
+    functions::function::{Func, StretchYBounds},
+    graph::Graph,
+    history::{history_stack::shared_entry::ApplyOtherOp, History},
+    reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    types::point::{Point, X, Y},
+};
+
+fn func(values: &[Y]) -> Func {
+    Func::new_from(
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, y)| Point::new(i as X, *y))
+            .collect(),
+    )
+}
+
+fn values(graph: &Graph, index: usize) -> Vec<Y> {
+    graph
+        .get_func(index)
+        .unwrap()
+        .points()
+        .iter()
+        .map(|p| p.y)
+        .collect()
+}
+
+fn graph() -> Graph {
+    let mut graph = Graph::new(vec![
+        func(&[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]),
+        func(&[10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0, 17.0]),
+    ]);
+    graph.change_selection(1.0..=2.0);
+    graph
+}
+
+#[test]
+fn ranges_are_merged_and_aggregated() {
+    let mut graph = graph();
+    graph.change_func_state(1, plots::graph::GraphFuncState::Inactive);
+    assert_eq!(graph.value_range(), Some(1.0..=2.0));
+
+    graph.add_selection_range(6.0..=6.0);
+    graph.add_selection_range(2.0..=3.0);
+    assert_eq!(graph.selection_ranges(), vec![1.0..=3.0, 6.0..=6.0]);
+    assert_eq!(graph.value_range(), Some(1.0..=6.0));
+    assert_eq!(graph.min(), Some(1.0));
+    assert_eq!(graph.max(), Some(6.0));
+
+    assert!(graph.remove_selection_range_at(6.0));
+    assert!(!graph.remove_selection_range_at(6.0));
+    assert_eq!(graph.value_range(), Some(1.0..=3.0));
+}
+
+#[test]
+fn deletes_all_ranges_as_one_entry() {
+    let mut graph = BasicReversibleGraph::new(graph(), History::<(), NoOp, AllAlter>::new());
+    graph.add_selection_range(5.0..=6.0);
+    graph
+        .action(())
+        .for_each_selection(|b, excluded| b.delete_excluding(excluded));
+
+    assert_eq!(values(graph.graph(), 0), vec![0.0, 3.0, 4.0, 7.0]);
+    assert_eq!(values(graph.graph(), 1), vec![10.0, 13.0, 14.0, 17.0]);
+    assert_eq!(graph.graph().selection(), &(1.0..=2.0));
+
+    graph.undo(|_: ApplyOtherOp<()>| {});
+    assert_eq!(
+        values(graph.graph(), 0),
+        (0..8).map(Y::from).collect::<Vec<_>>()
+    );
+    assert_eq!(graph.graph().selection(), &(1.0..=2.0));
+}
+
+#[test]
+fn function_with_own_selection_is_deleted_once() {
+    let mut graph = graph();
+    graph.set_func_selection(1, Some(3.0..=4.0));
+    let mut graph = BasicReversibleGraph::new(graph, History::<(), NoOp, AllAlter>::new());
+    graph.add_selection_range(5.0..=6.0);
+    graph
+        .action(())
+        .for_each_selection(|b, excluded| b.delete_excluding(excluded));
+
+    assert_eq!(values(graph.graph(), 0), vec![0.0, 3.0, 4.0, 7.0]);
+    assert_eq!(
+        values(graph.graph(), 1),
+        vec![10.0, 11.0, 12.0, 15.0, 16.0, 17.0]
+    );
+    assert_eq!(graph.graph().is_locked(1), Some(false));
+
+    graph.undo(|_: ApplyOtherOp<()>| {});
+    assert_eq!(
+        values(graph.graph(), 1),
+        (10..18).map(Y::from).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        values(graph.graph(), 0),
+        (0..8).map(Y::from).collect::<Vec<_>>()
+    );
+    graph.redo(|_: ApplyOtherOp<()>| {});
+    assert_eq!(
+        values(graph.graph(), 1),
+        vec![10.0, 11.0, 12.0, 15.0, 16.0, 17.0]
+    );
+}
+
+#[test]
+fn stretches_ranges_by_same_factor() {
+    let mut graph = Graph::new(vec![func(&[0.0, 0.0, 5.0, 0.0, 0.0, 0.0, 8.0, 0.0, 0.0])]);
+    graph.change_selection(1.0..=3.0);
+    graph.add_selection_range(5.0..=7.0);
+    let stretch = graph.stretch_y_for_ranges(&StretchYBounds::new_top(10.0));
+    let stretch = stretch.ok().filter(|s| s.stretches()).unwrap();
+    assert_eq!(graph.selection(), &(1.0..=3.0));
+    assert_eq!(graph.max(), Some(8.0));
+
+    for range in graph.selection_ranges() {
+        graph.change_selection(range);
+        graph.stretch_y_with_factor(&stretch);
+    }
+    assert_eq!(values(&graph, 0)[2], 6.25);
+    assert_eq!(values(&graph, 0)[6], 10.0);
+}
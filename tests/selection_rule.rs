@@ -0,0 +1,136 @@
+use chrono::{NaiveDate, NaiveTime};
+use plots::{
+    functions::function::Func,
+    graph::{Graph, GraphFuncState},
+    selection_rule::{SelectionRule, SelectionRuleError, SelectionRulesError},
+    types::{
+        datetime_f64_mapper::{DateTimeF64Mapper, DateTimePrecision},
+        point::{Point, X, Y},
+    },
+};
+
+fn func(values: &[Y]) -> Func {
+    Func::new_from(
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, y)| Point::new(i as X, *y))
+            .collect(),
+    )
+}
+
+fn time(hour: u32, min: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, min, 0).unwrap()
+}
+
+#[test]
+fn value_rules_use_first_active_function() {
+    let mut graph = Graph::new(vec![
+        func(&[0.0, 5.0, 6.0, 1.0, 7.0, 0.0]),
+        func(&[9.0, 9.0, 9.0, 9.0, 9.0, 9.0]),
+    ]);
+    assert_eq!(
+        SelectionRule::Above(4.0).ranges(&graph, None),
+        Ok(vec![1.0..=2.0, 4.0..=4.0])
+    );
+    assert_eq!(
+        SelectionRule::Below(1.0).ranges(&graph, None),
+        Ok(vec![0.0..=0.0, 5.0..=5.0])
+    );
+    assert_eq!(
+        SelectionRule::AroundMaxima(1).ranges(&graph, None),
+        Ok(vec![1.0..=5.0])
+    );
+    assert_eq!(
+        SelectionRule::AroundMaxima(0).ranges(&graph, None),
+        Ok(vec![2.0..=2.0, 4.0..=4.0])
+    );
+
+    graph.change_func_state(0, GraphFuncState::Inactive);
+    assert_eq!(
+        SelectionRule::Above(10.0).ranges(&graph, None),
+        Err(SelectionRuleError::NoMatch)
+    );
+    graph.change_func_state(1, GraphFuncState::Inactive);
+    assert_eq!(
+        SelectionRule::Above(0.0).ranges(&graph, None),
+        Err(SelectionRuleError::NoActiveFunction)
+    );
+}
+
+#[test]
+fn calendar_rules_use_mapper() {
+    // Hourly values from Friday 2024-03-01 00:00 till Monday 2024-03-04 23:00.
+    let graph = Graph::new(vec![func(&[0.0; 96])]);
+    let zero = NaiveDate::from_ymd_opt(2024, 3, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let mapper = DateTimeF64Mapper::new(zero, DateTimePrecision::Hours);
+
+    assert_eq!(
+        SelectionRule::Weekends.ranges(&graph, None),
+        Err(SelectionRuleError::NoTimeAxis)
+    );
+    assert_eq!(
+        SelectionRule::Weekends.ranges(&graph, Some(&mapper)),
+        Ok(vec![24.0..=71.0])
+    );
+    assert_eq!(
+        SelectionRule::DailyTime(time(22, 0), time(6, 0)).ranges(&graph, Some(&mapper)),
+        Ok(vec![
+            0.0..=5.0,
+            22.0..=29.0,
+            46.0..=53.0,
+            70.0..=77.0,
+            94.0..=95.0
+        ])
+    );
+    assert_eq!(
+        SelectionRule::DailyTime(time(12, 0), time(13, 0)).ranges(&graph, Some(&mapper)),
+        Ok(vec![12.0..=12.0, 36.0..=36.0, 60.0..=60.0, 84.0..=84.0])
+    );
+}
+
+#[test]
+fn calendar_rules_follow_x_of_points() {
+    // Half hourly values of Friday 2024-03-01 on hourly axis, as after resample.
+    let points = (0..48).map(|i| Point::new(i as X * 0.5, 0.0)).collect();
+    let graph = Graph::new(vec![Func::new_from(points)]);
+    let zero = NaiveDate::from_ymd_opt(2024, 3, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let mapper = DateTimeF64Mapper::new(zero, DateTimePrecision::Hours);
+    assert_eq!(
+        SelectionRule::DailyTime(time(12, 0), time(13, 0)).ranges(&graph, Some(&mapper)),
+        Ok(vec![12.0..=12.5])
+    );
+}
+
+#[test]
+fn rules_are_saved_as_lines() {
+    let rules = vec![
+        SelectionRule::Above(1.5),
+        SelectionRule::Below(-2.0),
+        SelectionRule::Weekends,
+        SelectionRule::DailyTime(time(22, 0), time(6, 30)),
+        SelectionRule::AroundMaxima(5),
+    ];
+    let text = rules
+        .iter()
+        .map(|r| r.to_line())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(
+        SelectionRule::parse_all(&format!("# saved\n\n{}", text)).unwrap(),
+        rules
+    );
+
+    assert!(matches!(
+        SelectionRule::parse_all("weekends\nabove x"),
+        Err(SelectionRulesError::InvalidLine(2, line)) if line == "above x"
+    ));
+    assert_eq!(SelectionRule::parse("daily 22:00"), None);
+    assert_eq!(SelectionRule::parse("weekends now"), None);
+}
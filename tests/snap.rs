@@ -0,0 +1,54 @@
+use chrono::NaiveDate;
+use plots::{
+    date_time_ctx::{CalendarUnit, DateTimeCtx},
+    importer::DateTimeInfo,
+    types::{
+        datetime_f64_mapper::{DateTimeF64Mapper, DateTimePrecision},
+        point::{Point, X},
+    },
+    ui::snap::Snap,
+};
+
+fn ctx() -> DateTimeCtx {
+    // Samples every minute from 2024-01-30 23:00.
+    let zero = NaiveDate::from_ymd_opt(2024, 1, 30)
+        .unwrap()
+        .and_hms_opt(23, 0, 0)
+        .unwrap();
+    DateTimeCtx::new(DateTimeInfo {
+        format: "%Y-%m-%d %H:%M".to_string(),
+        mapper: DateTimeF64Mapper::new(zero, DateTimePrecision::Minutes),
+    })
+}
+
+#[test]
+fn snaps_to_calendar_units() {
+    let ctx = ctx();
+    let snap = |x: X, unit| Snap::Calendar(unit).apply(x, Some(&ctx), &[]);
+    assert_eq!(snap(10.4, CalendarUnit::Minute), 10.0);
+    assert_eq!(snap(29.0, CalendarUnit::Hour), 0.0);
+    assert_eq!(snap(31.0, CalendarUnit::Hour), 60.0);
+    assert_eq!(snap(700.0, CalendarUnit::Day), 60.0);
+    assert_eq!(snap(800.0, CalendarUnit::Day), 1500.0);
+    // February starts one day and one hour after zero.
+    assert_eq!(snap(2000.0, CalendarUnit::Month), 1500.0);
+    assert_eq!(
+        Snap::Calendar(CalendarUnit::Hour).apply(31.0, None, &[]),
+        31.0
+    );
+}
+
+#[test]
+fn snaps_to_nearest_extremum() {
+    let points = [0.0, 2.0, 1.0, 1.0, 3.0, 3.0, 0.0]
+        .iter()
+        .enumerate()
+        .map(|(i, y)| Point::new(i as X, *y))
+        .collect::<Vec<_>>();
+    assert_eq!(Snap::Extrema.apply(0.2, None, &points), 1.0);
+    assert_eq!(Snap::Extrema.apply(2.4, None, &points), 2.0);
+    assert_eq!(Snap::Extrema.apply(5.9, None, &points), 4.0);
+    assert_eq!(Snap::Extrema.apply(5.9, None, &points[..2]), 5.9);
+    assert_eq!(Snap::Samples.apply(5.6, None, &points), 6.0);
+    assert_eq!(Snap::Off.apply(5.6, None, &points), 5.6);
+}
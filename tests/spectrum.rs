@@ -0,0 +1,80 @@
+use std::f64::consts::PI;
+
+use plots::{
+    functions::function::{
+        spectrum::{Filter, Spectrum, Window},
+        Func,
+    },
+    graph::Graph,
+    types::point::{Point, X, Y},
+};
+
+fn points(len: usize, f: impl Fn(X) -> Y) -> Vec<Point> {
+    (0..len).map(|i| Point::new(i as X, f(i as X))).collect()
+}
+
+#[test]
+fn peak_of_sinusoid_for_any_length() {
+    // 100 is not a power of two, 128 is.
+    for len in [100, 128] {
+        let values = points(len, |x| 5.0 + 2.0 * (2.0 * PI * 10.0 * x / len as X).sin());
+        let spectrum = Spectrum::new(&values, Window::Rectangular).unwrap();
+        assert_eq!(spectrum.frequencies.len(), len / 2 + 1);
+        assert!((spectrum.amplitudes[0] - 5.0).abs() < 1e-9);
+        let (frequency, amplitude) = spectrum.peak().unwrap();
+        assert!((frequency - 10.0 / len as X).abs() < 1e-12);
+        assert!((amplitude - 2.0).abs() < 1e-9);
+
+        let hann = Spectrum::new(&values, Window::Hann).unwrap();
+        assert_eq!(hann.peak().unwrap().0, frequency);
+    }
+    assert_eq!(Spectrum::new(&points(1, |_| 1.0), Window::Hann), None);
+}
+
+#[test]
+fn filters_remove_periodic_noise() {
+    // Noise is zero at both ends.
+    let len = 91;
+    let signal = |x: X| 0.1 * x + (2.0 * PI * x / 45.0).sin();
+    let noise = |x: X| 0.5 * (2.0 * PI * x / 5.0).sin();
+    let mut values = points(len, |x| signal(x) + noise(x));
+    values[10].y = Y::NAN;
+
+    let filters = [
+        Filter::LowPass { cutoff: 0.1 },
+        Filter::BandStop {
+            low: 0.12,
+            high: 0.3,
+        },
+    ];
+    for filter in filters {
+        let filtered = filter.values(&values);
+        assert!(filtered[10].is_nan());
+        for (i, y) in filtered.iter().enumerate().filter(|(i, _)| *i != 10) {
+            // Filtering is circular, so some noise stays near ends.
+            let tolerance = if (9..=81).contains(&i) { 0.05 } else { 0.25 };
+            assert!((y - signal(i as X)).abs() < tolerance, "{filter:?} at {i}");
+        }
+    }
+    assert_eq!(
+        Filter::LowPass { cutoff: 6.0 }.scaled(1.0 / 60.0),
+        Filter::LowPass { cutoff: 0.1 }
+    );
+}
+
+#[test]
+fn only_selection_of_editable_functions_is_filtered() {
+    let noisy = |x: X| if x as usize % 2 == 0 { 1.0 } else { -1.0 };
+    let mut graph = Graph::new(vec![
+        Func::new_from(points(8, noisy)),
+        Func::new_from(points(8, noisy)),
+    ]);
+    graph.set_locked(1, true);
+    graph.change_selection(2.0..=5.0);
+    let filtered = graph.filtered(&Filter::LowPass { cutoff: 0.4 });
+    assert_eq!(filtered.len(), 1);
+    let (fi, x, values) = &filtered[0];
+    assert_eq!((*fi, *x), (0, 2.0));
+    assert_eq!(values.len(), 4);
+    assert!(values.iter().all(|y| y.abs() < 1.0));
+}
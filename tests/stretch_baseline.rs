@@ -0,0 +1,71 @@
+use enumflags2::BitFlags;
+use plots::{
+    functions::function::{Func, StretchY, StretchYBounds},
+    graph::Graph,
+    stretchers::y_stretcher::{StretchBaseline, YStretcherFlags},
+    types::point::{Point, X, Y},
+};
+
+fn graph(values: &[Y]) -> Graph {
+    let mut graph = Graph::new(vec![Func::new_from(
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, y)| Point::new(i as X, *y))
+            .collect(),
+    )]);
+    graph.change_selection(0.0..=(values.len() - 1) as X);
+    graph
+}
+
+fn values(graph: &Graph) -> Vec<Y> {
+    let points = graph.get_func(0).unwrap().points();
+    points.iter().map(|p| p.y).collect()
+}
+
+#[test]
+fn baselines_of_selected_points() {
+    let points = [1.0, 4.0, 3.0, 8.0]
+        .iter()
+        .enumerate()
+        .map(|(i, y)| Point::new(i as X, *y))
+        .collect::<Vec<_>>();
+    let line = |baseline: StretchBaseline| {
+        let line = baseline.line(&points).unwrap();
+        (line.k(), line.q())
+    };
+    assert_eq!(line(StretchBaseline::Chord), (7.0 / 3.0, 1.0));
+    assert_eq!(line(StretchBaseline::Mean), (0.0, 4.0));
+    assert_eq!(line(StretchBaseline::Median), (0.0, 3.5));
+    assert_eq!(line(StretchBaseline::Min), (0.0, 1.0));
+    assert_eq!(line(StretchBaseline::Value(-2.0)), (0.0, -2.0));
+    let (k, q) = line(StretchBaseline::Regression);
+    assert!((k - 2.0).abs() < 1e-9 && (q - 1.0).abs() < 1e-9);
+    assert!(StretchBaseline::Regression.line(&points[..1]).is_none());
+}
+
+#[test]
+fn one_sided_stretch_around_mean_is_reverted() {
+    let original = [0.0, 4.0, 2.0, 6.0, 0.0, 3.0];
+    let mut graph = graph(&original);
+    let stretch = StretchY::new(2.0, BitFlags::from(YStretcherFlags::Top))
+        .unwrap()
+        .with_baseline(StretchBaseline::Mean);
+    // Mean moves with stretched points, so stretch is reverted around resolved line.
+    let stretch = graph.resolve_stretch(&stretch);
+    assert!(graph.stretch_y_with_factor(&stretch));
+    assert_eq!(values(&graph), vec![0.0, 5.5, 2.0, 9.5, 0.0, 3.5]);
+    assert_eq!(stretch.unresolved().baseline, StretchBaseline::Mean);
+
+    graph.stretch_y_with_factor(&stretch.inverted());
+    assert_eq!(values(&graph), original);
+}
+
+#[test]
+fn bounds_stretch_around_value() {
+    let mut graph = graph(&[1.0, 3.0, 2.0, 5.0, 1.0]);
+    let bounds = StretchYBounds::new_top(9.0).with_baseline(StretchBaseline::Value(1.0));
+    let stretch = graph.stretch_y(&bounds).ok().unwrap();
+    assert_eq!(stretch.factor, 2.0);
+    assert_eq!(values(&graph), vec![1.0, 5.0, 3.0, 9.0, 1.0]);
+}
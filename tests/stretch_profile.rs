@@ -0,0 +1,61 @@
+use enumflags2::BitFlags;
+use plots::{
+    functions::function::{Func, StretchY},
+    graph::Graph,
+    stretchers::stretch_profile::StretchProfile,
+    types::point::{Point, X, Y},
+};
+
+fn values(graph: &Graph) -> Vec<Y> {
+    let points = graph.get_func(0).unwrap().points();
+    points.iter().map(|p| p.y).collect()
+}
+
+#[test]
+fn profiles_weight_stretch_across_range() {
+    let approx = |a: f64, b: f64| (a - b).abs() < 1e-9;
+    assert!(approx(StretchProfile::Constant.weight(0.0), 1.0));
+    assert!(approx(StretchProfile::Cosine.weight(0.0), 0.0));
+    assert!(approx(StretchProfile::Cosine.weight(0.5), 1.0));
+    assert!(approx(StretchProfile::Cosine.weight(1.0), 0.0));
+    let triangle = StretchProfile::Triangular { peak: 0.25 };
+    assert!(approx(triangle.weight(0.25), 1.0));
+    assert!(approx(triangle.weight(0.625), 0.5));
+    assert!(approx(StretchProfile::Bell { width: 0.2 }.weight(0.5), 1.0));
+    assert!(approx(StretchProfile::EaseIn.weight(0.0), 0.0));
+    assert!(approx(StretchProfile::EaseIn.weight(1.0), 1.0));
+    assert!(approx(StretchProfile::EaseOut.weight(0.0), 1.0));
+    let envelope = StretchProfile::Envelope(vec![0.0, 1.0, 0.0].into());
+    assert!(approx(envelope.weight(0.25), 0.5));
+    assert!(approx(envelope.weight(0.5), 1.0));
+    assert!(approx(envelope.weight(2.0), 0.0));
+}
+
+#[test]
+fn profile_stretch_is_reverted_by_inverted_stretch() {
+    let original = [0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0];
+    let mut graph = Graph::new(vec![Func::new_from(
+        original
+            .iter()
+            .enumerate()
+            .map(|(i, y)| Point::new(i as X, *y))
+            .collect(),
+    )]);
+    graph.change_selection(0.0..=8.0);
+    let stretch = StretchY::new(4.0, BitFlags::all())
+        .and_then(|s| s.with_profile(StretchProfile::Cosine))
+        .unwrap();
+    assert!(graph.stretch_y_with_factor(&stretch));
+    let stretched = values(&graph);
+    assert!((stretched[4] - 4.0).abs() < 1e-9);
+    assert!(stretched[1] > 1.0 && stretched[1] < stretched[2]);
+    assert_eq!(stretched[0], 0.0);
+
+    graph.stretch_y_with_factor(&stretch.inverted());
+    for (y, expected) in values(&graph).iter().zip(original) {
+        assert!((y - expected).abs() < 1e-9);
+    }
+    assert!(StretchY::new(-2.0, BitFlags::all())
+        .and_then(|s| s.with_profile(StretchProfile::Cosine))
+        .is_none());
+}
@@ -0,0 +1,41 @@
+use plots::{
+    functions::function::{Func, StretchYBounds, StretchYBoundsError},
+    types::point::{Point, X, Y},
+};
+
+fn func(values: &[Y]) -> Func {
+    let mut func = Func::new_from(
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, y)| Point::new(i as X, *y))
+            .collect(),
+    );
+    func.change_selection(&(0.0..=(values.len() - 1) as X));
+    func
+}
+
+#[test]
+fn unreachable_targets_are_reported() {
+    let f = func(&[0.0, 3.0, 1.0, 4.0, 0.0]);
+    assert_eq!(
+        f.min_y_stretch_factor_for_bounds(&StretchYBounds::new_top(8.0))
+            .ok(),
+        Some(2.0)
+    );
+    // Chord goes through the first and the last point, which can not be moved.
+    let err = f
+        .min_y_stretch_factor_for_bounds(&StretchYBounds::new_top(-1.0))
+        .err();
+    assert_eq!(err, Some(StretchYBoundsError::BoundsOutOfRange));
+    assert_eq!(
+        err.unwrap().to_string(),
+        "target can not be reached by stretching"
+    );
+    let flat = func(&[1.0, 1.0, 1.0, 1.0]);
+    assert_eq!(
+        flat.min_y_stretch_factor_for_bounds(&StretchYBounds::new_bottom(0.0))
+            .err(),
+        Some(StretchYBoundsError::Unstretchable)
+    );
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use chrono::{NaiveDate, TimeDelta};
+use common::{AllAlter, NoOp};
+use plots::{
+    functions::function::{transform::Transform, Func},
+    graph::Graph,
+    history::{history_stack::shared_entry::ApplyOtherOp, History},
+    reversible_graph::basic_reversible_graph::BasicReversibleGraph,
+    types::{
+        datetime_f64_mapper::{DateTimeF64Mapper, DateTimePrecision},
+        point::{Point, X, Y},
+    },
+};
+
+fn points(values: &[Y]) -> Vec<Point> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, y)| Point::new(i as X, *y))
+        .collect()
+}
+
+#[test]
+fn power_and_energy_per_hour_of_minute_samples() {
+    let zero = NaiveDate::from_ymd_opt(2024, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let mapper = DateTimeF64Mapper::new(zero, DateTimePrecision::Minutes);
+    let x_unit = Transform::x_unit(&mapper, TimeDelta::hours(1));
+    assert_eq!(x_unit, 1.0 / 60.0);
+
+    // Meter reading in kWh each minute, power in kW
+    let energy = points(&[100.0, 101.0, 101.5, 101.5, 103.0]);
+    let power = Transform::Derivative.values(&energy, x_unit);
+    assert!(power[0].is_nan());
+    let rounded = power[1..].iter().map(|y| y.round()).collect::<Vec<_>>();
+    assert_eq!(rounded, vec![60.0, 30.0, 0.0, 90.0]);
+
+    let inverse = Transform::Derivative.inverse(&energy);
+    assert_eq!(inverse, Transform::Integral { initial: 100.0 });
+    let integral = inverse.values(&points(&power), x_unit);
+    for (y, expected) in integral.iter().zip(energy.iter().map(|p| p.y)) {
+        assert!((y - expected).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn cumulative_sum_skips_missing_values() {
+    let values = points(&[5.0, 1.0, Y::NAN, 2.0, 3.0]);
+    let sum = Transform::Integral { initial: 0.0 }.values(&values, 1.0);
+    assert_eq!(sum[..2], [0.0, 1.0]);
+    assert!(sum[2].is_nan());
+    assert_eq!(sum[3..], [3.0, 6.0]);
+
+    let derivative = Transform::Derivative.values(&values, 1.0);
+    assert_eq!(derivative[1], -4.0);
+    assert!(derivative[2].is_nan() && derivative[3].is_nan());
+    assert_eq!(derivative[4], 1.0);
+
+    let mut graph = Graph::new(vec![
+        Func::new_from(points(&[1.0, 2.0, 4.0])),
+        Func::new_from(points(&[0.0, 0.0, 1.0])),
+    ]);
+    graph.set_locked(1, true);
+    let transformed = graph.transformed(&Transform::Derivative, 1.0);
+    assert_eq!(transformed.len(), 1);
+    assert_eq!((transformed[0].0, transformed[0].1), (0, 0.0));
+    assert_eq!(transformed[0].2[1..], [1.0, 2.0]);
+}
+
+#[test]
+fn inverse_in_place_restores_first_value_lost_by_derivative() {
+    let graph = Graph::new(vec![
+        Func::new_from(points(&[100.0, 101.0, 103.0, 106.0])),
+        Func::new_from(points(&[5.0, 7.0, 4.0])),
+    ]);
+    let mut graph: BasicReversibleGraph<(), NoOp, AllAlter> =
+        BasicReversibleGraph::new(graph, History::new());
+    let values = |graph: &BasicReversibleGraph<(), NoOp, AllAlter>, fi: usize| {
+        let points = graph.graph().get_func(fi).unwrap().points();
+        points.iter().map(|p| p.y).collect::<Vec<_>>()
+    };
+
+    let inverse = graph.action(()).transform(&Transform::Derivative, 1.0);
+    assert_eq!(
+        inverse,
+        vec![
+            (0, Transform::Integral { initial: 100.0 }),
+            (1, Transform::Integral { initial: 5.0 }),
+        ]
+    );
+    assert!(values(&graph, 0)[0].is_nan());
+    assert_eq!(values(&graph, 0)[1..], [1.0, 2.0, 3.0]);
+
+    let derivative = graph.action(()).transform_funcs(&inverse, 1.0);
+    assert_eq!(values(&graph, 0), vec![100.0, 101.0, 103.0, 106.0]);
+    assert_eq!(values(&graph, 1), vec![5.0, 7.0, 4.0]);
+    assert_eq!(
+        derivative,
+        vec![(0, Transform::Derivative), (1, Transform::Derivative)]
+    );
+
+    // Each in place transform is undone on its own.
+    graph.undo(|_: ApplyOtherOp<()>| {});
+    assert!(values(&graph, 1)[0].is_nan());
+    graph.undo(|_: ApplyOtherOp<()>| {});
+    assert_eq!(values(&graph, 1), vec![5.0, 7.0, 4.0]);
+}